@@ -1,4 +1,7 @@
 use clap::Args;
+use reth_primitives::U256;
+use reth_rpc::eth::gas_oracle::GasPriceOracleConfig;
+use thiserror::Error;
 
 /// Parameters to configure Gas Price Oracle
 #[derive(Debug, Clone, Args, PartialEq, Eq)]
@@ -19,6 +22,60 @@ pub struct GasPriceOracleArgs {
     /// The percentile of gas prices to use for the estimate
     #[arg(long = "gpo.percentile", default_value = "60")]
     pub percentile: Option<u32>,
+
+    /// Skip blocks without transactions instead of counting them toward the sampled blocks
+    #[arg(long = "gpo.skipempty")]
+    pub skip_empty: bool,
+
+    /// Floor of the price suggested by gpo, in wei: lower estimates, e.g. when recent blocks are
+    /// nearly empty, are raised to it. Applied after `gpo.maxprice`, so it wins if higher.
+    #[arg(long = "gpo.minsuggested", default_value = "0")]
+    pub min_suggested: u64,
+}
+
+/// Errors returned by [`GasPriceOracleArgs::validate`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum GasPriceOracleArgsError {
+    /// The maximum price is below the ignore price, so the oracle would ignore every price it
+    /// could suggest.
+    #[error(
+        "--gpo.maxprice ({max_price}) must not be lower than --gpo.ignoreprice ({ignore_price})"
+    )]
+    MaxPriceBelowIgnorePrice {
+        /// Value of `--gpo.maxprice`.
+        max_price: u64,
+        /// Value of `--gpo.ignoreprice`.
+        ignore_price: u64,
+    },
+}
+
+impl GasPriceOracleArgs {
+    /// Checks that the parsed arguments are consistent with each other.
+    pub fn validate(&self) -> Result<(), GasPriceOracleArgsError> {
+        if let (Some(max_price), Some(ignore_price)) = (self.max_price, self.ignore_price) {
+            if max_price < ignore_price {
+                return Err(GasPriceOracleArgsError::MaxPriceBelowIgnorePrice {
+                    max_price,
+                    ignore_price,
+                })
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the [GasPriceOracleConfig] for the parsed arguments.
+    pub fn gas_price_oracle_config(&self) -> GasPriceOracleConfig {
+        GasPriceOracleConfig {
+            skip_empty_blocks: self.skip_empty,
+            min_suggested_price: U256::from(self.min_suggested),
+            ..GasPriceOracleConfig::new(
+                self.blocks,
+                self.ignore_price,
+                self.max_price,
+                self.percentile,
+            )
+        }
+    }
 }
 
 impl Default for GasPriceOracleArgs {
@@ -28,6 +85,8 @@ impl Default for GasPriceOracleArgs {
             ignore_price: Some(2),
             max_price: Some(500000000000),
             percentile: Some(60),
+            skip_empty: false,
+            min_suggested: 0,
         }
     }
 }
@@ -54,10 +113,69 @@ mod tests {
                 ignore_price: Some(2),
                 max_price: Some(500000000000),
                 percentile: Some(60),
+                skip_empty: false,
+                min_suggested: 0,
             }
         );
     }
 
+    #[test]
+    fn test_parse_gpo_min_suggested() {
+        let args =
+            CommandParser::<GasPriceOracleArgs>::parse_from(["reth", "--gpo.minsuggested", "1000"])
+                .args;
+        assert_eq!(args.min_suggested, 1000);
+        assert_eq!(args.gas_price_oracle_config().min_suggested_price, U256::from(1000));
+
+        let args = CommandParser::<GasPriceOracleArgs>::parse_from(["reth"]).args;
+        assert_eq!(args.min_suggested, 0);
+        assert_eq!(args.gas_price_oracle_config().min_suggested_price, U256::ZERO);
+    }
+
+    #[test]
+    fn test_parse_gpo_skip_empty() {
+        let args =
+            CommandParser::<GasPriceOracleArgs>::parse_from(["reth", "--gpo.skipempty"]).args;
+        assert!(args.skip_empty);
+        assert!(args.gas_price_oracle_config().skip_empty_blocks);
+
+        let args = CommandParser::<GasPriceOracleArgs>::parse_from(["reth"]).args;
+        assert!(!args.skip_empty);
+        assert!(!args.gas_price_oracle_config().skip_empty_blocks);
+    }
+
+    #[test]
+    fn test_validate_gpo_prices() {
+        let args = CommandParser::<GasPriceOracleArgs>::parse_from([
+            "reth",
+            "--gpo.maxprice",
+            "10",
+            "--gpo.ignoreprice",
+            "100",
+        ])
+        .args;
+        assert_eq!(
+            args.validate(),
+            Err(GasPriceOracleArgsError::MaxPriceBelowIgnorePrice {
+                max_price: 10,
+                ignore_price: 100
+            })
+        );
+
+        for (max_price, ignore_price) in [("100", "10"), ("100", "100")] {
+            let args = CommandParser::<GasPriceOracleArgs>::parse_from([
+                "reth",
+                "--gpo.maxprice",
+                max_price,
+                "--gpo.ignoreprice",
+                ignore_price,
+            ])
+            .args;
+            assert_eq!(args.validate(), Ok(()));
+        }
+        assert_eq!(GasPriceOracleArgs::default().validate(), Ok(()));
+    }
+
     #[test]
     fn gpo_args_default_sanity_test() {
         let default_args = GasPriceOracleArgs::default();
@@ -33,7 +33,7 @@ pub use stage_args::StageEnum;
 
 /// Gas price oracle related arguments
 mod gas_price_oracle_args;
-pub use gas_price_oracle_args::GasPriceOracleArgs;
+pub use gas_price_oracle_args::{GasPriceOracleArgs, GasPriceOracleArgsError};
 
 /// TxPoolArgs for configuring the transaction pool
 mod txpool_args;
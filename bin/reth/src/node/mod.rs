@@ -244,6 +244,8 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         // Does not do anything on windows.
         raise_fd_limit();
 
+        self.rpc.gas_price_oracle.validate()?;
+
         // get config
         let config = self.load_config()?;
 
@@ -30,6 +30,9 @@ pub enum DatabaseError {
     /// Failed to decode a key from a table.
     #[error("failed to decode a key from a table")]
     Decode,
+    /// Failed to decode a value encoded with an unsupported version, e.g. by a newer client.
+    #[error("failed to decode a value with unsupported encoding version {0}")]
+    UnsupportedVersion(u8),
     /// Failed to get database stats.
     #[error("failed to get stats ({0})")]
     Stats(i32),
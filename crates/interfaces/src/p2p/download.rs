@@ -10,4 +10,11 @@ pub trait DownloadClient: Send + Sync + Debug {
 
     /// Returns how many peers the network is currently connected to.
     fn num_connected_peers(&self) -> usize;
+
+    /// Returns `true` if the client can currently make progress on requests.
+    ///
+    /// By default, this is the case if there is at least one connected peer.
+    fn is_ready(&self) -> bool {
+        self.num_connected_peers() > 0
+    }
 }
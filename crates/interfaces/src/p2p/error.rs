@@ -155,6 +155,9 @@ pub enum DownloadError {
     /// Received more bodies than requested.
     #[error("received more bodies than requested: {0}")]
     TooManyBodies(GotExpected<usize>),
+    /// Received a body that was not requested, or the same body more than once.
+    #[error("received unexpected or duplicate body with roots hash {0}")]
+    UnexpectedBody(B256),
     /// Headers missing from the database.
     #[error("header missing from the database: {block_number}")]
     MissingHeader {
@@ -10,7 +10,7 @@ use std::{
 
 /// The header request struct to be sent to connected peers, which
 /// will proceed to ask them to stream the requested headers to us.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct HeadersRequest {
     /// The starting block
     pub start: BlockHashOrNumber,
@@ -1,5 +1,7 @@
 /// BlockHeader and BodyHeader DownloadRequest priority
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+///
+/// Priorities are ordered from [Priority::Normal] to [Priority::Critical].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
 pub enum Priority {
     /// Queued from the back for download requests.
     #[default]
@@ -7,6 +9,11 @@ pub enum Priority {
 
     /// Queued from the front for download requests.
     High,
+
+    /// Queued from the front for download requests, ahead of [Priority::High] requests.
+    ///
+    /// Reserved for requests on the critical path of live sync, e.g. the tip.
+    Critical,
 }
 
 impl Priority {
@@ -15,6 +22,11 @@ impl Priority {
         matches!(self, Priority::High)
     }
 
+    /// Returns `true` if this is [Priority::Critical]
+    pub fn is_critical(&self) -> bool {
+        matches!(self, Priority::Critical)
+    }
+
     /// Returns `true` if this is [Priority::Normal]
     pub fn is_normal(&self) -> bool {
         matches!(self, Priority::Normal)
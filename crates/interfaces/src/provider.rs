@@ -113,12 +113,40 @@ pub enum ProviderError {
     /// Snapshot file is not found at specified path.
     #[error("not able to find {0} snapshot file at {1}")]
     MissingSnapshotPath(SnapshotSegment, PathBuf),
-    /// Snapshot file is not found for requested block.
+    /// Snapshot file is not found for requested block, since it's above the highest snapshot of
+    /// the segment, or the segment has no snapshots.
     #[error("not able to find {0} snapshot file for block number {1}")]
     MissingSnapshotBlock(SnapshotSegment, BlockNumber),
-    /// Snapshot file is not found for requested transaction.
+    /// Snapshot file is not found for requested transaction, since it's above the highest
+    /// snapshot of the segment, or the segment has no snapshots.
     #[error("not able to find {0} snapshot file for transaction id {1}")]
     MissingSnapshotTx(SnapshotSegment, TxNumber),
+    /// Requested block is below the lowest snapshot of the segment, e.g. because it was pruned.
+    #[error("block number {1} is below the lowest {0} snapshot, starting at block number {2}")]
+    SnapshotBlockBelowLowest(SnapshotSegment, BlockNumber, BlockNumber),
+    /// Requested transaction is below the lowest snapshot of the segment, e.g. because it was
+    /// pruned.
+    #[error("transaction id {1} is below the lowest {0} snapshot, starting at transaction id {2}")]
+    SnapshotTxBelowLowest(SnapshotSegment, TxNumber, TxNumber),
+    /// Requested block is in a gap between two snapshots of the segment.
+    #[error("block number {1} is in a gap between {0} snapshots")]
+    SnapshotBlockInGap(SnapshotSegment, BlockNumber),
+    /// Requested transaction is in a gap between two snapshots of the segment.
+    #[error("transaction id {1} is in a gap between {0} snapshots")]
+    SnapshotTxInGap(SnapshotSegment, TxNumber),
+    /// Snapshot file does not match the checksum recorded with it, e.g. because it was corrupted
+    /// on disk.
+    #[error("{0} snapshot file at {1} does not match its checksum")]
+    SnapshotChecksumMismatch(SnapshotSegment, PathBuf),
+    /// Error encountered when interacting with the filesystem.
+    #[error("{0}")]
+    FsPathError(String),
+}
+
+impl From<reth_primitives::fs::FsPathError> for ProviderError {
+    fn from(err: reth_primitives::fs::FsPathError) -> Self {
+        ProviderError::FsPathError(err.to_string())
+    }
 }
 
 impl From<reth_nippy_jar::NippyJarError> for ProviderError {
@@ -9,10 +9,11 @@ use reth_interfaces::{
     },
 };
 use reth_primitives::{
-    BlockBody, GotExpected, PeerId, SealedBlock, SealedHeader, WithPeerId, B256,
+    keccak256, BlockBody, BlockBodyRoots, GotExpected, PeerId, SealedBlock, SealedHeader,
+    WithPeerId, B256,
 };
 use std::{
-    collections::VecDeque,
+    collections::{HashMap, VecDeque},
     mem,
     pin::Pin,
     sync::Arc,
@@ -32,12 +33,12 @@ use std::{
 /// request. If [BodiesRequestFuture] was initialized with all empty headers, no request will be
 /// dispatched and they will be immediately returned upon polling.
 ///
-/// NB: This assumes that peers respond with bodies in the order that they were requested.
-/// This is a reasonable assumption to make as that's [what Geth
-/// does](https://github.com/ethereum/go-ethereum/blob/f53ff0ff4a68ffc56004ab1d5cc244bcb64d3277/les/server_requests.go#L245).
-/// All errors regarding the response cause the peer to get penalized, meaning that adversaries
-/// that try to give us bodies that do not match the requested order are going to be penalized
-/// and eventually disconnected.
+/// NB: Peers usually respond with bodies in the order that they were requested, as that's [what
+/// Geth does](https://github.com/ethereum/go-ethereum/blob/f53ff0ff4a68ffc56004ab1d5cc244bcb64d3277/les/server_requests.go#L245).
+/// Responses are nonetheless re-matched to the requested headers with [match_bodies], so bodies
+/// received out of order are reassembled. Bodies that follow a requested body missing from the
+/// response are requested again. Bodies that were not requested or that were received more than
+/// once cause the peer to get penalized.
 pub(crate) struct BodiesRequestFuture<B: BodiesClient> {
     client: Arc<B>,
     consensus: Arc<dyn Consensus>,
@@ -141,6 +142,20 @@ where
             }))
         }
 
+        // Restore the requested order of the bodies
+        let requested = self
+            .pending_headers
+            .iter()
+            .filter(|h| !h.is_empty())
+            .take(request_len)
+            .map(|h| body_hash(&h.body_roots()))
+            .collect::<Vec<_>>();
+        let bodies = match_bodies(&requested, bodies).map_err(|err| match err {
+            MismatchError::Unexpected(hash) | MismatchError::Duplicate(hash) => {
+                DownloadError::UnexpectedBody(hash)
+            }
+        })?;
+
         // Buffer block responses
         self.try_buffer_blocks(bodies)?;
 
@@ -157,14 +172,18 @@ where
     /// Attempt to buffer body responses. Returns an error if body response fails validation.
     /// Every body preceeding the failed one will be buffered.
     ///
+    /// The bodies are paired with the non-empty pending headers by position, as returned by
+    /// [match_bodies]. Buffering stops at the first header without a body, the bodies that follow
+    /// it are requested again.
+    ///
     /// This method removes headers from the internal collection.
     /// If the response fails validation, then the header will be put back.
-    fn try_buffer_blocks(&mut self, bodies: Vec<BlockBody>) -> DownloadResult<()> {
+    fn try_buffer_blocks(&mut self, bodies: Vec<Option<BlockBody>>) -> DownloadResult<()> {
         let bodies_capacity = bodies.capacity();
-        let bodies_len = bodies.len();
+        let bodies_len = bodies.iter().flatten().count();
         let mut bodies = bodies.into_iter().peekable();
 
-        let mut total_size = bodies_capacity * mem::size_of::<BlockBody>();
+        let mut total_size = bodies_capacity * mem::size_of::<Option<BlockBody>>();
         while bodies.peek().is_some() {
             let next_header = match self.pending_headers.pop_front() {
                 Some(header) => header,
@@ -176,7 +195,11 @@ where
                 total_size += mem::size_of::<BlockBody>();
                 self.buffer.push(BlockResponse::Empty(next_header));
             } else {
-                let next_body = bodies.next().unwrap();
+                let Some(next_body) = bodies.next().flatten() else {
+                    // The body is missing from the response, put the header back
+                    self.pending_headers.push_front(next_header);
+                    break
+                };
 
                 // increment full block body metric
                 total_size += next_body.size();
@@ -245,6 +268,60 @@ where
     }
 }
 
+/// Error returned by [match_bodies] when a response does not match the requested bodies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub(crate) enum MismatchError {
+    /// The response contains a body that was not requested.
+    #[error("received unexpected body with roots hash {0}")]
+    Unexpected(B256),
+    /// The response contains the same body more than once.
+    #[error("received duplicate body with roots hash {0}")]
+    Duplicate(B256),
+}
+
+/// Returns the hash committing to all roots of a block body.
+///
+/// Used to correlate block bodies with the headers they were requested for, see
+/// [reth_primitives::Header::body_roots] and [BlockBody::calculate_roots].
+pub(crate) fn body_hash(roots: &BlockBodyRoots) -> B256 {
+    let mut buf = Vec::with_capacity(3 * B256::len_bytes());
+    buf.extend_from_slice(roots.tx_root.as_slice());
+    buf.extend_from_slice(roots.ommers_hash.as_slice());
+    if let Some(withdrawals_root) = roots.withdrawals_root {
+        buf.extend_from_slice(withdrawals_root.as_slice());
+    }
+    keccak256(buf)
+}
+
+/// Matches the received bodies against the requested body hashes (see [body_hash]).
+///
+/// Returns the body of every requested hash, at the same position as in `requested`. Requested
+/// hashes without a matching body are `None`, which is the case for partial responses.
+///
+/// Returns an error if a body was not requested, or if it was received more times than requested.
+pub(crate) fn match_bodies(
+    requested: &[B256],
+    got: Vec<BlockBody>,
+) -> Result<Vec<Option<BlockBody>>, MismatchError> {
+    // Positions of every requested hash, in reverse order, so they are popped in request order.
+    let mut positions = HashMap::<B256, Vec<usize>>::with_capacity(requested.len());
+    for (idx, hash) in requested.iter().enumerate().rev() {
+        positions.entry(*hash).or_default().push(idx);
+    }
+
+    let mut matched = std::iter::repeat_with(|| None).take(requested.len()).collect::<Vec<_>>();
+    for body in got {
+        let hash = body_hash(&body.calculate_roots());
+        let idx = match positions.get_mut(&hash) {
+            Some(idxs) => idxs.pop().ok_or(MismatchError::Duplicate(hash))?,
+            None => return Err(MismatchError::Unexpected(hash)),
+        };
+        matched[idx] = Some(body);
+    }
+
+    Ok(matched)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -252,6 +329,7 @@ mod tests {
         bodies::test_utils::zip_blocks,
         test_utils::{generate_bodies, TestBodiesClient},
     };
+    use rand::seq::SliceRandom;
     use reth_interfaces::{
         p2p::bodies::response::BlockResponse,
         test_utils::{generators, generators::random_header_range, TestConsensus},
@@ -259,6 +337,83 @@ mod tests {
     use reth_primitives::B256;
     use std::sync::Arc;
 
+    /// Returns the non-empty headers and their bodies, in order.
+    fn non_empty_bodies() -> (Vec<B256>, Vec<BlockBody>) {
+        let (headers, mut bodies) = generate_bodies(0..=19);
+        headers
+            .iter()
+            .filter(|h| !h.is_empty())
+            .map(|h| (body_hash(&h.body_roots()), bodies.remove(&h.hash()).unwrap()))
+            .unzip()
+    }
+
+    #[test]
+    fn match_bodies_reorders_shuffled() {
+        let (requested, bodies) = non_empty_bodies();
+        assert!(bodies.len() > 1);
+
+        let mut shuffled = bodies.clone();
+        shuffled.shuffle(&mut generators::rng());
+        assert_eq!(
+            match_bodies(&requested, shuffled).unwrap(),
+            bodies.iter().cloned().map(Some).collect::<Vec<_>>()
+        );
+
+        // Partial response
+        let mut partial = bodies[1..].to_vec();
+        partial.reverse();
+        let matched = match_bodies(&requested, partial).unwrap();
+        assert_eq!(matched[0], None);
+        assert_eq!(matched[1..], bodies[1..].iter().cloned().map(Some).collect::<Vec<_>>());
+    }
+
+    /// Check that bodies following a gap in a response are not paired with the wrong headers.
+    #[tokio::test]
+    async fn request_buffers_until_gap() {
+        let (headers, mut bodies) = generate_bodies(0..=19);
+        let non_empty = headers.iter().filter(|h| !h.is_empty()).collect::<Vec<_>>();
+        assert!(non_empty.len() > 3);
+
+        let client = Arc::new(TestBodiesClient::default().with_bodies(bodies.clone()));
+        let mut fut = BodiesRequestFuture::new(
+            client,
+            Arc::new(TestConsensus::default()),
+            BodyDownloaderMetrics::default(),
+        )
+        .with_headers(headers.clone());
+
+        // The third requested body is missing from the response
+        let response = [0, 1, 3]
+            .into_iter()
+            .map(|idx| bodies[&non_empty[idx].hash()].clone())
+            .rev()
+            .collect::<Vec<_>>();
+        fut.on_block_response(WithPeerId::new(PeerId::random(), response)).unwrap();
+
+        // Only the blocks before the gap are buffered
+        let gap = headers.iter().position(|h| h.hash() == non_empty[2].hash()).unwrap();
+        assert_eq!(fut.buffer, zip_blocks(headers[..gap].iter(), &mut bodies));
+        assert_eq!(fut.pending_headers.front(), Some(non_empty[2]));
+    }
+
+    #[test]
+    fn match_bodies_rejects_extraneous() {
+        let (requested, bodies) = non_empty_bodies();
+
+        // Body that was not requested
+        let extra = bodies[0].clone();
+        let extra_hash = body_hash(&extra.calculate_roots());
+        assert_eq!(
+            match_bodies(&requested[1..], bodies.clone()),
+            Err(MismatchError::Unexpected(extra_hash))
+        );
+
+        // Body received twice
+        let mut duplicated = bodies;
+        duplicated.push(extra);
+        assert_eq!(match_bodies(&requested, duplicated), Err(MismatchError::Duplicate(extra_hash)));
+    }
+
     /// Check if future returns empty bodies without dispathing any requests.
     #[tokio::test]
     async fn request_returns_empty_bodies() {
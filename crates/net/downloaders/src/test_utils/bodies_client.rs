@@ -1,6 +1,7 @@
 use reth_interfaces::p2p::{
     bodies::client::{BodiesClient, BodiesFut},
     download::DownloadClient,
+    error::RequestError,
     priority::Priority,
 };
 use reth_primitives::{BlockBody, PeerId, B256};
@@ -23,6 +24,9 @@ pub struct TestBodiesClient {
     max_batch_size: Option<usize>,
     times_requested: AtomicU64,
     empty_response_mod: Option<u64>,
+    delay: Option<Duration>,
+    request_timeout: Option<Duration>,
+    timeouts: Arc<AtomicU64>,
 }
 
 impl TestBodiesClient {
@@ -48,6 +52,24 @@ impl TestBodiesClient {
         self
     }
 
+    /// Delays every response by the given duration.
+    pub(crate) fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Fails requests with [RequestError::Timeout] once the given duration has elapsed, if the
+    /// configured delay exceeds it.
+    pub(crate) fn with_request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = Some(request_timeout);
+        self
+    }
+
+    /// Returns the number of requests that timed out.
+    pub(crate) fn timeout_count(&self) -> u64 {
+        self.timeouts.load(Ordering::Relaxed)
+    }
+
     pub(crate) fn times_requested(&self) -> u64 {
         self.times_requested.load(Ordering::Relaxed)
     }
@@ -86,6 +108,9 @@ impl BodiesClient for TestBodiesClient {
         let should_delay = self.should_delay;
         let bodies = self.bodies.clone();
         let max_batch_size = self.max_batch_size;
+        let delay = self.delay;
+        let request_timeout = self.request_timeout;
+        let timeouts = self.timeouts.clone();
 
         self.times_requested.fetch_add(1, Ordering::Relaxed);
         let should_respond_empty = self.should_respond_empty();
@@ -99,6 +124,15 @@ impl BodiesClient for TestBodiesClient {
                 tokio::time::sleep(Duration::from_millis((hashes[0][0] % 100) as u64)).await;
             }
 
+            if let Some(delay) = delay {
+                if let Some(request_timeout) = request_timeout.filter(|timeout| delay > *timeout) {
+                    tokio::time::sleep(request_timeout).await;
+                    timeouts.fetch_add(1, Ordering::Relaxed);
+                    return Err(RequestError::Timeout)
+                }
+                tokio::time::sleep(delay).await;
+            }
+
             let bodies = &mut *bodies.lock().await;
             Ok((
                 PeerId::default(),
@@ -116,3 +150,34 @@ impl BodiesClient for TestBodiesClient {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::generate_bodies;
+
+    #[tokio::test]
+    async fn test_timeout_count() {
+        let (headers, bodies) = generate_bodies(0..=9);
+        let hashes = headers.iter().map(|header| header.hash()).collect::<Vec<_>>();
+
+        let client = TestBodiesClient::default()
+            .with_bodies(bodies)
+            .with_delay(Duration::from_millis(50))
+            .with_request_timeout(Duration::from_millis(10));
+
+        assert_eq!(client.timeout_count(), 0);
+        for expected in 1..=2 {
+            let result = client.get_block_bodies(hashes.clone()).await;
+            assert!(matches!(result, Err(RequestError::Timeout)));
+            assert_eq!(client.timeout_count(), expected);
+        }
+        assert_eq!(client.times_requested(), 2);
+
+        // responses delayed within the timeout are served
+        let client = client.with_request_timeout(Duration::from_secs(1));
+        let response = client.get_block_bodies(hashes.clone()).await.unwrap();
+        assert_eq!(response.into_data().len(), hashes.len());
+        assert_eq!(client.timeout_count(), 2);
+    }
+}
@@ -40,11 +40,14 @@ use tracing::{trace, warn};
 /// For example, if the file contains 3 blocks, the file is assumed to be encoded as follows:
 /// rlp(block1) || rlp(block2) || rlp(block3)
 ///
+/// Files opened with [`FileClient::new_with_magic`] must additionally start with a magic header
+/// identifying the file as a reth block file.
+///
 /// Blocks are assumed to have populated transactions, so reading headers will also buffer
 /// transactions in memory for use in the bodies stage.
 ///
 /// This reads the entire file into memory, so it is not suitable for large files.
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct FileClient {
     /// The buffered headers retrieved when fetching new bodies.
     headers: HashMap<BlockNumber, Header>,
@@ -52,6 +55,9 @@ pub struct FileClient {
     /// A mapping between block hash and number.
     hash_to_number: HashMap<BlockHash, BlockNumber>,
 
+    /// A mapping between block number and hash.
+    number_to_hash: HashMap<BlockNumber, BlockHash>,
+
     /// The buffered bodies retrieved when fetching new headers.
     bodies: HashMap<BlockHash, BlockBody>,
 }
@@ -66,6 +72,30 @@ pub enum FileClientError {
     /// An error occurred when decoding blocks, headers, or rlp headers from the file.
     #[error(transparent)]
     Rlp(#[from] alloy_rlp::Error),
+
+    /// The file did not contain any complete block.
+    #[error("no blocks found in file")]
+    Empty,
+
+    /// A frame claimed a length larger than the configured maximum.
+    #[error("frame length {len} exceeds maximum of {max}")]
+    FrameTooLarge {
+        /// Length claimed by the frame, including its RLP header.
+        len: usize,
+        /// Maximum allowed frame length.
+        max: usize,
+    },
+
+    /// The file does not start with the block file magic header.
+    #[error("file does not start with the block file magic header")]
+    BadMagic,
+
+    /// The file contains the same block more than once.
+    #[error("block {hash} found more than once in file")]
+    DuplicateBlock {
+        /// Hash of the duplicated block.
+        hash: BlockHash,
+    },
 }
 
 impl FileClient {
@@ -75,8 +105,29 @@ impl FileClient {
         FileClient::from_file(file).await
     }
 
+    /// Create a new file client from a file path, expecting the file to start with the block file
+    /// magic header.
+    pub async fn new_with_magic<P: AsRef<Path>>(path: P) -> Result<Self, FileClientError> {
+        let file = File::open(path).await?;
+        FileClient::from_file_with_magic(file).await
+    }
+
     /// Initialize the [`FileClient`] with a file directly.
-    pub(crate) async fn from_file(mut file: File) -> Result<Self, FileClientError> {
+    pub(crate) async fn from_file(file: File) -> Result<Self, FileClientError> {
+        Self::from_file_with_codec(file, BlockFileCodec::default()).await
+    }
+
+    /// Initialize the [`FileClient`] with a file directly, expecting it to start with the block
+    /// file magic header.
+    pub(crate) async fn from_file_with_magic(file: File) -> Result<Self, FileClientError> {
+        Self::from_file_with_codec(file, BlockFileCodec::default().with_magic()).await
+    }
+
+    /// Initialize the [`FileClient`] with a file directly, decoding it with the given codec.
+    async fn from_file_with_codec(
+        mut file: File,
+        codec: BlockFileCodec,
+    ) -> Result<Self, FileClientError> {
         // get file len from metadata before reading
         let metadata = file.metadata().await?;
         let file_len = metadata.len();
@@ -87,18 +138,27 @@ impl FileClient {
 
         let mut headers = HashMap::new();
         let mut hash_to_number = HashMap::new();
+        let mut number_to_hash = HashMap::new();
         let mut bodies = HashMap::new();
 
-        // use with_capacity to make sure the internal buffer contains the entire file
-        let mut stream = FramedRead::with_capacity(&reader[..], BlockFileCodec, file_len as usize);
+        // use with_capacity to make sure the internal buffer contains the entire file, no frame can
+        // be larger than the file itself
+        let codec = codec.with_max_frame_len(file_len as usize);
+        let mut stream = FramedRead::with_capacity(&reader[..], codec, file_len as usize);
 
         while let Some(block_res) = stream.next().await {
             let block = block_res?;
             let block_hash = block.header.hash_slow();
 
+            // a valid chain has no duplicates, so a repeated block means the file is corrupt
+            if bodies.contains_key(&block_hash) {
+                return Err(FileClientError::DuplicateBlock { hash: block_hash })
+            }
+
             // add to the internal maps
             headers.insert(block.header.number, block.header.clone());
             hash_to_number.insert(block_hash, block.header.number);
+            number_to_hash.insert(block.header.number, block_hash);
             bodies.insert(
                 block_hash,
                 BlockBody {
@@ -109,9 +169,13 @@ impl FileClient {
             );
         }
 
+        if headers.is_empty() {
+            return Err(FileClientError::Empty)
+        }
+
         trace!(blocks = headers.len(), "Initialized file client");
 
-        Ok(Self { headers, hash_to_number, bodies })
+        Ok(Self { headers, hash_to_number, number_to_hash, bodies })
     }
 
     /// Get the tip hash of the chain.
@@ -119,6 +183,13 @@ impl FileClient {
         self.headers.get(&(self.headers.len() as u64)).map(|h| h.hash_slow())
     }
 
+    /// Returns the buffered block with the given number, if any.
+    pub fn block_by_number(&self, number: BlockNumber) -> Option<Block> {
+        let header = self.headers.get(&number)?;
+        let body = self.bodies.get(self.number_to_hash.get(&number)?)?;
+        Some(body.create_block(header.clone()))
+    }
+
     /// Returns the highest block number of this client has or `None` if empty
     pub fn max_block(&self) -> Option<u64> {
         self.headers.keys().max().copied()
@@ -142,6 +213,46 @@ impl FileClient {
         true
     }
 
+    /// Returns the buffered headers answering `request`, from its start block following its
+    /// direction, up to its limit.
+    ///
+    /// Like a peer would, falling requests stop at the genesis block, and the response stops at
+    /// the first header that isn't buffered. A start hash that isn't buffered yields no headers.
+    pub fn serve_headers(&self, request: &HeadersRequest) -> Vec<Header> {
+        let Some(start_num) = self.request_start(request.start) else { return Vec::new() };
+
+        Self::request_numbers(start_num, request)
+            .map_while(|number| self.headers.get(&number).cloned())
+            .collect()
+    }
+
+    /// Returns the number of the start block of a request, if known.
+    fn request_start(&self, start: BlockHashOrNumber) -> Option<BlockNumber> {
+        match start {
+            BlockHashOrNumber::Hash(hash) => self.hash_to_number.get(&hash).copied(),
+            BlockHashOrNumber::Number(num) => Some(num),
+        }
+    }
+
+    /// Returns the numbers of the blocks of a request starting at `start_num`, in the order they
+    /// are served.
+    fn request_numbers(
+        start_num: BlockNumber,
+        request: &HeadersRequest,
+    ) -> impl Iterator<Item = BlockNumber> {
+        match request.direction {
+            HeadersDirection::Rising => {
+                Either::Left(start_num..start_num.saturating_add(request.limit))
+            }
+            // stop at the genesis block, like a peer would
+            HeadersDirection::Falling => Either::Right(
+                (start_num.saturating_sub(request.limit.saturating_sub(1))..=start_num)
+                    .rev()
+                    .take(request.limit as usize),
+            ),
+        }
+    }
+
     /// Use the provided bodies as the file client's block body buffer.
     pub(crate) fn with_bodies(mut self, bodies: HashMap<BlockHash, BlockBody>) -> Self {
         self.bodies = bodies;
@@ -152,7 +263,9 @@ impl FileClient {
     pub(crate) fn with_headers(mut self, headers: HashMap<BlockNumber, Header>) -> Self {
         self.headers = headers;
         for (number, header) in &self.headers {
-            self.hash_to_number.insert(header.hash_slow(), *number);
+            let hash = header.hash_slow();
+            self.hash_to_number.insert(hash, *number);
+            self.number_to_hash.insert(*number, hash);
         }
         self
     }
@@ -167,41 +280,20 @@ impl HeadersClient for FileClient {
         _priority: Priority,
     ) -> Self::Output {
         // this just searches the buffer, and fails if it can't find the header
-        let mut headers = Vec::new();
         trace!(target: "downloaders::file", request=?request, "Getting headers");
 
-        let start_num = match request.start {
-            BlockHashOrNumber::Hash(hash) => match self.hash_to_number.get(&hash) {
-                Some(num) => *num,
-                None => {
-                    warn!(%hash, "Could not find starting block number for requested header hash");
-                    return Box::pin(async move { Err(RequestError::BadResponse) })
-                }
-            },
-            BlockHashOrNumber::Number(num) => num,
-        };
-
-        let range = if request.limit == 1 {
-            Either::Left(start_num..start_num + 1)
-        } else {
-            match request.direction {
-                HeadersDirection::Rising => Either::Left(start_num..start_num + request.limit),
-                HeadersDirection::Falling => {
-                    Either::Right((start_num - request.limit + 1..=start_num).rev())
-                }
-            }
+        let Some(start_num) = self.request_start(request.start) else {
+            warn!(
+                start=?request.start,
+                "Could not find starting block number for requested header hash"
+            );
+            return Box::pin(async move { Err(RequestError::BadResponse) })
         };
 
-        trace!(target: "downloaders::file", range=?range, "Getting headers with range");
-
-        for block_number in range {
-            match self.headers.get(&block_number).cloned() {
-                Some(header) => headers.push(header),
-                None => {
-                    warn!(number=%block_number, "Could not find header");
-                    return Box::pin(async move { Err(RequestError::BadResponse) })
-                }
-            }
+        let headers = self.serve_headers(&request);
+        if let Some(missing) = Self::request_numbers(start_num, &request).nth(headers.len()) {
+            warn!(number=%missing, "Could not find header");
+            return Box::pin(async move { Err(RequestError::BadResponse) })
         }
 
         Box::pin(async move { Ok((PeerId::default(), headers).into()) })
@@ -253,7 +345,7 @@ mod tests {
             test_utils::{create_raw_bodies, insert_headers, zip_blocks},
         },
         headers::{reverse_headers::ReverseHeadersDownloaderBuilder, test_utils::child_header},
-        test_utils::{generate_bodies, generate_bodies_file},
+        test_utils::{file_codec::BLOCK_FILE_MAGIC, generate_bodies, generate_bodies_file},
     };
     use alloy_rlp::Encodable;
     use assert_matches::assert_matches;
@@ -284,11 +376,7 @@ mod tests {
 
         insert_headers(db.db(), &headers);
 
-        // create an empty file
-        let file = tempfile::tempfile().unwrap();
-
-        let client =
-            Arc::new(FileClient::from_file(file.into()).await.unwrap().with_bodies(bodies.clone()));
+        let client = Arc::new(FileClient::default().with_bodies(bodies.clone()));
         let mut downloader = BodiesDownloaderBuilder::default().build(
             client.clone(),
             Arc::new(TestConsensus::default()),
@@ -311,15 +399,12 @@ mod tests {
         let p1 = child_header(&p2);
         let p0 = child_header(&p1);
 
-        let file = tempfile::tempfile().unwrap();
-        let client = Arc::new(FileClient::from_file(file.into()).await.unwrap().with_headers(
-            HashMap::from([
-                (0u64, p0.clone().unseal()),
-                (1, p1.clone().unseal()),
-                (2, p2.clone().unseal()),
-                (3, p3.clone().unseal()),
-            ]),
-        ));
+        let client = Arc::new(FileClient::default().with_headers(HashMap::from([
+            (0u64, p0.clone().unseal()),
+            (1, p1.clone().unseal()),
+            (2, p2.clone().unseal()),
+            (3, p3.clone().unseal()),
+        ])));
 
         let mut downloader = ReverseHeadersDownloaderBuilder::default()
             .stream_batch_size(3)
@@ -359,6 +444,119 @@ mod tests {
         assert_eq!(downloaded_headers, headers[1..]);
     }
 
+    #[tokio::test]
+    async fn test_get_headers_falling() {
+        let (file, headers, _) = generate_bodies_file(0..=19).await;
+        let client = FileClient::from_file(file).await.unwrap();
+
+        // by hash, from the tip
+        let tip = headers.last().unwrap();
+        let request = HeadersRequest {
+            start: tip.hash().into(),
+            limit: 5,
+            direction: HeadersDirection::Falling,
+        };
+        let (_, response) = client.get_headers(request).await.unwrap().split();
+        let expected = headers[15..].iter().rev().map(|h| h.header.clone()).collect::<Vec<_>>();
+        assert_eq!(response, expected);
+
+        // by number, past the genesis block
+        let request =
+            HeadersRequest { start: 3u64.into(), limit: 10, direction: HeadersDirection::Falling };
+        let (_, response) = client.get_headers(request).await.unwrap().split();
+        let expected = headers[..=3].iter().rev().map(|h| h.header.clone()).collect::<Vec<_>>();
+        assert_eq!(response, expected);
+    }
+
+    #[test]
+    fn test_request_numbers_falling_bounds() {
+        let request = |limit| HeadersRequest {
+            start: u64::MAX.into(),
+            limit,
+            direction: HeadersDirection::Falling,
+        };
+        let numbers = |start, limit| {
+            FileClient::request_numbers(start, &request(limit)).collect::<Vec<BlockNumber>>()
+        };
+
+        assert_eq!(numbers(u64::MAX, 2), vec![u64::MAX, u64::MAX - 1]);
+        assert_eq!(numbers(u64::MAX, 1), vec![u64::MAX]);
+        assert!(numbers(u64::MAX, 0).is_empty());
+        assert_eq!(numbers(1, 5), vec![1, 0]);
+    }
+
+    #[tokio::test]
+    async fn test_block_by_number() {
+        let (file, headers, bodies) = generate_bodies_file(0..=19).await;
+        let client = FileClient::from_file(file).await.unwrap();
+
+        let header = &headers[10];
+        assert_eq!(
+            client.block_by_number(10),
+            Some(bodies[&header.hash()].create_block(header.header.clone()))
+        );
+        assert_eq!(client.block_by_number(20), None);
+    }
+
+    #[tokio::test]
+    async fn test_serve_headers() {
+        let (file, headers, _) = generate_bodies_file(0..=19).await;
+        let client = FileClient::from_file(file).await.unwrap();
+        let headers = headers.into_iter().map(SealedHeader::unseal).collect::<Vec<_>>();
+
+        // forward, by hash
+        let request = HeadersRequest {
+            start: headers[5].hash_slow().into(),
+            limit: 3,
+            direction: HeadersDirection::Rising,
+        };
+        assert_eq!(client.serve_headers(&request), headers[5..8]);
+
+        // reverse, by number
+        let request =
+            HeadersRequest { start: 10u64.into(), limit: 4, direction: HeadersDirection::Falling };
+        assert_eq!(
+            client.serve_headers(&request),
+            headers[7..=10].iter().rev().cloned().collect::<Vec<_>>()
+        );
+
+        // the limit is truncated at the last buffered header, and at the genesis block
+        let request =
+            HeadersRequest { start: 17u64.into(), limit: 10, direction: HeadersDirection::Rising };
+        assert_eq!(client.serve_headers(&request), headers[17..]);
+        let request =
+            HeadersRequest { start: 2u64.into(), limit: 10, direction: HeadersDirection::Falling };
+        assert_eq!(
+            client.serve_headers(&request),
+            headers[..=2].iter().rev().cloned().collect::<Vec<_>>()
+        );
+
+        // out of range starts
+        let request =
+            HeadersRequest { start: 20u64.into(), limit: 5, direction: HeadersDirection::Rising };
+        assert!(client.serve_headers(&request).is_empty());
+        let request = HeadersRequest {
+            start: B256::random().into(),
+            limit: 5,
+            direction: HeadersDirection::Falling,
+        };
+        assert!(client.serve_headers(&request).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_generate_bodies_file_range() {
+        let (file, headers, bodies) = generate_bodies_file(5..=8).await;
+        assert_eq!(headers.iter().map(|h| h.number).collect::<Vec<_>>(), vec![5, 6, 7, 8]);
+        assert_eq!(bodies.len(), 4);
+
+        // only the requested blocks are written to the file
+        let client = FileClient::from_file(file).await.unwrap();
+        assert_eq!(client.headers.len(), 4);
+        assert_eq!(client.bodies.len(), 4);
+        assert_eq!(client.max_block(), Some(8));
+        assert!(client.has_canonical_blocks());
+    }
+
     #[tokio::test]
     async fn test_download_bodies_from_file() {
         // Generate some random blocks
@@ -383,4 +581,54 @@ mod tests {
             Some(Ok(res)) => assert_eq!(res, zip_blocks(headers.iter(), &mut bodies))
         );
     }
+
+    #[tokio::test]
+    async fn test_empty_file() {
+        let file = tempfile::tempfile().unwrap();
+        assert_matches!(FileClient::from_file(file.into()).await, Err(FileClientError::Empty));
+    }
+
+    #[tokio::test]
+    async fn test_file_magic() {
+        let (mut file, headers, _) = generate_bodies_file(0..=4).await;
+        let mut blocks = vec![];
+        file.read_to_end(&mut blocks).await.unwrap();
+
+        // a file with the magic header
+        let mut file: File = tempfile::tempfile().unwrap().into();
+        file.write_all(BLOCK_FILE_MAGIC).await.unwrap();
+        file.write_all(&blocks).await.unwrap();
+        file.seek(SeekFrom::Start(0)).await.unwrap();
+        let client = FileClient::from_file_with_magic(file).await.unwrap();
+        assert_eq!(client.headers.len(), headers.len());
+
+        // a file with a different magic header, and a legacy file without one
+        for magic in [&b"RETHBLK\x02"[..], &[]] {
+            let mut file: File = tempfile::tempfile().unwrap().into();
+            file.write_all(magic).await.unwrap();
+            file.write_all(&blocks).await.unwrap();
+            file.seek(SeekFrom::Start(0)).await.unwrap();
+            assert_matches!(
+                FileClient::from_file_with_magic(file).await,
+                Err(FileClientError::BadMagic)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_block() {
+        let (mut file, headers, _) = generate_bodies_file(0..=4).await;
+        let mut blocks = vec![];
+        file.read_to_end(&mut blocks).await.unwrap();
+
+        // the whole chain written twice, so the first block is the first duplicate
+        let mut file: File = tempfile::tempfile().unwrap().into();
+        file.write_all(&blocks).await.unwrap();
+        file.write_all(&blocks).await.unwrap();
+        file.seek(SeekFrom::Start(0)).await.unwrap();
+        assert_matches!(
+            FileClient::from_file(file).await,
+            Err(FileClientError::DuplicateBlock { hash }) => assert_eq!(hash, headers[0].hash())
+        );
+    }
 }
@@ -7,6 +7,10 @@ use reth_primitives::{
 };
 use tokio_util::codec::{Decoder, Encoder};
 
+/// Magic and format version at the start of a block file written with
+/// [`with_magic`](BlockFileCodec::with_magic).
+pub(crate) const BLOCK_FILE_MAGIC: &[u8; 8] = b"RETHBLK\x01";
+
 /// Codec for reading raw block bodies from a file.
 ///
 /// If using with [`FramedRead`](tokio_util::codec::FramedRead), the user should make sure the
@@ -20,7 +24,46 @@ use tokio_util::codec::{Decoder, Encoder};
 ///
 /// It's recommended to use [`with_capacity`](tokio_util::codec::FramedRead::with_capacity) to set
 /// the capacity of the framed reader to the size of the file.
-pub(crate) struct BlockFileCodec;
+///
+/// The length of a frame is not capped by default. Use
+/// [`with_max_frame_len`](BlockFileCodec::with_max_frame_len) when decoding untrusted files, so
+/// that a corrupt length prefix is rejected before the block is decoded.
+///
+/// By default the stream only consists of blocks. Use [`with_magic`](BlockFileCodec::with_magic)
+/// to write and expect [BLOCK_FILE_MAGIC] at the start of the stream.
+#[derive(Debug, Default)]
+pub(crate) struct BlockFileCodec {
+    /// Maximum length of a frame, including its RLP header.
+    max_frame_len: Option<usize>,
+    /// Whether the stream starts with [BLOCK_FILE_MAGIC].
+    magic: bool,
+    /// Whether the magic was already written or read.
+    magic_done: bool,
+}
+
+impl BlockFileCodec {
+    /// Sets the maximum length of a frame, including its RLP header.
+    ///
+    /// Frames claiming a larger length are rejected with [FileClientError::FrameTooLarge].
+    pub(crate) fn with_max_frame_len(mut self, max_frame_len: usize) -> Self {
+        self.max_frame_len = Some(max_frame_len);
+        self
+    }
+
+    /// Writes [BLOCK_FILE_MAGIC] before the first block, and expects it before the first block
+    /// when decoding.
+    ///
+    /// A stream starting with anything else is rejected with [FileClientError::BadMagic].
+    pub(crate) fn with_magic(mut self) -> Self {
+        self.magic = true;
+        self
+    }
+
+    /// Returns `true` if the magic must still be written or read.
+    fn magic_pending(&self) -> bool {
+        self.magic && !self.magic_done
+    }
+}
 
 impl Decoder for BlockFileCodec {
     type Item = Block;
@@ -30,6 +73,25 @@ impl Decoder for BlockFileCodec {
         if src.is_empty() {
             return Ok(None)
         }
+        if self.magic_pending() {
+            let len = src.len().min(BLOCK_FILE_MAGIC.len());
+            if src[..len] != BLOCK_FILE_MAGIC[..len] {
+                return Err(FileClientError::BadMagic)
+            }
+            if len < BLOCK_FILE_MAGIC.len() {
+                return Ok(None)
+            }
+            src.advance(len);
+            self.magic_done = true;
+            if src.is_empty() {
+                return Ok(None)
+            }
+        }
+        if let Some((len, max)) = self.max_frame_len.zip(frame_len(src)) {
+            if len > max {
+                return Err(FileClientError::FrameTooLarge { len, max })
+            }
+        }
         let mut buf_slice = &mut src.as_ref();
         let body = Block::decode(buf_slice)?;
         src.advance(src.len() - buf_slice.len());
@@ -37,11 +99,97 @@ impl Decoder for BlockFileCodec {
     }
 }
 
+/// Returns the length of the frame at the start of `buf`, including its RLP header, as claimed by
+/// the header.
+///
+/// This only inspects the length prefix, so the payload does not need to be buffered. Returns
+/// `None` if the header is incomplete.
+fn frame_len(buf: &[u8]) -> Option<usize> {
+    let (&first, rest) = buf.split_first()?;
+    let (header_len, payload_len) = match first {
+        0x00..=0x7f => (0, 1),
+        0x80..=0xb7 => (1, (first - 0x80) as usize),
+        0xc0..=0xf7 => (1, (first - 0xc0) as usize),
+        0xb8..=0xbf | 0xf8..=0xff => {
+            let len_of_len = (first - if first >= 0xf8 { 0xf7 } else { 0xb7 }) as usize;
+            let payload_len = rest
+                .get(..len_of_len)?
+                .iter()
+                .fold(0usize, |len, byte| len.saturating_mul(256).saturating_add(*byte as usize));
+            (1 + len_of_len, payload_len)
+        }
+    };
+    Some(payload_len.saturating_add(header_len))
+}
+
 impl Encoder<Block> for BlockFileCodec {
     type Error = FileClientError;
 
     fn encode(&mut self, item: Block, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        if self.magic_pending() {
+            dst.extend_from_slice(BLOCK_FILE_MAGIC);
+            self.magic_done = true;
+        }
         item.encode(dst);
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+
+    #[test]
+    fn rejects_oversized_frame() {
+        // list header claiming a payload of 0xffffff bytes, followed by nothing
+        let mut src = BytesMut::from(&[0xfa, 0xff, 0xff, 0xff][..]);
+
+        let mut codec = BlockFileCodec::default().with_max_frame_len(1024);
+        assert_matches!(
+            codec.decode(&mut src),
+            Err(FileClientError::FrameTooLarge { len: 0x1000003, max: 1024 })
+        );
+        // nothing was consumed
+        assert_eq!(src.len(), 4);
+
+        // without a cap the frame is only rejected once decoding runs out of input
+        let mut codec = BlockFileCodec::default();
+        assert_matches!(
+            codec.decode(&mut src),
+            Err(FileClientError::Rlp(alloy_rlp::Error::InputTooShort))
+        );
+    }
+
+    #[test]
+    fn magic_roundtrip() {
+        let block = Block::default();
+        let mut dst = BytesMut::new();
+        let mut codec = BlockFileCodec::default().with_magic();
+        codec.encode(block.clone(), &mut dst).unwrap();
+        codec.encode(block.clone(), &mut dst).unwrap();
+
+        // the magic is only written once
+        assert_eq!(&dst[..BLOCK_FILE_MAGIC.len()], BLOCK_FILE_MAGIC);
+        assert_eq!(dst.len(), BLOCK_FILE_MAGIC.len() + 2 * block.length());
+
+        let mut codec = BlockFileCodec::default().with_magic();
+        assert_eq!(codec.decode(&mut dst).unwrap(), Some(block.clone()));
+        assert_eq!(codec.decode(&mut dst).unwrap(), Some(block));
+        assert_eq!(codec.decode(&mut dst).unwrap(), None);
+    }
+
+    #[test]
+    fn rejects_bad_magic() {
+        // a block without the magic
+        let mut src = BytesMut::new();
+        Block::default().encode(&mut src);
+        let mut codec = BlockFileCodec::default().with_magic();
+        assert_matches!(codec.decode(&mut src), Err(FileClientError::BadMagic));
+
+        // an incomplete magic waits for more input
+        let mut src = BytesMut::from(&BLOCK_FILE_MAGIC[..4]);
+        assert_matches!(codec.decode(&mut src), Ok(None));
+        assert_eq!(src.len(), 4);
+    }
+}
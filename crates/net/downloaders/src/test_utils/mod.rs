@@ -51,13 +51,13 @@ pub(crate) fn generate_bodies(
 /// Generate a set of bodies, write them to a temporary file, and return the file along with the
 /// bodies and corresponding block hashes
 pub(crate) async fn generate_bodies_file(
-    rng: RangeInclusive<u64>,
+    range: RangeInclusive<u64>,
 ) -> (tokio::fs::File, Vec<SealedHeader>, HashMap<B256, BlockBody>) {
-    let (headers, mut bodies) = generate_bodies(0..=19);
+    let (headers, mut bodies) = generate_bodies(range);
     let raw_block_bodies = create_raw_bodies(headers.clone().iter(), &mut bodies.clone());
 
     let mut file: File = tempfile::tempfile().unwrap().into();
-    let mut writer = FramedWrite::new(file, BlockFileCodec);
+    let mut writer = FramedWrite::new(file, BlockFileCodec::default());
 
     // rlp encode one after the other
     for block in raw_block_bodies {
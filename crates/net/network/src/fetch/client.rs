@@ -1,8 +1,13 @@
 //! A client implementation that can interact with the network and download data.
 
-use crate::{fetch::DownloadRequest, flattened_response::FlattenedResponse, peers::PeersHandle};
-use futures::{future, future::Either};
+use crate::{
+    fetch::{DownloadRequest, FetcherCounters, PeerSelectionPolicy},
+    flattened_response::FlattenedResponse,
+    peers::PeersHandle,
+};
+use futures::{future, Future};
 
+use parking_lot::Mutex;
 use reth_interfaces::p2p::{
     bodies::client::{BodiesClient, BodiesFut},
     download::DownloadClient,
@@ -11,12 +16,17 @@ use reth_interfaces::p2p::{
     priority::Priority,
 };
 use reth_network_api::ReputationChangeKind;
-use reth_primitives::{Header, PeerId, B256};
-use std::sync::{
-    atomic::{AtomicUsize, Ordering},
-    Arc,
+use reth_primitives::{Header, HeadersDirection, PeerId, B256};
+use std::{
+    fmt,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
 };
-use tokio::sync::{mpsc::UnboundedSender, oneshot};
+use tokio::sync::{mpsc::UnboundedSender, oneshot, watch, Notify, Semaphore};
 
 /// Front-end API for fetching data from the network.
 ///
@@ -64,24 +74,414 @@ pub struct FetchClient {
     pub(crate) peers_handle: PeersHandle,
     /// Number of active peer sessions the node's currently handling.
     pub(crate) num_active_peers: Arc<AtomicUsize>,
+    /// Number of peers available to the fetcher for requests.
+    pub(crate) active_peers: watch::Receiver<usize>,
+    /// Counters of the fetcher this client sends requests to.
+    pub(crate) counters: Arc<FetcherCounters>,
+    /// How the fetcher this client sends requests to selects peers.
+    pub(crate) peer_selection: Arc<Mutex<PeerSelectionPolicy>>,
+    /// Permits for inflight requests, shared by all clones, if their number is limited.
+    pub(crate) request_permits: Option<Arc<Semaphore>>,
+    /// Callback invoked with the result of every completed request, if any.
+    pub(crate) result_tap: Option<ResultTap>,
+    /// Requests of this client and its clones that are in flight, and whether it was shut down.
+    pub(crate) shutdown: Arc<ShutdownState>,
+}
+
+impl FetchClient {
+    /// Limits the number of requests of this client and its clones that can be inflight at the
+    /// same time.
+    ///
+    /// Requests past the limit wait for a permit, which is released once a response is received,
+    /// before being handed to the fetcher. Note that this makes requests lazy: they are only sent
+    /// once the returned future is polled.
+    pub fn with_max_concurrent_requests(mut self, max_concurrent_requests: usize) -> Self {
+        self.request_permits = Some(Arc::new(Semaphore::new(max_concurrent_requests)));
+        self
+    }
+
+    /// Registers a callback invoked with the [`FetchResult`] of every request of this client and
+    /// its clones, once the request completes.
+    ///
+    /// This allows streaming download events into custom telemetry. The callback is invoked on
+    /// the task awaiting the response, so it should return quickly.
+    pub fn with_result_tap(mut self, tap: impl Fn(&FetchResult) + Send + Sync + 'static) -> Self {
+        self.result_tap = Some(ResultTap(Arc::new(tap)));
+        self
+    }
+
+    /// Stops accepting new requests on this client and its clones, and waits until all requests
+    /// in flight resolved, or until the timeout elapsed.
+    ///
+    /// Requests sent after the shutdown fail with [RequestError::ChannelClosed]. Requests in
+    /// flight are still answered by the fetcher, which avoids spurious errors during an orderly
+    /// shutdown.
+    pub async fn shutdown(&self, timeout: Duration) -> FetchShutdownSummary {
+        self.shutdown.closed.store(true, Ordering::SeqCst);
+        let inflight = self.shutdown.inflight.load(Ordering::SeqCst);
+
+        let drained = async {
+            loop {
+                // created before checking the count so no completion is missed
+                let notified = self.shutdown.drained.notified();
+                if self.shutdown.inflight.load(Ordering::SeqCst) == 0 {
+                    return
+                }
+                notified.await;
+            }
+        };
+        let _ = tokio::time::timeout(timeout, drained).await;
+
+        let abandoned = self.shutdown.inflight.load(Ordering::SeqCst);
+        FetchShutdownSummary { completed: inflight.saturating_sub(abandoned), abandoned }
+    }
+
+    /// Returns `true` if [`Self::shutdown`] was called on this client or one of its clones.
+    pub fn is_shut_down(&self) -> bool {
+        self.shutdown.closed.load(Ordering::SeqCst)
+    }
+
+    /// Sends the request to the fetcher and returns a future resolving to its response.
+    ///
+    /// The request counts as in flight until the response resolves or the future is dropped. If a
+    /// result tap is registered, it's invoked once the response resolves.
+    fn send_request<T: Send + Sync + 'static>(
+        &self,
+        kind: FetchRequestKind,
+        request: DownloadRequest,
+        rx: oneshot::Receiver<PeerRequestResult<Vec<T>>>,
+    ) -> RequestFuture<PeerRequestResult<Vec<T>>> {
+        let Some(guard) = InflightGuard::new(&self.shutdown) else {
+            return Box::pin(future::err(RequestError::ChannelClosed))
+        };
+        let response = self.dispatch_request(request, rx);
+        let tap = self.result_tap.clone();
+
+        let started = Instant::now();
+        Box::pin(async move {
+            let _guard = guard;
+            let response = response.await;
+            let Some(tap) = tap else { return response };
+            tap.0(&FetchResult {
+                kind,
+                peer_id: response.as_ref().ok().map(|response| response.peer_id()),
+                result: response
+                    .as_ref()
+                    .map(|response| response.data().len())
+                    .map_err(Clone::clone),
+                latency: started.elapsed(),
+            });
+            response
+        })
+    }
+
+    /// Sends the request to the fetcher and returns a future resolving to its response.
+    ///
+    /// If the number of concurrent requests is limited, the request is only sent once a permit was
+    /// acquired.
+    fn dispatch_request<T: Send + 'static>(
+        &self,
+        request: DownloadRequest,
+        rx: oneshot::Receiver<PeerRequestResult<T>>,
+    ) -> RequestFuture<PeerRequestResult<T>> {
+        let Some(permits) = self.request_permits.clone() else {
+            return if self.request_tx.send(request).is_ok() {
+                Box::pin(FlattenedResponse::from(rx))
+            } else {
+                Box::pin(future::err(RequestError::ChannelClosed))
+            }
+        };
+
+        let request_tx = self.request_tx.clone();
+        Box::pin(async move {
+            // held until the response is received
+            let _permit = permits.acquire_owned().await.map_err(|_| RequestError::ChannelClosed)?;
+            request_tx.send(request).map_err(|_| RequestError::ChannelClosed)?;
+            FlattenedResponse::from(rx).await
+        })
+    }
+
+    /// Returns a client that sends all requests to the given peer, instead of the next available
+    /// one.
+    ///
+    /// This is useful to probe the data of a single peer. Requests fail with
+    /// [RequestError::ConnectionDropped] if the peer is not connected, or disconnects before they
+    /// are dispatched.
+    pub fn pinned(&self, peer_id: PeerId) -> PinnedFetchClient {
+        PinnedFetchClient { client: self.clone(), peer_id }
+    }
+
+    /// Sends a `GetBlockHeaders` request, to the given peer if any.
+    fn request_headers(
+        &self,
+        request: HeadersRequest,
+        priority: Priority,
+        peer_id: Option<PeerId>,
+        order: Option<HeadersDirection>,
+    ) -> RequestFuture<PeerRequestResult<Vec<Header>>> {
+        let (response, rx) = oneshot::channel();
+        let response = self.send_request(
+            FetchRequestKind::Headers,
+            DownloadRequest::GetBlockHeaders { request, response, priority, peer_id },
+            rx,
+        );
+        let Some(order) = order else { return response };
+
+        Box::pin(async move {
+            let mut response = response.await;
+            if let Ok(headers) = &mut response {
+                match order {
+                    HeadersDirection::Rising => headers.1.sort_unstable_by_key(|h| h.number),
+                    HeadersDirection::Falling => {
+                        headers.1.sort_unstable_by(|a, b| b.number.cmp(&a.number))
+                    }
+                }
+            }
+            response
+        })
+    }
+
+    /// Sends a `GetBlockBodies` request, to the given peer if any.
+    fn request_bodies(
+        &self,
+        request: Vec<B256>,
+        priority: Priority,
+        peer_id: Option<PeerId>,
+    ) -> BodiesFut {
+        let (response, rx) = oneshot::channel();
+        self.send_request(
+            FetchRequestKind::Bodies,
+            DownloadRequest::GetBlockBodies { request, response, priority, peer_id },
+            rx,
+        )
+    }
+
+    /// Sends a `GetBlockHeaders` request and sorts the headers of the response by block number, in
+    /// the given order, regardless of the order the peer returned them in.
+    ///
+    /// For example, [HeadersDirection::Rising] always returns headers in ascending order, even for
+    /// requests with [HeadersDirection::Falling].
+    pub fn get_headers_with_priority_and_order(
+        &self,
+        request: HeadersRequest,
+        priority: Priority,
+        order: HeadersDirection,
+    ) -> RequestFuture<PeerRequestResult<Vec<Header>>> {
+        self.request_headers(request, priority, None, Some(order))
+    }
+
+    /// Sends a `GetBlockHeaders` request with [Priority::Critical], which is dispatched ahead of
+    /// all other queued requests.
+    ///
+    /// This should be used for requests on the critical path of live sync, e.g. the tip.
+    pub fn get_headers_critical(
+        &self,
+        request: HeadersRequest,
+    ) -> RequestFuture<PeerRequestResult<Vec<Header>>> {
+        self.get_headers_with_priority(request, Priority::Critical)
+    }
+
+    /// Sends all `GetBlockHeaders` requests and returns a future resolving to the first successful
+    /// response, or to the error of the last request if all of them fail.
+    ///
+    /// Once a request succeeded, the others are cancelled: the fetcher drops them if they are still
+    /// queued, and discards their response otherwise. Dropping the returned future cancels all of
+    /// them. Resolves to [RequestError::ChannelClosed] if there are no requests.
+    pub fn race_headers(
+        &self,
+        requests: impl IntoIterator<Item = HeadersRequest>,
+    ) -> impl Future<Output = PeerRequestResult<Vec<Header>>> + Send + 'static {
+        let responses =
+            requests.into_iter().map(|request| self.get_headers(request)).collect::<Vec<_>>();
+        async move {
+            if responses.is_empty() {
+                return Err(RequestError::ChannelClosed)
+            }
+            // the remaining responses are dropped, which cancels their requests
+            future::select_ok(responses).await.map(|(response, _)| response)
+        }
+    }
+
+    /// Returns a future that resolves once at least `min` peers are available for requests.
+    ///
+    /// This can be used to gate requests on peer availability, since requests submitted without
+    /// any active peers are queued until one connects.
+    ///
+    /// The future also resolves if the network was shut down.
+    pub fn wait_for_peers(&self, min: usize) -> impl Future<Output = ()> + Send + 'static {
+        let mut active_peers = self.active_peers.clone();
+        async move {
+            while *active_peers.borrow_and_update() < min {
+                if active_peers.changed().await.is_err() {
+                    return
+                }
+            }
+        }
+    }
+
+    /// Returns the number of requests queued in the fetcher, waiting for an idle peer.
+    ///
+    /// Requests that were sent but not yet received by the fetcher are not included, cancelled
+    /// requests are included until the fetcher reaches them in the queue.
+    pub fn pending_request_count(&self) -> usize {
+        self.counters.queued.load(Ordering::Relaxed)
+    }
+
+    /// Returns `true` if at least `threshold` requests are queued in the fetcher, see
+    /// [`Self::pending_request_count`].
+    ///
+    /// Callers can use this to slow down submitting new requests while the fetcher catches up.
+    pub fn is_saturated(&self, threshold: usize) -> bool {
+        self.pending_request_count() >= threshold
+    }
+
+    /// Sets how the fetcher selects the peer of requests that are not pinned to a peer.
+    ///
+    /// This applies to the requests of all clients of the fetcher, not just this one and its
+    /// clones.
+    pub fn set_peer_selection(&self, policy: PeerSelectionPolicy) {
+        *self.peer_selection.lock() = policy;
+    }
+
+    /// Returns how the fetcher selects the peer of requests that are not pinned to a peer, see
+    /// [`Self::set_peer_selection`].
+    pub fn peer_selection(&self) -> PeerSelectionPolicy {
+        *self.peer_selection.lock()
+    }
+
+    /// Returns a snapshot of the fetcher's peer and request counters.
+    pub fn metrics_snapshot(&self) -> FetchMetrics {
+        FetchMetrics {
+            active_peers: self.num_active_peers.load(Ordering::Relaxed),
+            idle_peers: self.counters.idle_peers.load(Ordering::Relaxed),
+            inflight: self.counters.inflight.load(Ordering::Relaxed),
+            dispatched_total: self.counters.dispatched_total.load(Ordering::Relaxed),
+            failed_total: self.counters.failed_total.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// A snapshot of the counters of the fetcher, see [`FetchClient::metrics_snapshot`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FetchMetrics {
+    /// Number of active peer sessions the node's currently handling.
+    pub active_peers: usize,
+    /// Number of peers available to the fetcher that are not handling a request.
+    pub idle_peers: usize,
+    /// Number of requests sent to peers and waiting for a response.
+    pub inflight: usize,
+    /// Total number of requests sent to peers.
+    pub dispatched_total: u64,
+    /// Total number of requests sent to peers that failed, including those dropped because the
+    /// peer disconnected.
+    pub failed_total: u64,
+}
+
+/// Summary of [`FetchClient::shutdown`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FetchShutdownSummary {
+    /// Number of requests in flight at the shutdown that resolved before the timeout.
+    pub completed: usize,
+    /// Number of requests still in flight when the timeout elapsed.
+    pub abandoned: usize,
+}
+
+/// Requests in flight of a [`FetchClient`] and its clones, see [`FetchClient::shutdown`].
+#[derive(Debug, Default)]
+pub(crate) struct ShutdownState {
+    /// Whether new requests are rejected.
+    closed: AtomicBool,
+    /// Number of requests whose response has not resolved yet.
+    inflight: AtomicUsize,
+    /// Notified whenever a request in flight resolves.
+    drained: Notify,
+}
+
+/// Counts a request as in flight until dropped.
+struct InflightGuard(Arc<ShutdownState>);
+
+impl InflightGuard {
+    /// Registers a new request in flight, or returns `None` if the client was shut down.
+    fn new(state: &Arc<ShutdownState>) -> Option<Self> {
+        // registered before checking for a shutdown, so that a concurrent shutdown either rejects
+        // the request or waits for it
+        state.inflight.fetch_add(1, Ordering::SeqCst);
+        let guard = Self(Arc::clone(state));
+        (!state.closed.load(Ordering::SeqCst)).then_some(guard)
+    }
+}
+
+impl Drop for InflightGuard {
+    fn drop(&mut self) {
+        self.0.inflight.fetch_sub(1, Ordering::SeqCst);
+        self.0.drained.notify_waiters();
+    }
+}
+
+/// Kind of a request sent by a [`FetchClient`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FetchRequestKind {
+    /// A `GetBlockHeaders` request.
+    Headers,
+    /// A `GetBlockBodies` request.
+    Bodies,
+}
+
+/// Summary of a completed request of a [`FetchClient`], see [`FetchClient::with_result_tap`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FetchResult {
+    /// Kind of the request.
+    pub kind: FetchRequestKind,
+    /// The peer that answered the request, `None` if it failed.
+    pub peer_id: Option<PeerId>,
+    /// Number of headers or bodies of the response, or the error of the request.
+    pub result: Result<usize, RequestError>,
+    /// Time from sending the request, including waiting for a permit if the number of concurrent
+    /// requests is limited, until its response resolved.
+    pub latency: Duration,
+}
+
+/// Callback registered with [`FetchClient::with_result_tap`].
+#[derive(Clone)]
+pub(crate) struct ResultTap(Arc<dyn Fn(&FetchResult) + Send + Sync>);
+
+impl fmt::Debug for ResultTap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResultTap").finish_non_exhaustive()
+    }
+}
+
+impl FetchClient {
+    /// Reports a bad message from the peer, applying the given reputation change.
+    ///
+    /// This allows penalizing a peer proportionally to the misbehavior, see also
+    /// [DownloadClient::report_bad_message].
+    pub fn report_bad_message_with(&self, peer_id: PeerId, kind: ReputationChangeKind) {
+        self.peers_handle.reputation_change(peer_id, kind);
+    }
 }
 
 impl DownloadClient for FetchClient {
     fn report_bad_message(&self, peer_id: PeerId) {
-        self.peers_handle.reputation_change(peer_id, ReputationChangeKind::BadMessage);
+        self.report_bad_message_with(peer_id, ReputationChangeKind::BadMessage);
     }
 
     fn num_connected_peers(&self) -> usize {
         self.num_active_peers.load(Ordering::Relaxed)
     }
+
+    /// Returns `true` if there is at least one connected peer and the network is still accepting
+    /// requests.
+    fn is_ready(&self) -> bool {
+        !self.request_tx.is_closed() && !self.is_shut_down() && self.num_connected_peers() > 0
+    }
 }
 
-// The `Output` future of the [HeadersClient] impl of [FetchClient] that either returns a response
-// or an error.
-type HeadersClientFuture<T> = Either<FlattenedResponse<T>, future::Ready<T>>;
+// The `Output` future of the requests of [FetchClient] that either returns a response or an error.
+type RequestFuture<T> = Pin<Box<dyn Future<Output = T> + Send + Sync>>;
 
 impl HeadersClient for FetchClient {
-    type Output = HeadersClientFuture<PeerRequestResult<Vec<Header>>>;
+    type Output = RequestFuture<PeerRequestResult<Vec<Header>>>;
 
     /// Sends a `GetBlockHeaders` request to an available peer.
     fn get_headers_with_priority(
@@ -89,16 +489,7 @@ impl HeadersClient for FetchClient {
         request: HeadersRequest,
         priority: Priority,
     ) -> Self::Output {
-        let (response, rx) = oneshot::channel();
-        if self
-            .request_tx
-            .send(DownloadRequest::GetBlockHeaders { request, response, priority })
-            .is_ok()
-        {
-            Either::Left(FlattenedResponse::from(rx))
-        } else {
-            Either::Right(future::err(RequestError::ChannelClosed))
-        }
+        self.request_headers(request, priority, None, None)
     }
 }
 
@@ -111,15 +502,226 @@ impl BodiesClient for FetchClient {
         request: Vec<B256>,
         priority: Priority,
     ) -> Self::Output {
-        let (response, rx) = oneshot::channel();
-        if self
-            .request_tx
-            .send(DownloadRequest::GetBlockBodies { request, response, priority })
-            .is_ok()
-        {
-            Box::pin(FlattenedResponse::from(rx))
-        } else {
-            Box::pin(future::err(RequestError::ChannelClosed))
+        self.request_bodies(request, priority, None)
+    }
+}
+
+/// A [FetchClient] that sends all requests to a single peer, bypassing the selection of the next
+/// available peer.
+///
+/// See [FetchClient::pinned].
+#[derive(Debug, Clone)]
+pub struct PinnedFetchClient {
+    /// The client used to send the requests.
+    client: FetchClient,
+    /// The peer all requests are sent to.
+    peer_id: PeerId,
+}
+
+impl PinnedFetchClient {
+    /// Returns the peer all requests are sent to.
+    pub fn peer_id(&self) -> PeerId {
+        self.peer_id
+    }
+}
+
+impl DownloadClient for PinnedFetchClient {
+    fn report_bad_message(&self, peer_id: PeerId) {
+        self.client.report_bad_message(peer_id)
+    }
+
+    fn num_connected_peers(&self) -> usize {
+        self.client.num_connected_peers()
+    }
+
+    fn is_ready(&self) -> bool {
+        self.client.is_ready()
+    }
+}
+
+impl HeadersClient for PinnedFetchClient {
+    type Output = RequestFuture<PeerRequestResult<Vec<Header>>>;
+
+    /// Sends a `GetBlockHeaders` request to the pinned peer.
+    fn get_headers_with_priority(
+        &self,
+        request: HeadersRequest,
+        priority: Priority,
+    ) -> Self::Output {
+        self.client.request_headers(request, priority, Some(self.peer_id), None)
+    }
+}
+
+impl BodiesClient for PinnedFetchClient {
+    type Output = BodiesFut;
+
+    /// Sends a `GetBlockBodies` request to the pinned peer.
+    fn get_block_bodies_with_priority(
+        &self,
+        request: Vec<B256>,
+        priority: Priority,
+    ) -> Self::Output {
+        self.client.request_bodies(request, priority, Some(self.peer_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::peers::PeersManager;
+    use std::{
+        future::poll_fn,
+        net::{IpAddr, Ipv4Addr, SocketAddr},
+        task::Poll,
+    };
+    use tokio::sync::mpsc;
+
+    /// Returns a client over the given peers, and the receiver of the requests it sends to the
+    /// fetcher.
+    fn test_client(
+        peers_handle: PeersHandle,
+    ) -> (FetchClient, mpsc::UnboundedReceiver<DownloadRequest>) {
+        let (request_tx, request_rx) = mpsc::unbounded_channel();
+        let client = FetchClient {
+            request_tx,
+            peers_handle,
+            num_active_peers: Default::default(),
+            active_peers: watch::channel(0).1,
+            counters: Default::default(),
+            peer_selection: Default::default(),
+            request_permits: None,
+            result_tap: None,
+            shutdown: Default::default(),
+        };
+        (client, request_rx)
+    }
+
+    #[tokio::test]
+    async fn test_report_bad_message_with() {
+        let peer = PeerId::random();
+        let socket_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 1, 2)), 8008);
+        let mut peers = PeersManager::default();
+        peers.add_peer(peer, socket_addr, None);
+
+        let (client, _request_rx) = test_client(peers.handle());
+
+        client.report_bad_message_with(peer, ReputationChangeKind::Other(-1024));
+
+        // drain the commands sent through the handle
+        poll_fn(|cx| {
+            while peers.poll(cx).is_ready() {}
+            Poll::Ready(())
+        })
+        .await;
+        assert_eq!(peers.get_reputation(&peer), Some(-1024));
+    }
+
+    #[tokio::test]
+    async fn test_max_concurrent_requests() {
+        let (client, mut request_rx) = test_client(PeersManager::default().handle());
+        let client = client.with_max_concurrent_requests(2);
+
+        let request =
+            HeadersRequest { start: 0u64.into(), limit: 1, direction: Default::default() };
+        let _requests = (0..3)
+            .map(|_| tokio::spawn(client.clone().get_headers(request.clone())))
+            .collect::<Vec<_>>();
+        tokio::task::yield_now().await;
+
+        // only two requests are handed to the fetcher
+        let mut responses = Vec::new();
+        for _ in 0..2 {
+            match request_rx.recv().await.unwrap() {
+                DownloadRequest::GetBlockHeaders { response, .. } => responses.push(response),
+                _ => unreachable!(),
+            }
         }
+        assert!(request_rx.try_recv().is_err());
+
+        // answering a request releases its permit
+        let peer = PeerId::random();
+        responses.pop().unwrap().send(Ok((peer, Vec::new()).into())).unwrap();
+        let response = tokio::time::timeout(std::time::Duration::from_secs(5), request_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(response, DownloadRequest::GetBlockHeaders { .. }));
+        assert!(request_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_drains_inflight_requests() {
+        let (client, mut request_rx) = test_client(PeersManager::default().handle());
+
+        let request =
+            HeadersRequest { start: 0u64.into(), limit: 1, direction: Default::default() };
+        let requests = (0..2)
+            .map(|_| tokio::spawn(client.clone().get_headers(request.clone())))
+            .collect::<Vec<_>>();
+        let mut responses = Vec::new();
+        for _ in 0..2 {
+            match request_rx.recv().await.unwrap() {
+                DownloadRequest::GetBlockHeaders { response, .. } => responses.push(response),
+                _ => unreachable!(),
+            }
+        }
+
+        let shutdown = tokio::spawn({
+            let client = client.clone();
+            async move { client.shutdown(Duration::from_secs(5)).await }
+        });
+        while !client.is_shut_down() {
+            tokio::task::yield_now().await;
+        }
+
+        // new requests are rejected
+        assert!(matches!(
+            client.get_headers(request.clone()).await,
+            Err(RequestError::ChannelClosed)
+        ));
+        assert!(request_rx.try_recv().is_err());
+
+        // requests in flight are still answered
+        let peer = PeerId::random();
+        for response in responses {
+            response.send(Ok((peer, Vec::new()).into())).unwrap();
+        }
+        for request in requests {
+            assert_eq!(request.await.unwrap().unwrap().peer_id(), peer);
+        }
+        assert_eq!(shutdown.await.unwrap(), FetchShutdownSummary { completed: 2, abandoned: 0 });
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_timeout() {
+        let (client, mut request_rx) = test_client(PeersManager::default().handle());
+
+        let request =
+            HeadersRequest { start: 0u64.into(), limit: 1, direction: Default::default() };
+        let _request = tokio::spawn(client.clone().get_headers(request));
+        // keep the request unanswered
+        let _pending = request_rx.recv().await.unwrap();
+
+        assert_eq!(
+            client.shutdown(Duration::from_millis(10)).await,
+            FetchShutdownSummary { completed: 0, abandoned: 1 }
+        );
+    }
+
+    #[test]
+    fn test_is_ready() {
+        let (client, request_rx) = test_client(PeersManager::default().handle());
+
+        // no connected peers
+        assert!(!client.is_ready());
+
+        client.num_active_peers.store(1, Ordering::Relaxed);
+        assert!(client.is_ready());
+        assert!(client.pinned(PeerId::random()).is_ready());
+
+        // the fetcher is gone
+        drop(request_rx);
+        assert!(!client.is_ready());
+        assert!(!client.pinned(PeerId::random()).is_ready());
     }
 }
@@ -2,6 +2,8 @@
 
 use crate::{message::BlockRequest, peers::PeersHandle};
 use futures::StreamExt;
+use parking_lot::Mutex;
+use rand::distributions::{Distribution, WeightedIndex};
 use reth_eth_wire::{GetBlockBodies, GetBlockHeaders};
 use reth_interfaces::p2p::{
     error::{EthResponseValidator, PeerRequestResult, RequestError, RequestResult},
@@ -11,18 +13,23 @@ use reth_interfaces::p2p::{
 use reth_network_api::ReputationChangeKind;
 use reth_primitives::{BlockBody, Header, PeerId, B256};
 use std::{
-    collections::{HashMap, VecDeque},
+    collections::{hash_map::Entry, HashMap, VecDeque},
+    hash::Hash,
     sync::{
         atomic::{AtomicU64, AtomicUsize, Ordering},
         Arc,
     },
     task::{Context, Poll},
+    time::{Duration, Instant},
 };
-use tokio::sync::{mpsc, mpsc::UnboundedSender, oneshot};
+use tokio::sync::{mpsc, mpsc::UnboundedSender, oneshot, watch};
 use tokio_stream::wrappers::UnboundedReceiverStream;
 
 mod client;
-pub use client::FetchClient;
+pub use client::{
+    FetchClient, FetchMetrics, FetchRequestKind, FetchResult, FetchShutdownSummary,
+    PinnedFetchClient,
+};
 
 /// Manages data fetching operations.
 ///
@@ -44,8 +51,20 @@ pub struct StateFetcher {
     peers_handle: PeersHandle,
     /// Number of active peer sessions the node's currently handling.
     num_active_peers: Arc<AtomicUsize>,
+    /// Sender half of the channel tracking the number of peers available to the fetcher.
+    active_peers_tx: watch::Sender<usize>,
+    /// Counters of the fetcher, shared with its clients.
+    counters: Arc<FetcherCounters>,
+    /// How the peer of requests that are not pinned is selected, shared with the clients.
+    peer_selection: Arc<Mutex<PeerSelectionPolicy>>,
     /// Requests queued for processing
     queued_requests: VecDeque<DownloadRequest>,
+    /// Response senders of [`GetBlockHeaders`] requests that wait for an identical queued or
+    /// inflight request.
+    coalesced_headers_requests: CoalescedRequests<HeadersRequest, Vec<Header>>,
+    /// Response senders of [`GetBlockBodies`] requests that wait for an identical queued or
+    /// inflight request.
+    coalesced_bodies_requests: CoalescedRequests<Vec<B256>, Vec<BlockBody>>,
     /// Receiver for new incoming download requests
     download_requests_rx: UnboundedReceiverStream<DownloadRequest>,
     /// Sender for download requests, used to detach a [`FetchClient`]
@@ -63,7 +82,12 @@ impl StateFetcher {
             peers: Default::default(),
             peers_handle,
             num_active_peers,
+            active_peers_tx: watch::channel(0).0,
+            counters: Default::default(),
+            peer_selection: Default::default(),
             queued_requests: Default::default(),
+            coalesced_headers_requests: Default::default(),
+            coalesced_bodies_requests: Default::default(),
             download_requests_rx: UnboundedReceiverStream::new(download_requests_rx),
             download_requests_tx,
         }
@@ -77,8 +101,15 @@ impl StateFetcher {
         best_number: u64,
         timeout: Arc<AtomicU64>,
     ) {
-        self.peers
-            .insert(peer_id, Peer { state: PeerState::Idle, best_hash, best_number, timeout });
+        let was_idle = self
+            .peers
+            .insert(
+                peer_id,
+                Peer { state: PeerState::Idle, best_hash, best_number, timeout, latency: None },
+            )
+            .is_some_and(|peer| peer.state.is_idle());
+        self.counters.on_peer_state_changed(was_idle, true);
+        self.active_peers_tx.send_replace(self.peers.len());
     }
 
     /// Removes the peer from the peer list, after which it is no longer available for future
@@ -88,13 +119,52 @@ impl StateFetcher {
     ///
     /// This cancels also inflight request and sends an error to the receiver.
     pub(crate) fn on_session_closed(&mut self, peer: &PeerId) {
-        self.peers.remove(peer);
+        if let Some(removed) = self.peers.remove(peer) {
+            self.counters.on_peer_state_changed(removed.state.is_idle(), false);
+        }
+        self.active_peers_tx.send_replace(self.peers.len());
+
+        // fail the queued requests that can only be sent to this peer
+        let (pinned, queued) = std::mem::take(&mut self.queued_requests)
+            .into_iter()
+            .partition::<VecDeque<_>, _>(|req| req.peer_id() == Some(peer));
+        self.queued_requests = queued;
+        for req in pinned {
+            req.send_err_response(RequestError::ConnectionDropped);
+        }
+
         if let Some(req) = self.inflight_headers_requests.remove(peer) {
+            self.counters.failed_total.fetch_add(1, Ordering::Relaxed);
+            if !req.pinned {
+                self.coalesced_headers_requests.send_response(
+                    &req.request,
+                    *peer,
+                    &Err(RequestError::ConnectionDropped),
+                );
+            }
             let _ = req.response.send(Err(RequestError::ConnectionDropped));
         }
         if let Some(req) = self.inflight_bodies_requests.remove(peer) {
+            self.counters.failed_total.fetch_add(1, Ordering::Relaxed);
+            if !req.pinned {
+                self.coalesced_bodies_requests.send_response(
+                    &req.request,
+                    *peer,
+                    &Err(RequestError::ConnectionDropped),
+                );
+            }
             let _ = req.response.send(Err(RequestError::ConnectionDropped));
         }
+        self.update_request_counters();
+    }
+
+    /// Updates the number of queued and inflight requests shared with the clients.
+    ///
+    /// The number of idle peers is updated when the state of a peer changes.
+    fn update_request_counters(&self) {
+        let inflight = self.inflight_headers_requests.len() + self.inflight_bodies_requests.len();
+        self.counters.inflight.store(inflight, Ordering::Relaxed);
+        self.counters.queued.store(self.queued_requests.len(), Ordering::Relaxed);
     }
 
     /// Updates the block information for the peer.
@@ -114,34 +184,115 @@ impl StateFetcher {
     /// Invoked when an active session is about to be disconnected.
     pub(crate) fn on_pending_disconnect(&mut self, peer_id: &PeerId) {
         if let Some(peer) = self.peers.get_mut(peer_id) {
+            self.counters.on_peer_state_changed(peer.state.is_idle(), false);
             peer.state = PeerState::Closing;
         }
     }
 
-    /// Returns the _next_ idle peer that's ready to accept a request,
-    /// prioritizing those with the lowest timeout/latency.
-    /// Once a peer has been yielded, it will be moved to the end of the map
+    /// Resets the state of the peer on a received response, see
+    /// [`PeerState::on_request_finished`].
+    ///
+    /// Returns `true` if the peer is ready for another request.
+    fn on_peer_request_finished(&mut self, peer_id: &PeerId) -> bool {
+        let Some(peer) = self.peers.get_mut(peer_id) else { return false };
+        let was_idle = peer.state.is_idle();
+        let is_ready = peer.state.on_request_finished();
+        self.counters.on_peer_state_changed(was_idle, peer.state.is_idle());
+        is_ready
+    }
+
+    /// Returns the _next_ idle peer that's ready to accept a request, according to the
+    /// [`PeerSelectionPolicy`].
     fn next_peer(&mut self) -> Option<PeerId> {
-        self.peers
-            .iter()
-            .filter(|(_, peer)| peer.state.is_idle())
-            .min_by_key(|(_, peer)| peer.timeout())
-            .map(|(id, _)| *id)
+        let idle = self.peers.iter().filter(|(_, peer)| peer.state.is_idle());
+        match *self.peer_selection.lock() {
+            PeerSelectionPolicy::LowestTimeout => {
+                idle.min_by_key(|(_, peer)| peer.timeout()).map(|(id, _)| *id)
+            }
+            PeerSelectionPolicy::LatencyWeighted => {
+                let idle = idle.collect::<Vec<_>>();
+                // peers without an estimate are assumed to be as fast as the fastest one, so they
+                // are measured soon
+                let fastest = idle.iter().filter_map(|(_, peer)| peer.latency).min();
+                let weights = idle.iter().map(|(_, peer)| {
+                    peer.latency
+                        .or(fastest)
+                        .map_or(1.0, |latency| 1.0 / latency.as_secs_f64().max(1e-6))
+                });
+                let index = WeightedIndex::new(weights).ok()?.sample(&mut rand::thread_rng());
+                Some(*idle[index].0)
+            }
+        }
+    }
+
+    /// Updates the average response time of the peer with the latency of a response.
+    fn on_response_latency(&mut self, peer_id: &PeerId, latency: Duration) {
+        if let Some(peer) = self.peers.get_mut(peer_id) {
+            peer.latency = Some(match peer.latency {
+                Some(average) => {
+                    average.mul_f64(1.0 - LATENCY_EWMA_WEIGHT) +
+                        latency.mul_f64(LATENCY_EWMA_WEIGHT)
+                }
+                None => latency,
+            });
+        }
+    }
+
+    /// Returns the position of the first queued request that can be dispatched, and the peer to
+    /// dispatch it to.
+    ///
+    /// Requests pinned to a peer wait until that peer is idle, all other requests are sent to the
+    /// next idle peer.
+    fn next_request(&mut self) -> Option<(usize, PeerId)> {
+        let next_peer = self.next_peer();
+        self.queued_requests.iter().enumerate().find_map(|(idx, req)| match req.peer_id() {
+            Some(peer_id) => {
+                let peer = self.peers.get(peer_id)?;
+                peer.state.is_idle().then_some((idx, *peer_id))
+            }
+            None => next_peer.map(|peer_id| (idx, peer_id)),
+        })
+    }
+
+    /// Returns `true` if the queued request was cancelled, i.e. its response receiver was dropped,
+    /// and no identical request waits for its response.
+    ///
+    /// Cancelled requests are dropped when they are popped from the queue. Requests that are
+    /// already inflight can't be recalled, their response is discarded.
+    fn is_cancelled(&mut self, req: &DownloadRequest) -> bool {
+        match req {
+            DownloadRequest::GetBlockHeaders { request, response, peer_id, .. } => {
+                response.is_closed() &&
+                    (peer_id.is_some() || self.coalesced_headers_requests.cancel(request))
+            }
+            DownloadRequest::GetBlockBodies { request, response, peer_id, .. } => {
+                response.is_closed() &&
+                    (peer_id.is_some() || self.coalesced_bodies_requests.cancel(request))
+            }
+        }
     }
 
     /// Returns the next action to return
     fn poll_action(&mut self) -> PollAction {
-        // we only check and not pop here since we don't know yet whether a peer is available.
-        if self.queued_requests.is_empty() {
-            return PollAction::NoRequests
-        }
+        loop {
+            // we only check and not pop here since we don't know yet whether a peer is available.
+            if self.queued_requests.is_empty() {
+                return PollAction::NoRequests
+            }
 
-        let Some(peer_id) = self.next_peer() else { return PollAction::NoPeersAvailable };
+            let Some((idx, peer_id)) = self.next_request() else {
+                return PollAction::NoPeersAvailable
+            };
 
-        let request = self.queued_requests.pop_front().expect("not empty; qed");
-        let request = self.prepare_block_request(peer_id, request);
+            let request = self.queued_requests.remove(idx).expect("exists; qed");
+            if self.is_cancelled(&request) {
+                self.update_request_counters();
+                continue
+            }
+            let request = self.prepare_block_request(peer_id, request);
 
-        PollAction::Ready(FetchAction::BlockRequest { peer_id, request })
+            return PollAction::Ready(FetchAction::BlockRequest { peer_id, request })
+        }
     }
 
     /// Advance the state the syncer
@@ -157,21 +308,7 @@ impl StateFetcher {
             loop {
                 // poll incoming requests
                 match self.download_requests_rx.poll_next_unpin(cx) {
-                    Poll::Ready(Some(request)) => match request.get_priority() {
-                        Priority::High => {
-                            // find the first normal request and queue before, add this request to
-                            // the back of the high-priority queue
-                            let pos = self
-                                .queued_requests
-                                .iter()
-                                .position(|req| req.is_normal_priority())
-                                .unwrap_or(0);
-                            self.queued_requests.insert(pos, request);
-                        }
-                        Priority::Normal => {
-                            self.queued_requests.push_back(request);
-                        }
-                    },
+                    Poll::Ready(Some(request)) => self.queue_request(request),
                     Poll::Ready(None) => {
                         unreachable!("channel can't close")
                     }
@@ -185,18 +322,128 @@ impl StateFetcher {
         }
     }
 
+    /// Queues a new download request according to its priority.
+    ///
+    /// Critical requests are queued in front of all other requests, and high-priority requests in
+    /// front of normal ones. Requests with the same priority are dispatched in arrival order.
+    ///
+    /// A request that is not pinned to a peer and identical to a queued or inflight request that
+    /// is not pinned either is not queued, it gets the response of that request instead. If that
+    /// request is still queued with a lower priority, it is moved to the position of the higher
+    /// one.
+    fn queue_request(&mut self, request: DownloadRequest) {
+        // requests pinned to a peer that is not connected can't be dispatched
+        if request.peer_id().is_some_and(|peer_id| !self.peers.contains_key(peer_id)) {
+            request.send_err_response(RequestError::ConnectionDropped);
+            return
+        }
+
+        let Some(request) = self.coalesce_request(request) else { return };
+        self.insert_queued_request(request);
+    }
+
+    /// Inserts the request into the queue at the position of its priority.
+    fn insert_queued_request(&mut self, request: DownloadRequest) {
+        let priority = *request.get_priority();
+        let pos = match priority {
+            // add this request to the back of the critical queue
+            Priority::Critical => {
+                self.queued_requests.iter().position(|req| !req.get_priority().is_critical())
+            }
+            // find the first normal request and queue before, add this request to the back of the
+            // high-priority queue
+            Priority::High => self.queued_requests.iter().position(|req| req.is_normal_priority()),
+            Priority::Normal => None,
+        };
+        match pos {
+            Some(pos) => self.queued_requests.insert(pos, request),
+            None => self.queued_requests.push_back(request),
+        }
+        self.update_request_counters();
+    }
+
+    /// Attaches a request that is not pinned to a peer to an identical queued or inflight request.
+    ///
+    /// Returns the request if it must be queued, in which case identical requests that follow are
+    /// attached to it until it is answered.
+    fn coalesce_request(&mut self, request: DownloadRequest) -> Option<DownloadRequest> {
+        match request {
+            DownloadRequest::GetBlockHeaders { request, response, priority, peer_id: None } => {
+                let Some(response) = self.coalesced_headers_requests.attach(&request, response)
+                else {
+                    self.raise_queued_priority(priority, |req| match req {
+                        DownloadRequest::GetBlockHeaders { request: queued, .. } => {
+                            *queued == request
+                        }
+                        _ => false,
+                    });
+                    return None
+                };
+                Some(DownloadRequest::GetBlockHeaders {
+                    request,
+                    response,
+                    priority,
+                    peer_id: None,
+                })
+            }
+            DownloadRequest::GetBlockBodies { request, response, priority, peer_id: None } => {
+                let Some(response) = self.coalesced_bodies_requests.attach(&request, response)
+                else {
+                    self.raise_queued_priority(priority, |req| match req {
+                        DownloadRequest::GetBlockBodies { request: queued, .. } => {
+                            *queued == request
+                        }
+                        _ => false,
+                    });
+                    return None
+                };
+                Some(DownloadRequest::GetBlockBodies { request, response, priority, peer_id: None })
+            }
+            request => Some(request),
+        }
+    }
+
+    /// Moves the queued request that is not pinned to a peer and matches to the position of the
+    /// given priority, if it is higher than its own.
+    ///
+    /// Does nothing if there's no such request, e.g. because it is already inflight.
+    fn raise_queued_priority(
+        &mut self,
+        priority: Priority,
+        is_match: impl Fn(&DownloadRequest) -> bool,
+    ) {
+        let Some(idx) =
+            self.queued_requests.iter().position(|req| req.peer_id().is_none() && is_match(req))
+        else {
+            return
+        };
+        if *self.queued_requests[idx].get_priority() >= priority {
+            return
+        }
+        let mut request = self.queued_requests.remove(idx).expect("exists; qed");
+        request.set_priority(priority);
+        self.insert_queued_request(request);
+    }
+
     /// Handles a new request to a peer.
     ///
     /// Caution: this assumes the peer exists and is idle
     fn prepare_block_request(&mut self, peer_id: PeerId, req: DownloadRequest) -> BlockRequest {
         // update the peer's state
         if let Some(peer) = self.peers.get_mut(&peer_id) {
+            self.counters.on_peer_state_changed(peer.state.is_idle(), false);
             peer.state = req.peer_state();
         }
 
-        match req {
-            DownloadRequest::GetBlockHeaders { request, response, .. } => {
-                let inflight = Request { request: request.clone(), response };
+        self.counters.dispatched_total.fetch_add(1, Ordering::Relaxed);
+        let request = match req {
+            DownloadRequest::GetBlockHeaders { request, response, peer_id: pinned, .. } => {
+                let inflight = Request {
+                    request: request.clone(),
+                    response,
+                    pinned: pinned.is_some(),
+                    started: Instant::now(),
+                };
                 self.inflight_headers_requests.insert(peer_id, inflight);
                 let HeadersRequest { start, limit, direction } = request;
                 BlockRequest::GetBlockHeaders(GetBlockHeaders {
@@ -206,21 +453,38 @@ impl StateFetcher {
                     direction,
                 })
             }
-            DownloadRequest::GetBlockBodies { request, response, .. } => {
-                let inflight = Request { request: request.clone(), response };
+            DownloadRequest::GetBlockBodies { request, response, peer_id: pinned, .. } => {
+                let inflight = Request {
+                    request: request.clone(),
+                    response,
+                    pinned: pinned.is_some(),
+                    started: Instant::now(),
+                };
                 self.inflight_bodies_requests.insert(peer_id, inflight);
                 BlockRequest::GetBlockBodies(GetBlockBodies(request))
             }
-        }
+        };
+        self.update_request_counters();
+        request
     }
 
     /// Returns a new followup request for the peer.
     ///
     /// Caution: this expects that the peer is _not_ closed.
     fn followup_request(&mut self, peer_id: PeerId) -> Option<BlockResponseOutcome> {
-        let req = self.queued_requests.pop_front()?;
-        let req = self.prepare_block_request(peer_id, req);
-        Some(BlockResponseOutcome::Request(peer_id, req))
+        loop {
+            let idx = self
+                .queued_requests
+                .iter()
+                .position(|req| req.peer_id().map_or(true, |pinned| *pinned == peer_id))?;
+            let req = self.queued_requests.remove(idx)?;
+            if self.is_cancelled(&req) {
+                self.update_request_counters();
+                continue
+            }
+            let req = self.prepare_block_request(peer_id, req);
+            return Some(BlockResponseOutcome::Request(peer_id, req))
+        }
     }
 
     /// Called on a `GetBlockHeaders` response from a peer.
@@ -237,6 +501,9 @@ impl StateFetcher {
         let maybe_reputation_change = res.reputation_change_err();
 
         let resp = self.inflight_headers_requests.remove(&peer_id);
+        if is_error && resp.is_some() {
+            self.counters.failed_total.fetch_add(1, Ordering::Relaxed);
+        }
 
         let is_likely_bad_response = resp
             .as_ref()
@@ -244,16 +511,23 @@ impl StateFetcher {
             .unwrap_or_default();
 
         if let Some(resp) = resp {
+            if !is_error {
+                self.on_response_latency(&peer_id, resp.started.elapsed());
+            }
             // delegate the response
+            if !resp.pinned {
+                self.coalesced_headers_requests.send_response(&resp.request, peer_id, &res);
+            }
             let _ = resp.response.send(res.map(|h| (peer_id, h).into()));
         }
 
-        if let Some(peer) = self.peers.get_mut(&peer_id) {
-            // If the peer is still ready to accept new requests, we try to send a followup
-            // request immediately.
-            if peer.state.on_request_finished() && !is_error && !is_likely_bad_response {
-                return self.followup_request(peer_id)
-            }
+        let is_ready = self.on_peer_request_finished(&peer_id);
+        self.update_request_counters();
+
+        // If the peer is still ready to accept new requests, we try to send a followup request
+        // immediately.
+        if is_ready && !is_error && !is_likely_bad_response {
+            return self.followup_request(peer_id)
         }
 
         // if the response was an `Err` worth reporting the peer for then we return a `BadResponse`
@@ -269,12 +543,21 @@ impl StateFetcher {
         res: RequestResult<Vec<BlockBody>>,
     ) -> Option<BlockResponseOutcome> {
         if let Some(resp) = self.inflight_bodies_requests.remove(&peer_id) {
+            if res.is_err() {
+                self.counters.failed_total.fetch_add(1, Ordering::Relaxed);
+            } else {
+                self.on_response_latency(&peer_id, resp.started.elapsed());
+            }
+            if !resp.pinned {
+                self.coalesced_bodies_requests.send_response(&resp.request, peer_id, &res);
+            }
             let _ = resp.response.send(res.map(|b| (peer_id, b).into()));
         }
-        if let Some(peer) = self.peers.get_mut(&peer_id) {
-            if peer.state.on_request_finished() {
-                return self.followup_request(peer_id)
-            }
+        let is_ready = self.on_peer_request_finished(&peer_id);
+        self.update_request_counters();
+
+        if is_ready {
+            return self.followup_request(peer_id)
         }
         None
     }
@@ -285,10 +568,66 @@ impl StateFetcher {
             request_tx: self.download_requests_tx.clone(),
             peers_handle: self.peers_handle.clone(),
             num_active_peers: Arc::clone(&self.num_active_peers),
+            active_peers: self.active_peers_tx.subscribe(),
+            counters: Arc::clone(&self.counters),
+            peer_selection: Arc::clone(&self.peer_selection),
+            request_permits: None,
+            result_tap: None,
+            shutdown: Default::default(),
         }
     }
 }
 
+/// Counters of a [`StateFetcher`], shared with its [`FetchClient`]s, see
+/// [`FetchClient::metrics_snapshot`].
+#[derive(Debug, Default)]
+pub(crate) struct FetcherCounters {
+    /// Number of peers available to the fetcher that are idle.
+    idle_peers: AtomicUsize,
+    /// Number of requests sent to peers and waiting for a response.
+    inflight: AtomicUsize,
+    /// Number of requests queued in the fetcher, waiting for an idle peer.
+    queued: AtomicUsize,
+    /// Total number of requests sent to peers.
+    dispatched_total: AtomicU64,
+    /// Total number of requests sent to peers that failed.
+    failed_total: AtomicU64,
+}
+
+impl FetcherCounters {
+    /// Updates the number of idle peers when a peer changes its state, is added or removed.
+    fn on_peer_state_changed(&self, was_idle: bool, is_idle: bool) {
+        match (was_idle, is_idle) {
+            (false, true) => {
+                self.idle_peers.fetch_add(1, Ordering::Relaxed);
+            }
+            (true, false) => {
+                self.idle_peers.fetch_sub(1, Ordering::Relaxed);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Weight of the latest response time in the average response time of a peer.
+const LATENCY_EWMA_WEIGHT: f64 = 0.2;
+
+/// How the fetcher selects the idle peer a request is sent to, unless the request is pinned to a
+/// peer, see [`FetchClient::set_peer_selection`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PeerSelectionPolicy {
+    /// Selects the idle peer with the lowest request timeout.
+    #[default]
+    LowestTimeout,
+    /// Selects an idle peer at random, with a probability inversely proportional to its average
+    /// response time, an exponentially weighted moving average of the latency of its successful
+    /// responses.
+    ///
+    /// This favors fast peers, while still sending requests to slow ones from time to time, which
+    /// keeps their estimate fresh.
+    LatencyWeighted,
+}
+
 /// The outcome of [`StateFetcher::poll_action`]
 enum PollAction {
     Ready(FetchAction),
@@ -307,6 +646,9 @@ struct Peer {
     best_number: u64,
     /// Tracks the current timeout value we use for the peer.
     timeout: Arc<AtomicU64>,
+    /// Average response time of the peer, if it answered a request, see
+    /// [`PeerSelectionPolicy::LatencyWeighted`].
+    latency: Option<Duration>,
 }
 
 impl Peer {
@@ -359,6 +701,74 @@ struct Request<Req, Resp> {
     #[allow(unused)]
     request: Req,
     response: oneshot::Sender<Resp>,
+    /// Whether the request is pinned to the peer, in which case it has no coalesced requests.
+    pinned: bool,
+    /// When the request was sent to the peer.
+    started: Instant,
+}
+
+/// Response senders of requests that are coalesced into an identical request, keyed by the
+/// request.
+///
+/// An entry exists while the request it was created for is queued or inflight.
+#[derive(Debug)]
+struct CoalescedRequests<Req, Resp> {
+    waiters: HashMap<Req, Vec<oneshot::Sender<PeerRequestResult<Resp>>>>,
+}
+
+// === impl CoalescedRequests ===
+
+impl<Req, Resp> CoalescedRequests<Req, Resp>
+where
+    Req: Clone + Eq + Hash,
+    Resp: Clone,
+{
+    /// Attaches the response sender to an identical request.
+    ///
+    /// Returns the sender if there's no such request, after which the request is tracked until
+    /// its response is sent.
+    fn attach(
+        &mut self,
+        request: &Req,
+        response: oneshot::Sender<PeerRequestResult<Resp>>,
+    ) -> Option<oneshot::Sender<PeerRequestResult<Resp>>> {
+        match self.waiters.entry(request.clone()) {
+            Entry::Occupied(mut entry) => {
+                entry.get_mut().push(response);
+                None
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(Vec::new());
+                Some(response)
+            }
+        }
+    }
+
+    /// Stops tracking the request if no sender attached to it is waiting for its response.
+    ///
+    /// Returns `true` if the request was cancelled.
+    fn cancel(&mut self, request: &Req) -> bool {
+        let Some(waiters) = self.waiters.get_mut(request) else { return true };
+        waiters.retain(|waiter| !waiter.is_closed());
+        if !waiters.is_empty() {
+            return false
+        }
+        self.waiters.remove(request);
+        true
+    }
+
+    /// Sends the response of the request to all senders attached to it and stops tracking it.
+    fn send_response(&mut self, request: &Req, peer_id: PeerId, res: &RequestResult<Resp>) {
+        for waiter in self.waiters.remove(request).unwrap_or_default() {
+            let _ = waiter.send(res.clone().map(|resp| (peer_id, resp).into()));
+        }
+    }
+}
+
+impl<Req, Resp> Default for CoalescedRequests<Req, Resp> {
+    fn default() -> Self {
+        Self { waiters: HashMap::new() }
+    }
 }
 
 /// Requests that can be sent to the Syncer from a [`FetchClient`]
@@ -369,12 +779,16 @@ pub(crate) enum DownloadRequest {
         request: HeadersRequest,
         response: oneshot::Sender<PeerRequestResult<Vec<Header>>>,
         priority: Priority,
+        /// The peer the request must be sent to, if any.
+        peer_id: Option<PeerId>,
     },
     /// Download the requested headers and send response through channel
     GetBlockBodies {
         request: Vec<B256>,
         response: oneshot::Sender<PeerRequestResult<Vec<BlockBody>>>,
         priority: Priority,
+        /// The peer the request must be sent to, if any.
+        peer_id: Option<PeerId>,
     },
 }
 
@@ -397,10 +811,38 @@ impl DownloadRequest {
         }
     }
 
+    /// Sets the priority of this request.
+    fn set_priority(&mut self, new_priority: Priority) {
+        match self {
+            DownloadRequest::GetBlockHeaders { priority, .. } => *priority = new_priority,
+            DownloadRequest::GetBlockBodies { priority, .. } => *priority = new_priority,
+        }
+    }
+
     /// Returns `true` if this request is normal priority.
     fn is_normal_priority(&self) -> bool {
         self.get_priority().is_normal()
     }
+
+    /// Returns the peer this request must be sent to, if any.
+    fn peer_id(&self) -> Option<&PeerId> {
+        match self {
+            DownloadRequest::GetBlockHeaders { peer_id, .. } => peer_id.as_ref(),
+            DownloadRequest::GetBlockBodies { peer_id, .. } => peer_id.as_ref(),
+        }
+    }
+
+    /// Fails the request with the given error.
+    fn send_err_response(self, err: RequestError) {
+        match self {
+            DownloadRequest::GetBlockHeaders { response, .. } => {
+                let _ = response.send(Err(err));
+            }
+            DownloadRequest::GetBlockBodies { response, .. } => {
+                let _ = response.send(Err(err));
+            }
+        }
+    }
 }
 
 /// An action the syncer can emit.
@@ -429,7 +871,8 @@ pub(crate) enum BlockResponseOutcome {
 mod tests {
     use super::*;
     use crate::{peers::PeersManager, PeersConfig};
-    use reth_primitives::{SealedHeader, B256, B512};
+    use reth_interfaces::p2p::{bodies::client::BodiesClient, headers::client::HeadersClient};
+    use reth_primitives::{HeadersDirection, SealedHeader, B256, B512};
     use std::future::poll_fn;
 
     #[tokio::test(flavor = "multi_thread")]
@@ -444,6 +887,7 @@ mod tests {
                 request: vec![],
                 response: tx,
                 priority: Priority::default(),
+                peer_id: None,
             });
             assert!(fetcher.poll(cx).is_pending());
 
@@ -452,6 +896,56 @@ mod tests {
         .await;
     }
 
+    #[tokio::test]
+    async fn test_queue_priority() {
+        let manager = PeersManager::new(PeersConfig::default());
+        let mut fetcher = StateFetcher::new(manager.handle(), Default::default());
+
+        let priorities = [
+            Priority::Normal,
+            Priority::High,
+            Priority::Critical,
+            Priority::Normal,
+            Priority::High,
+            Priority::Critical,
+        ];
+        for (start, priority) in priorities.into_iter().enumerate() {
+            let (tx, _rx) = oneshot::channel();
+            fetcher.queue_request(DownloadRequest::GetBlockHeaders {
+                request: HeadersRequest {
+                    start: (start as u64).into(),
+                    limit: 1,
+                    direction: Default::default(),
+                },
+                response: tx,
+                priority,
+                peer_id: None,
+            });
+        }
+
+        let dispatched = fetcher
+            .queued_requests
+            .iter()
+            .map(|req| match req {
+                DownloadRequest::GetBlockHeaders { request, priority, .. } => {
+                    (request.start, *priority)
+                }
+                DownloadRequest::GetBlockBodies { .. } => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            dispatched,
+            vec![
+                (2u64.into(), Priority::Critical),
+                (5u64.into(), Priority::Critical),
+                (1u64.into(), Priority::High),
+                (4u64.into(), Priority::High),
+                (0u64.into(), Priority::Normal),
+                (3u64.into(), Priority::Normal),
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn test_peer_rotation() {
         let manager = PeersManager::new(PeersConfig::default());
@@ -500,6 +994,48 @@ mod tests {
         assert_eq!(fetcher.next_peer(), Some(peer2));
     }
 
+    #[tokio::test]
+    async fn test_latency_weighted_peer_selection() {
+        let manager = PeersManager::new(PeersConfig::default());
+        let mut fetcher = StateFetcher::new(manager.handle(), Default::default());
+        let fast = B512::random();
+        let slow = B512::random();
+
+        // the slow peer has the lower timeout, so it's always selected by default
+        fetcher.new_active_peer(fast, B256::random(), 1, Arc::new(AtomicU64::new(300)));
+        fetcher.new_active_peer(slow, B256::random(), 2, Arc::new(AtomicU64::new(30)));
+        assert_eq!(fetcher.next_peer(), Some(slow));
+
+        let client = fetcher.client();
+        client.set_peer_selection(PeerSelectionPolicy::LatencyWeighted);
+        assert_eq!(client.peer_selection(), PeerSelectionPolicy::LatencyWeighted);
+
+        // simulate responses of differing latency
+        for _ in 0..10 {
+            fetcher.on_response_latency(&fast, Duration::from_millis(10));
+            fetcher.on_response_latency(&slow, Duration::from_millis(200));
+        }
+        assert!(fetcher.peers[&fast].latency < fetcher.peers[&slow].latency);
+
+        let mut fast_count = 0;
+        let mut slow_count = 0;
+        for _ in 0..1000 {
+            match fetcher.next_peer() {
+                Some(peer) if peer == fast => fast_count += 1,
+                Some(peer) if peer == slow => slow_count += 1,
+                peer => panic!("unexpected peer {peer:?}"),
+            }
+        }
+        assert!(fast_count > 5 * slow_count, "fast: {fast_count}, slow: {slow_count}");
+        // the slow peer is still probed
+        assert!(slow_count > 0);
+
+        // the average moves toward recent responses
+        fetcher.on_response_latency(&slow, Duration::from_millis(10));
+        let latency = fetcher.peers[&slow].latency.unwrap();
+        assert!(latency > Duration::from_millis(10) && latency < Duration::from_millis(200));
+    }
+
     #[tokio::test]
     async fn test_on_block_headers_response() {
         let manager = PeersManager::new(PeersConfig::default());
@@ -581,4 +1117,423 @@ mod tests {
 
         assert!(fetcher.peers[&peer_id].state.is_idle());
     }
+
+    #[tokio::test]
+    async fn test_wait_for_peers() {
+        let manager = PeersManager::new(PeersConfig::default());
+        let mut fetcher = StateFetcher::new(manager.handle(), Default::default());
+        let client = fetcher.client();
+
+        // resolves immediately if no peers are required
+        client.wait_for_peers(0).await;
+
+        let wait = tokio::spawn(client.wait_for_peers(2));
+
+        let peer1 = B512::random();
+        fetcher.new_active_peer(peer1, B256::random(), 1, Arc::new(AtomicU64::new(1)));
+        tokio::task::yield_now().await;
+        assert!(!wait.is_finished());
+
+        // dropping below the threshold keeps the future pending
+        fetcher.on_session_closed(&peer1);
+        fetcher.new_active_peer(B512::random(), B256::random(), 1, Arc::new(AtomicU64::new(1)));
+        tokio::task::yield_now().await;
+        assert!(!wait.is_finished());
+
+        fetcher.new_active_peer(B512::random(), B256::random(), 2, Arc::new(AtomicU64::new(1)));
+        tokio::time::timeout(std::time::Duration::from_secs(5), wait).await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_pinned_requests() {
+        let manager = PeersManager::new(PeersConfig::default());
+        let mut fetcher = StateFetcher::new(manager.handle(), Default::default());
+        // peer1 has the lowest timeout, so it's the first choice for requests that are not pinned
+        let peer1 = B512::random();
+        let peer2 = B512::random();
+        fetcher.new_active_peer(peer1, B256::random(), 1, Arc::new(AtomicU64::new(1)));
+        fetcher.new_active_peer(peer2, B256::random(), 2, Arc::new(AtomicU64::new(100)));
+
+        let client = fetcher.client();
+        let pinned = client.pinned(peer2);
+        let request =
+            HeadersRequest { start: 0u64.into(), limit: 1, direction: Default::default() };
+        let pinned_responses =
+            (0..3).map(|_| pinned.get_headers(request.clone())).collect::<Vec<_>>();
+        let _response = client.get_headers(request.clone());
+
+        poll_fn(|cx| {
+            let mut dispatched = Vec::new();
+            while let Poll::Ready(FetchAction::BlockRequest { peer_id, .. }) = fetcher.poll(cx) {
+                dispatched.push(peer_id);
+            }
+            // the remaining pinned requests wait for the pinned peer, while the request that is
+            // not pinned goes to the other idle peer
+            assert_eq!(dispatched, vec![peer2, peer1]);
+            Poll::Ready(())
+        })
+        .await;
+
+        // only the pinned peer gets the pinned requests as followups
+        assert_eq!(fetcher.on_block_headers_response(peer1, Ok(vec![Header::default()])), None);
+        for _ in 0..2 {
+            let outcome = fetcher.on_block_headers_response(peer2, Ok(vec![Header::default()]));
+            assert!(
+                matches!(outcome, Some(BlockResponseOutcome::Request(peer_id, _)) if peer_id == peer2)
+            );
+        }
+        assert_eq!(fetcher.on_block_headers_response(peer2, Ok(vec![Header::default()])), None);
+        for response in pinned_responses {
+            assert_eq!(response.await.unwrap().peer_id(), peer2);
+        }
+
+        // requests pinned to a peer that's not connected fail
+        let response = client.pinned(B512::random()).get_headers(request.clone());
+        poll_fn(|cx| {
+            assert!(fetcher.poll(cx).is_pending());
+            Poll::Ready(())
+        })
+        .await;
+        assert_eq!(response.await.unwrap_err(), RequestError::ConnectionDropped);
+
+        // queued requests pinned to a peer fail once it disconnects
+        fetcher.on_pending_disconnect(&peer2);
+        let response = pinned.get_headers(request);
+        poll_fn(|cx| {
+            assert!(fetcher.poll(cx).is_pending());
+            Poll::Ready(())
+        })
+        .await;
+        assert_eq!(fetcher.queued_requests.len(), 1);
+        fetcher.on_session_closed(&peer2);
+        assert!(fetcher.queued_requests.is_empty());
+        assert_eq!(response.await.unwrap_err(), RequestError::ConnectionDropped);
+    }
+
+    #[tokio::test]
+    async fn test_coalesced_requests() {
+        let manager = PeersManager::new(PeersConfig::default());
+        let mut fetcher = StateFetcher::new(manager.handle(), Default::default());
+        let peer1 = B512::random();
+        let peer2 = B512::random();
+        fetcher.new_active_peer(peer1, B256::random(), 1, Arc::new(AtomicU64::new(1)));
+        fetcher.new_active_peer(peer2, B256::random(), 2, Arc::new(AtomicU64::new(100)));
+
+        let client = fetcher.client();
+        let request =
+            HeadersRequest { start: 0u64.into(), limit: 1, direction: Default::default() };
+        let first = client.get_headers(request.clone());
+        let second = client.get_headers(request.clone());
+
+        poll_fn(|cx| {
+            let mut dispatched = Vec::new();
+            while let Poll::Ready(FetchAction::BlockRequest { peer_id, .. }) = fetcher.poll(cx) {
+                dispatched.push(peer_id);
+            }
+            // the identical request is not sent, even though another peer is idle
+            assert_eq!(dispatched, vec![peer1]);
+            Poll::Ready(())
+        })
+        .await;
+
+        let headers = vec![Header { number: 1, ..Default::default() }];
+        assert_eq!(fetcher.on_block_headers_response(peer1, Ok(headers.clone())), None);
+        for response in [first.await.unwrap(), second.await.unwrap()] {
+            assert_eq!(response.peer_id(), peer1);
+            assert_eq!(response.into_data(), headers);
+        }
+
+        // a request following the response is sent again
+        let _response = client.get_headers(request);
+        poll_fn(|cx| {
+            assert!(fetcher.poll(cx).is_ready());
+            Poll::Ready(())
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_coalesced_request_priority() {
+        let manager = PeersManager::new(PeersConfig::default());
+        let mut fetcher = StateFetcher::new(manager.handle(), Default::default());
+
+        let mut queue = |start: u64, priority| {
+            let (tx, rx) = oneshot::channel();
+            fetcher.queue_request(DownloadRequest::GetBlockHeaders {
+                request: HeadersRequest {
+                    start: start.into(),
+                    limit: 1,
+                    direction: Default::default(),
+                },
+                response: tx,
+                priority,
+                peer_id: None,
+            });
+            rx
+        };
+        let _normal = queue(0, Priority::Normal);
+        let _other = queue(1, Priority::Normal);
+        // a critical request joins the queued normal one
+        let critical = queue(0, Priority::Critical);
+        assert_eq!(fetcher.queued_requests.len(), 2);
+        assert!(fetcher.queued_requests[0].get_priority().is_critical());
+
+        let peer = B512::random();
+        fetcher.new_active_peer(peer, B256::random(), 1, Arc::new(AtomicU64::new(1)));
+        poll_fn(|cx| {
+            match fetcher.poll(cx) {
+                Poll::Ready(FetchAction::BlockRequest {
+                    request: BlockRequest::GetBlockHeaders(request),
+                    ..
+                }) => assert_eq!(request.start_block, 0u64.into()),
+                _ => unreachable!(),
+            }
+            Poll::Ready(())
+        })
+        .await;
+
+        let headers = vec![Header { number: 0, ..Default::default() }];
+        fetcher.on_block_headers_response(peer, Ok(headers.clone()));
+        assert_eq!(critical.await.unwrap().unwrap().into_data(), headers);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_snapshot() {
+        let manager = PeersManager::new(PeersConfig::default());
+        let mut fetcher = StateFetcher::new(manager.handle(), Default::default());
+        let client = fetcher.client();
+        assert_eq!(client.metrics_snapshot(), FetchMetrics::default());
+
+        let peer1 = B512::random();
+        let peer2 = B512::random();
+        fetcher.new_active_peer(peer1, B256::random(), 1, Arc::new(AtomicU64::new(1)));
+        fetcher.new_active_peer(peer2, B256::random(), 2, Arc::new(AtomicU64::new(100)));
+        fetcher.num_active_peers.store(2, Ordering::Relaxed);
+        assert_eq!(
+            client.metrics_snapshot(),
+            FetchMetrics { active_peers: 2, idle_peers: 2, ..Default::default() }
+        );
+
+        let request =
+            HeadersRequest { start: 0u64.into(), limit: 1, direction: Default::default() };
+        let response = client.get_headers(request.clone());
+        poll_fn(|cx| {
+            assert!(fetcher.poll(cx).is_ready());
+            Poll::Ready(())
+        })
+        .await;
+        assert_eq!(
+            client.metrics_snapshot(),
+            FetchMetrics {
+                active_peers: 2,
+                idle_peers: 1,
+                inflight: 1,
+                dispatched_total: 1,
+                failed_total: 0
+            }
+        );
+
+        // a failed response frees the peer
+        assert_eq!(fetcher.on_block_headers_response(peer1, Err(RequestError::BadResponse)), None);
+        assert_eq!(response.await.unwrap_err(), RequestError::BadResponse);
+        assert_eq!(
+            client.metrics_snapshot(),
+            FetchMetrics {
+                active_peers: 2,
+                idle_peers: 2,
+                inflight: 0,
+                dispatched_total: 1,
+                failed_total: 1
+            }
+        );
+
+        // requests dropped on disconnect count as failed
+        let response = client.get_headers(request);
+        poll_fn(|cx| {
+            assert!(fetcher.poll(cx).is_ready());
+            Poll::Ready(())
+        })
+        .await;
+        fetcher.on_session_closed(&peer1);
+        fetcher.num_active_peers.store(1, Ordering::Relaxed);
+        assert_eq!(response.await.unwrap_err(), RequestError::ConnectionDropped);
+        assert_eq!(
+            client.metrics_snapshot(),
+            FetchMetrics {
+                active_peers: 1,
+                idle_peers: 1,
+                inflight: 0,
+                dispatched_total: 2,
+                failed_total: 2
+            }
+        );
+
+        // a peer about to disconnect is no longer idle
+        fetcher.on_pending_disconnect(&peer2);
+        assert_eq!(client.metrics_snapshot().idle_peers, 0);
+    }
+
+    #[tokio::test]
+    async fn test_pending_request_count() {
+        let manager = PeersManager::new(PeersConfig::default());
+        let mut fetcher = StateFetcher::new(manager.handle(), Default::default());
+        let client = fetcher.client();
+
+        // requests queue up while no peer is available
+        let _responses = (0..3u64)
+            .map(|start| {
+                client.get_headers(HeadersRequest {
+                    start: start.into(),
+                    limit: 1,
+                    direction: Default::default(),
+                })
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(client.pending_request_count(), 0);
+        poll_fn(|cx| {
+            assert!(fetcher.poll(cx).is_pending());
+            Poll::Ready(())
+        })
+        .await;
+        assert_eq!(client.pending_request_count(), 3);
+        assert!(client.is_saturated(3));
+        assert!(!client.is_saturated(4));
+
+        // and are drained as peers become idle
+        let peer = B512::random();
+        fetcher.new_active_peer(peer, B256::random(), 1, Arc::new(AtomicU64::new(1)));
+        poll_fn(|cx| {
+            assert!(fetcher.poll(cx).is_ready());
+            assert!(fetcher.poll(cx).is_pending());
+            Poll::Ready(())
+        })
+        .await;
+        assert_eq!(client.pending_request_count(), 2);
+        assert!(!client.is_saturated(3));
+
+        let outcome = fetcher.on_block_headers_response(peer, Ok(vec![Header::default()]));
+        assert!(
+            matches!(outcome, Some(BlockResponseOutcome::Request(peer_id, _)) if peer_id == peer)
+        );
+        assert_eq!(client.pending_request_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_race_headers() {
+        let manager = PeersManager::new(PeersConfig::default());
+        let mut fetcher = StateFetcher::new(manager.handle(), Default::default());
+        let peer = B512::random();
+        fetcher.new_active_peer(peer, B256::random(), 1, Arc::new(AtomicU64::new(1)));
+        let client = fetcher.client();
+
+        let race = client.race_headers((0..3u64).map(|start| HeadersRequest {
+            start: start.into(),
+            limit: 1,
+            direction: Default::default(),
+        }));
+        poll_fn(|cx| {
+            assert!(fetcher.poll(cx).is_ready());
+            assert!(fetcher.poll(cx).is_pending());
+            Poll::Ready(())
+        })
+        .await;
+        assert_eq!(client.pending_request_count(), 2);
+
+        // the first request succeeds, and the second one is sent as a followup
+        let outcome = fetcher.on_block_headers_response(peer, Ok(vec![Header::default()]));
+        assert!(
+            matches!(outcome, Some(BlockResponseOutcome::Request(peer_id, _)) if peer_id == peer)
+        );
+        let response = race.await.unwrap();
+        assert_eq!(response.peer_id(), peer);
+        assert_eq!(response.into_data(), vec![Header::default()]);
+
+        // the inflight request's response is discarded
+        assert!(fetcher.inflight_headers_requests[&peer].response.is_closed());
+        assert_eq!(fetcher.on_block_headers_response(peer, Ok(vec![Header::default()])), None);
+
+        // the queued request is dropped
+        poll_fn(|cx| {
+            assert!(fetcher.poll(cx).is_pending());
+            Poll::Ready(())
+        })
+        .await;
+        assert_eq!(client.pending_request_count(), 0);
+        assert!(fetcher.coalesced_headers_requests.waiters.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_result_tap() {
+        let manager = PeersManager::new(PeersConfig::default());
+        let mut fetcher = StateFetcher::new(manager.handle(), Default::default());
+        let peer = B512::random();
+        fetcher.new_active_peer(peer, B256::random(), 1, Arc::new(AtomicU64::new(1)));
+
+        let results = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let client = fetcher.client().with_result_tap({
+            let results = Arc::clone(&results);
+            move |result: &FetchResult| results.lock().unwrap().push(result.clone())
+        });
+
+        let request =
+            HeadersRequest { start: 0u64.into(), limit: 1, direction: Default::default() };
+        let response = client.get_headers(request);
+        poll_fn(|cx| {
+            assert!(fetcher.poll(cx).is_ready());
+            Poll::Ready(())
+        })
+        .await;
+        assert!(results.lock().unwrap().is_empty());
+
+        fetcher.on_block_headers_response(peer, Ok(vec![Header::default()]));
+        assert_eq!(response.await.unwrap().peer_id(), peer);
+
+        let bodies = client.get_block_bodies(vec![B256::random()]);
+        poll_fn(|cx| {
+            assert!(fetcher.poll(cx).is_ready());
+            Poll::Ready(())
+        })
+        .await;
+        fetcher.on_session_closed(&peer);
+        assert_eq!(bodies.await.unwrap_err(), RequestError::ConnectionDropped);
+
+        let results = results.lock().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].kind, FetchRequestKind::Headers);
+        assert_eq!(results[0].peer_id, Some(peer));
+        assert_eq!(results[0].result, Ok(1));
+        assert_eq!(results[1].kind, FetchRequestKind::Bodies);
+        assert_eq!(results[1].peer_id, None);
+        assert_eq!(results[1].result, Err(RequestError::ConnectionDropped));
+    }
+
+    #[tokio::test]
+    async fn test_headers_order() {
+        let manager = PeersManager::new(PeersConfig::default());
+        let mut fetcher = StateFetcher::new(manager.handle(), Default::default());
+        let peer = B512::random();
+        fetcher.new_active_peer(peer, B256::random(), 3, Arc::new(AtomicU64::new(1)));
+        let client = fetcher.client();
+
+        // the peer returns the headers in the falling order of the request
+        let request =
+            HeadersRequest { start: 3u64.into(), limit: 4, direction: HeadersDirection::Falling };
+        let response = client.get_headers_with_priority_and_order(
+            request,
+            Priority::Normal,
+            HeadersDirection::Rising,
+        );
+        poll_fn(|cx| {
+            assert!(fetcher.poll(cx).is_ready());
+            Poll::Ready(())
+        })
+        .await;
+        let headers =
+            (0..=3).rev().map(|number| Header { number, ..Default::default() }).collect::<Vec<_>>();
+        fetcher.on_block_headers_response(peer, Ok(headers));
+
+        let numbers =
+            response.await.unwrap().into_data().iter().map(|h| h.number).collect::<Vec<_>>();
+        assert_eq!(numbers, vec![0, 1, 2, 3]);
+    }
 }
@@ -138,7 +138,10 @@ pub mod transactions;
 pub use builder::NetworkBuilder;
 pub use config::{NetworkConfig, NetworkConfigBuilder};
 pub use discovery::{Discovery, DiscoveryEvent};
-pub use fetch::FetchClient;
+pub use fetch::{
+    FetchClient, FetchMetrics, FetchRequestKind, FetchResult, FetchShutdownSummary,
+    PeerSelectionPolicy, PinnedFetchClient,
+};
 pub use manager::{NetworkEvent, NetworkManager};
 pub use message::PeerRequest;
 pub use network::{NetworkEvents, NetworkHandle, NetworkProtocols};
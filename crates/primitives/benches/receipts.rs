@@ -0,0 +1,44 @@
+use alloy_rlp::Encodable;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use reth_primitives::{Address, Bytes, Log, Receipt, TxType, B256};
+use std::{hint::black_box, time::Duration};
+
+/// Benchmarks the encoding of typed receipts with large logs.
+pub fn receipts_benchmark(c: &mut Criterion) {
+    let mut g = c.benchmark_group("receipts");
+    g.warm_up_time(Duration::from_secs(1));
+    g.noise_threshold(0.02);
+
+    for len in [0x100, 0x10000, 0xffffff] {
+        let receipt = large_receipt(len).with_bloom();
+        g.bench_with_input(BenchmarkId::new("encode", len), &receipt, |b, receipt| {
+            b.iter(|| {
+                let mut out = Vec::with_capacity(receipt.length());
+                black_box(receipt).encode(&mut out);
+                out
+            })
+        });
+    }
+}
+
+/// Returns an EIP-1559 receipt with two logs of `len` bytes of data each.
+fn large_receipt(len: usize) -> Receipt {
+    let log = Log {
+        address: Address::random(),
+        topics: vec![B256::random()],
+        data: Bytes::from(vec![1; len]),
+    };
+    Receipt {
+        tx_type: TxType::EIP1559,
+        success: true,
+        cumulative_gas_used: 16747627,
+        logs: vec![log.clone(), log],
+        #[cfg(feature = "optimism")]
+        deposit_nonce: None,
+        #[cfg(feature = "optimism")]
+        deposit_receipt_version: None,
+    }
+}
+
+criterion_group!(benches, receipts_benchmark);
+criterion_main!(benches);
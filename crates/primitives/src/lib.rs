@@ -76,7 +76,11 @@ pub use prune::{
     PruneCheckpoint, PruneMode, PruneModes, PruneProgress, PruneSegment, PruneSegmentError,
     ReceiptsLogPruneConfig, MINIMUM_PRUNING_DISTANCE,
 };
-pub use receipt::{Receipt, ReceiptWithBloom, ReceiptWithBloomRef, Receipts};
+pub use receipt::{
+    BlockLogEntry, Receipt, ReceiptDiff, ReceiptDiffKind, ReceiptError, ReceiptProof,
+    ReceiptWithBloom, ReceiptWithBloomRef, ReceiptWithMeta, Receipts, ReceiptsBuilder,
+    RECEIPT_CODEC_VERSION,
+};
 pub use serde_helper::JsonU256;
 pub use snapshot::SnapshotSegment;
 pub use storage::StorageEntry;
@@ -93,8 +97,8 @@ pub use transaction::{
     AccessList, AccessListItem, FromRecoveredTransaction, IntoRecoveredTransaction,
     InvalidTransactionError, Signature, Transaction, TransactionKind, TransactionMeta,
     TransactionSigned, TransactionSignedEcRecovered, TransactionSignedNoHash, TxEip1559, TxEip2930,
-    TxEip4844, TxHashOrNumber, TxLegacy, TxType, TxValue, EIP1559_TX_TYPE_ID, EIP2930_TX_TYPE_ID,
-    EIP4844_TX_TYPE_ID, LEGACY_TX_TYPE_ID,
+    TxEip4844, TxHashOrNumber, TxLegacy, TxType, TxValue, UnknownTxType, EIP1559_TX_TYPE_ID,
+    EIP2930_TX_TYPE_ID, EIP4844_TX_TYPE_ID, LEGACY_TX_TYPE_ID,
 };
 pub use withdrawal::Withdrawal;
 
@@ -126,6 +130,8 @@ pub type H512 = B512;
 
 #[cfg(any(test, feature = "arbitrary"))]
 pub use arbitrary;
+#[cfg(any(test, feature = "arbitrary"))]
+pub use receipt::arb_receipts;
 
 #[cfg(feature = "c-kzg")]
 pub use c_kzg as kzg;
@@ -4,8 +4,8 @@ use crate::{
     constants::EMPTY_OMMER_ROOT_HASH,
     keccak256,
     trie::{HashBuilder, Nibbles, TrieAccount},
-    Address, Header, Receipt, ReceiptWithBloom, ReceiptWithBloomRef, TransactionSigned, Withdrawal,
-    B256,
+    Address, Bytes, Header, Receipt, ReceiptWithBloom, ReceiptWithBloomRef, TransactionSigned,
+    Withdrawal, B256,
 };
 use alloy_primitives::U256;
 use alloy_rlp::Encodable;
@@ -29,14 +29,45 @@ pub fn ordered_trie_root<T: Encodable>(items: &[T]) -> B256 {
 }
 
 /// Compute a trie root of the collection of items with a custom encoder.
-pub fn ordered_trie_root_with_encoder<T, F>(items: &[T], mut encode: F) -> B256
+pub fn ordered_trie_root_with_encoder<T, F>(items: &[T], encode: F) -> B256
+where
+    F: FnMut(&T, &mut dyn BufMut),
+{
+    ordered_trie_with_encoder(HashBuilder::default(), items, encode).root()
+}
+
+/// Compute a trie root of the collection of items with a custom encoder, along with the proof of
+/// inclusion of the item at `index`.
+///
+/// The proof is the list of rlp-serialized trie nodes, starting from the root node and following
+/// the path of the rlp encoded `index`.
+pub fn ordered_trie_proof_with_encoder<T, F>(
+    items: &[T],
+    index: usize,
+    encode: F,
+) -> (B256, Vec<Bytes>)
+where
+    F: FnMut(&T, &mut dyn BufMut),
+{
+    let mut index_buffer = BytesMut::new();
+    index.encode(&mut index_buffer);
+    let target = Nibbles::unpack(&index_buffer);
+
+    let hb = HashBuilder::default().with_proof_retainer(vec![target]);
+    let mut hb = ordered_trie_with_encoder(hb, items, encode);
+    let root = hb.root();
+
+    (root, hb.take_proofs().into_values().collect())
+}
+
+/// Adds the collection of items to the hash builder, keyed by their rlp encoded index.
+fn ordered_trie_with_encoder<T, F>(mut hb: HashBuilder, items: &[T], mut encode: F) -> HashBuilder
 where
     F: FnMut(&T, &mut dyn BufMut),
 {
     let mut index_buffer = BytesMut::new();
     let mut value_buffer = BytesMut::new();
 
-    let mut hb = HashBuilder::default();
     let items_len = items.len();
     for i in 0..items_len {
         let index = adjust_index_for_rlp(i, items_len);
@@ -50,7 +81,7 @@ where
         hb.add_leaf(Nibbles::unpack(&index_buffer), &value_buffer);
     }
 
-    hb.root()
+    hb
 }
 
 /// Calculate a transaction root.
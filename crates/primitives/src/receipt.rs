@@ -1,24 +1,53 @@
 use crate::{
-    compression::{RECEIPT_COMPRESSOR, RECEIPT_DECOMPRESSOR},
+    compression::{
+        RECEIPT_COMPRESSOR, RECEIPT_COMPRESSOR as RECEIPTV0_COMPRESSOR, RECEIPT_DECOMPRESSOR,
+        RECEIPT_DECOMPRESSOR as RECEIPTV0_DECOMPRESSOR, RECEIPT_DICTIONARY,
+    },
     logs_bloom,
     proofs::calculate_receipt_root_ref,
-    Bloom, Log, PruneSegmentError, TxType, B256,
+    Address, BlockNumber, Bloom, BloomInput, Bytes, Log, PruneSegmentError, TxType, B256,
 };
 use alloy_rlp::{length_of_length, Decodable, Encodable};
 use bytes::{Buf, BufMut, BytesMut};
+use rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
 use reth_codecs::{add_arbitrary_tests, main_codec, Compact, CompactZstd};
+use serde::{Deserialize, Serialize};
 use std::{
     cmp::Ordering,
+    collections::{HashMap, HashSet},
     ops::{Deref, DerefMut},
 };
+use zstd::bulk::{Compressor, Decompressor};
 
 #[cfg(any(test, feature = "arbitrary"))]
 use proptest::strategy::Strategy;
 
+/// Bits that are all set in the version byte the [Compact] encoding of a [Receipt] starts with.
+///
+/// In receipts encoded before versioning was introduced, these bits of the first byte hold the
+/// length of `cumulative_gas_used` in bytes, which is at most 8, so they can't be all set.
+const RECEIPT_VERSION_MARKER: u8 = 0b0111_1000;
+
+/// Bits of the version byte of a [Receipt] that hold the version of its [Compact] encoding.
+const RECEIPT_VERSION_BITS: u8 = 0b0000_0111;
+
+/// Bit of the version byte of a [Receipt] that is set if the fields following it are compressed.
+const RECEIPT_ZSTD_BIT: u8 = 0b1000_0000;
+
+/// Current version of the [Compact] encoding of a [Receipt].
+///
+/// Receipts encoded before versioning was introduced are version 0, and have no version byte.
+pub const RECEIPT_CODEC_VERSION: u8 = 1;
+
+/// Encoded length of block receipts below which [ReceiptWithBloom::encode_blocks_par] encodes
+/// them serially, since spawning rayon tasks costs more than encoding them.
+const PAR_ENCODE_THRESHOLD: usize = 128 * 1024;
+
 /// Receipt containing result of transaction execution.
-#[main_codec(no_arbitrary, zstd)]
-#[add_arbitrary_tests]
-#[derive(Clone, Debug, PartialEq, Eq, Default)]
+///
+/// Its [Compact] encoding is versioned, see [RECEIPT_CODEC_VERSION].
+#[add_arbitrary_tests(compact)]
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub struct Receipt {
     /// Receipt type.
     pub tx_type: TxType,
@@ -55,6 +84,337 @@ impl Receipt {
     pub fn with_bloom(self) -> ReceiptWithBloom {
         self.into()
     }
+
+    /// Returns `true` if the receipt has identical logs, with the same address, topics and data,
+    /// at different positions.
+    ///
+    /// This is legal but rare, so it's only a heuristic to flag anomalies, e.g. malformed
+    /// execution.
+    pub fn has_duplicate_logs(&self) -> bool {
+        has_duplicate_logs(self.logs.iter())
+    }
+
+    /// Estimates the length of the receipt serialized as JSON, without serializing it.
+    ///
+    /// Log data and hashes are hex encoded, so they take twice their size plus the `0x` prefix.
+    /// The estimate assumes the longest transaction type and status, so it's never below the
+    /// actual length, and only exceeds it by a few bytes.
+    pub fn estimated_json_len(&self) -> usize {
+        // `{"tx_type":"EIP1559","success":false,"cumulative_gas_used":,"logs":[]}`
+        const RECEIPT_JSON_OVERHEAD: usize = 70;
+        // `{"address":"0x<40 hex>","topics":[],"data":"0x"}`
+        const LOG_JSON_OVERHEAD: usize = 80;
+        // `"0x<64 hex>"`
+        const TOPIC_JSON_LEN: usize = 68;
+
+        let logs_len = self
+            .logs
+            .iter()
+            .map(|log| {
+                LOG_JSON_OVERHEAD + log.topics.len() * (TOPIC_JSON_LEN + 1) -
+                    usize::from(!log.topics.is_empty()) +
+                    log.data.len() * 2
+            })
+            .sum::<usize>() +
+            self.logs.len().saturating_sub(1);
+
+        let len = RECEIPT_JSON_OVERHEAD + json_number_len(self.cumulative_gas_used) + logs_len;
+
+        #[cfg(feature = "optimism")]
+        let len = {
+            // `,"deposit_nonce":,"deposit_receipt_version":`
+            const OPTIMISM_JSON_OVERHEAD: usize = 44;
+            let optional_number_len = |number: Option<u64>| number.map_or(4, json_number_len);
+            len + OPTIMISM_JSON_OVERHEAD +
+                optional_number_len(self.deposit_nonce) +
+                optional_number_len(self.deposit_receipt_version)
+        };
+
+        len
+    }
+
+    /// Returns `false` if the receipt's bloom filter rules out a log emitted by `address` with all
+    /// of `topics`, in which case its logs don't need to be scanned.
+    ///
+    /// The bloom is calculated from the logs, see [ReceiptWithBloom::bloom_may_contain] to use a
+    /// cached one instead.
+    pub fn bloom_may_contain(&self, address: &Address, topics: &[B256]) -> bool {
+        bloom_may_contain(&self.bloom_slow(), address, topics)
+    }
+
+    /// Returns `true` if the receipt is of a legacy transaction.
+    pub fn is_legacy(&self) -> bool {
+        matches!(self.tx_type, TxType::Legacy)
+    }
+
+    /// Returns `true` if the receipt is of an [EIP-2930](https://eips.ethereum.org/EIPS/eip-2930)
+    /// transaction.
+    pub fn is_eip2930(&self) -> bool {
+        matches!(self.tx_type, TxType::EIP2930)
+    }
+
+    /// Returns `true` if the receipt is of an [EIP-1559](https://eips.ethereum.org/EIPS/eip-1559)
+    /// transaction.
+    pub fn is_eip1559(&self) -> bool {
+        matches!(self.tx_type, TxType::EIP1559)
+    }
+
+    /// Returns `true` if the receipt is of an [EIP-4844](https://eips.ethereum.org/EIPS/eip-4844)
+    /// transaction.
+    pub fn is_eip4844(&self) -> bool {
+        matches!(self.tx_type, TxType::EIP4844)
+    }
+
+    /// Returns `true` if the receipt is of an Optimism deposit transaction.
+    #[cfg(feature = "optimism")]
+    pub fn is_deposit(&self) -> bool {
+        matches!(self.tx_type, TxType::DEPOSIT)
+    }
+
+    /// Returns the [EIP-658](https://eips.ethereum.org/EIPS/eip-658) status code of the receipt,
+    /// `1` if the transaction succeeded and `0` otherwise, e.g. for the RPC `status` field.
+    pub fn status_code(&self) -> u8 {
+        self.success as u8
+    }
+
+    /// Returns the number of logs emitted in the receipt.
+    pub fn logs_len(&self) -> usize {
+        self.logs.len()
+    }
+
+    /// Returns the total length of the data of all logs emitted in the receipt.
+    pub fn total_log_data_len(&self) -> usize {
+        self.logs.iter().map(|log| log.data.len()).sum()
+    }
+
+    /// Returns the version of the [Compact] encoding of a receipt, i.e. [RECEIPT_CODEC_VERSION] or
+    /// `0` for receipts encoded before versioning was introduced.
+    ///
+    /// Returns an error if the receipt was encoded with an unknown version, e.g. by a newer
+    /// client.
+    pub fn compact_version(buf: &[u8]) -> Result<u8, ReceiptError> {
+        match buf.first() {
+            Some(&byte) if byte & RECEIPT_VERSION_MARKER == RECEIPT_VERSION_MARKER => {
+                match byte & RECEIPT_VERSION_BITS {
+                    RECEIPT_CODEC_VERSION => Ok(RECEIPT_CODEC_VERSION),
+                    version => Err(ReceiptError::UnsupportedCodecVersion(version)),
+                }
+            }
+            _ => Ok(0),
+        }
+    }
+
+    /// Decodes a receipt encoded with the current version of its [Compact] encoding, and migrates
+    /// receipts encoded with previous versions.
+    ///
+    /// Returns an error if the receipt was encoded with an unknown version, see
+    /// [Receipt::compact_version], or if its fields fail to decompress.
+    pub fn try_from_compact(buf: &[u8], len: usize) -> Result<(Self, &[u8]), ReceiptError> {
+        match Self::compact_version(buf)? {
+            RECEIPT_CODEC_VERSION if buf[0] & RECEIPT_ZSTD_BIT != 0 => {
+                let compressed = &buf[1..len];
+                let capacity =
+                    Decompressor::upper_bound(compressed).ok_or(ReceiptError::Decompression)?;
+                let fields = RECEIPT_DECOMPRESSOR
+                    .with(|decompressor| decompressor.borrow_mut().decompress(compressed, capacity))
+                    .map_err(|_| ReceiptError::Decompression)?;
+                let (receipt, _) = ReceiptV1::from_compact(&fields, fields.len());
+                Ok((receipt.into(), &buf[len..]))
+            }
+            RECEIPT_CODEC_VERSION => {
+                let (receipt, buf) = ReceiptV1::from_compact(&buf[1..], len - 1);
+                Ok((receipt.into(), buf))
+            }
+            _ => {
+                let (receipt, buf) = ReceiptV0::from_compact(buf, len);
+                Ok((receipt.into(), buf))
+            }
+        }
+    }
+
+    /// Encodes the receipt with the current version of its [Compact] encoding, compressing its
+    /// fields with the given compressor if they are large enough.
+    ///
+    /// Returns the number of bytes written.
+    fn to_compact_with<B>(self, buf: &mut B, compressor: &mut Compressor<'_>) -> usize
+    where
+        B: BufMut + AsMut<[u8]>,
+    {
+        let mut fields = Vec::with_capacity(64);
+        ReceiptV1::from(self).to_compact(&mut fields);
+
+        // Small receipts are not worth compressing
+        if fields.len() > 7 {
+            let compressed = compressor.compress(&fields).expect("Failed to compress.");
+            buf.put_u8(RECEIPT_VERSION_MARKER | RECEIPT_ZSTD_BIT | RECEIPT_CODEC_VERSION);
+            buf.put_slice(&compressed);
+            1 + compressed.len()
+        } else {
+            buf.put_u8(RECEIPT_VERSION_MARKER | RECEIPT_CODEC_VERSION);
+            buf.put_slice(&fields);
+            1 + fields.len()
+        }
+    }
+
+    /// Returns an upper bound on the length of the [Compact] encoding of the receipt, e.g. to
+    /// pre-allocate write buffers.
+    ///
+    /// Since larger receipts are compressed, the bound assumes incompressible fields, so it exceeds
+    /// the uncompressed length by the worst case overhead of zstd.
+    pub fn compact_size_hint(&self) -> usize {
+        // Flags of the small fields, e.g. the transaction type and status
+        const FLAGS_MAX_LEN: usize = 4;
+        // Flags and address
+        const LOG_OVERHEAD: usize = 1 + 20;
+        // Each topic is prefixed by its length
+        const TOPIC_LEN: usize = 1 + 32;
+
+        let logs_len = self
+            .logs
+            .iter()
+            .map(|log| {
+                let len = LOG_OVERHEAD +
+                    varuint_len(log.topics.len()) +
+                    log.topics.len() * TOPIC_LEN +
+                    varuint_len(log.data.len()) +
+                    log.data.len();
+                varuint_len(len) + len
+            })
+            .sum::<usize>();
+
+        // extended transaction type and cumulative gas used
+        let len = 1 + 8 + varuint_len(self.logs.len()) + logs_len;
+
+        // deposit nonce and receipt version
+        #[cfg(feature = "optimism")]
+        let len = len + 2 * 8;
+
+        // the version byte, followed by the fields, compressed along with their flags
+        1 + zstd::zstd_safe::compress_bound(FLAGS_MAX_LEN + len)
+    }
+
+    /// Encodes the receipt with [Compact], compressing it at the given zstd level instead of the
+    /// level of [RECEIPT_COMPRESSOR].
+    ///
+    /// The output decodes with [Compact] as usual, since the level doesn't affect decompression.
+    /// This is meant for one-off encodes, e.g. archival exports, as it creates a new compressor on
+    /// every call.
+    ///
+    /// Returns the number of bytes written, or an error if the compressor can't be created, e.g.
+    /// for an out of range level.
+    pub fn to_compact_with_level<B>(&self, buf: &mut B, level: i32) -> std::io::Result<usize>
+    where
+        B: BufMut + AsMut<[u8]>,
+    {
+        let mut compressor = Compressor::with_dictionary(level, &RECEIPT_DICTIONARY)?;
+        Ok(self.clone().to_compact_with(buf, &mut compressor))
+    }
+}
+
+impl Compact for Receipt {
+    /// Returns the number of bytes written.
+    fn to_compact<B>(self, buf: &mut B) -> usize
+    where
+        B: BufMut + AsMut<[u8]>,
+    {
+        RECEIPT_COMPRESSOR
+            .with(|compressor| self.to_compact_with(buf, &mut compressor.borrow_mut()))
+    }
+
+    /// See [Receipt::try_from_compact], which should be used instead to decode receipts that may
+    /// have been encoded by a newer client, as the database and snapshot readers do.
+    ///
+    /// # Panics
+    ///
+    /// If the receipt can't be decoded, since [Compact] can't report errors.
+    fn from_compact(buf: &[u8], len: usize) -> (Self, &[u8]) {
+        Self::try_from_compact(buf, len).expect("failed to decode receipt")
+    }
+}
+
+/// Fields of a [Receipt] in the version 0 of its [Compact] encoding, used before the encoding was
+/// versioned. Large receipts were compressed along with their fields.
+#[derive(Clone, Debug, PartialEq, Eq, Default, CompactZstd)]
+struct ReceiptV0 {
+    /// Receipt type.
+    tx_type: TxType,
+    /// If transaction is executed successfully.
+    success: bool,
+    /// Gas used
+    cumulative_gas_used: u64,
+    /// Log send from contracts.
+    logs: Vec<Log>,
+    /// Deposit nonce for Optimism deposit transactions
+    #[cfg(feature = "optimism")]
+    deposit_nonce: Option<u64>,
+    /// Deposit receipt version for Optimism deposit transactions
+    #[cfg(feature = "optimism")]
+    deposit_receipt_version: Option<u64>,
+}
+
+impl From<ReceiptV0> for Receipt {
+    fn from(receipt: ReceiptV0) -> Self {
+        Self {
+            tx_type: receipt.tx_type,
+            success: receipt.success,
+            cumulative_gas_used: receipt.cumulative_gas_used,
+            logs: receipt.logs,
+            #[cfg(feature = "optimism")]
+            deposit_nonce: receipt.deposit_nonce,
+            #[cfg(feature = "optimism")]
+            deposit_receipt_version: receipt.deposit_receipt_version,
+        }
+    }
+}
+
+/// Fields of a [Receipt] in the version 1 of its [Compact] encoding. They follow the version byte,
+/// which tells whether they are compressed.
+#[derive(Clone, Debug, PartialEq, Eq, Default, Compact)]
+struct ReceiptV1 {
+    /// Receipt type.
+    tx_type: TxType,
+    /// If transaction is executed successfully.
+    success: bool,
+    /// Gas used
+    cumulative_gas_used: u64,
+    /// Log send from contracts.
+    logs: Vec<Log>,
+    /// Deposit nonce for Optimism deposit transactions
+    #[cfg(feature = "optimism")]
+    deposit_nonce: Option<u64>,
+    /// Deposit receipt version for Optimism deposit transactions
+    #[cfg(feature = "optimism")]
+    deposit_receipt_version: Option<u64>,
+}
+
+impl From<Receipt> for ReceiptV1 {
+    fn from(receipt: Receipt) -> Self {
+        Self {
+            tx_type: receipt.tx_type,
+            success: receipt.success,
+            cumulative_gas_used: receipt.cumulative_gas_used,
+            logs: receipt.logs,
+            #[cfg(feature = "optimism")]
+            deposit_nonce: receipt.deposit_nonce,
+            #[cfg(feature = "optimism")]
+            deposit_receipt_version: receipt.deposit_receipt_version,
+        }
+    }
+}
+
+impl From<ReceiptV1> for Receipt {
+    fn from(receipt: ReceiptV1) -> Self {
+        Self {
+            tx_type: receipt.tx_type,
+            success: receipt.success,
+            cumulative_gas_used: receipt.cumulative_gas_used,
+            logs: receipt.logs,
+            #[cfg(feature = "optimism")]
+            deposit_nonce: receipt.deposit_nonce,
+            #[cfg(feature = "optimism")]
+            deposit_receipt_version: receipt.deposit_receipt_version,
+        }
+    }
 }
 
 /// A collection of receipts organized as a two-dimensional vector.
@@ -70,6 +430,11 @@ impl Receipts {
         Self { receipt_vec: vec![] }
     }
 
+    /// Create a new `Receipts` instance with space for the receipts of at least `blocks` blocks.
+    pub fn with_capacity(blocks: usize) -> Self {
+        Self { receipt_vec: Vec::with_capacity(blocks) }
+    }
+
     /// Create a new `Receipts` instance from an existing vector.
     pub fn from_vec(vec: Vec<Vec<Option<Receipt>>>) -> Self {
         Self { receipt_vec: vec }
@@ -90,11 +455,172 @@ impl Receipts {
         self.receipt_vec.is_empty()
     }
 
+    /// Shrinks the capacity of the `Receipts` vector and of the receipts of every block as much as
+    /// possible, e.g. after truncating it on a reorg.
+    pub fn shrink_to_fit(&mut self) {
+        self.receipt_vec.iter_mut().for_each(Vec::shrink_to_fit);
+        self.receipt_vec.shrink_to_fit();
+    }
+
+    /// Returns the number of receipts across all blocks that are not pruned.
+    pub fn count_present(&self) -> usize {
+        self.receipt_vec.iter().map(|receipts| receipts.iter().flatten().count()).sum()
+    }
+
+    /// Returns the number of pruned receipts across all blocks.
+    pub fn count_pruned(&self) -> usize {
+        self.receipt_vec
+            .iter()
+            .map(|receipts| receipts.iter().filter(|receipt| receipt.is_none()).count())
+            .sum()
+    }
+
+    /// Removes the trailing blocks without receipts, e.g. left behind by range operations.
+    ///
+    /// Empty blocks followed by a block with receipts are genuinely empty blocks, and are kept.
+    pub fn compact_trailing_empty(&mut self) {
+        while self.receipt_vec.last().is_some_and(Vec::is_empty) {
+            self.receipt_vec.pop();
+        }
+    }
+
+    /// Returns the receipts that are not pruned, paired with their block index and transaction
+    /// index within the block.
+    ///
+    /// This is a compact representation of sparse sets, see [Receipts::from_sparse].
+    pub fn to_sparse(&self) -> Vec<((usize, usize), Receipt)> {
+        self.receipt_vec
+            .iter()
+            .enumerate()
+            .flat_map(|(block, receipts)| {
+                receipts.iter().enumerate().filter_map(move |(tx, receipt)| {
+                    receipt.as_ref().map(|receipt| ((block, tx), receipt.clone()))
+                })
+            })
+            .collect()
+    }
+
+    /// Creates a new `Receipts` instance with the number of transactions of every block given by
+    /// `shape`, from the receipts returned by [Receipts::to_sparse]. All other receipts are
+    /// pruned.
+    ///
+    /// Returns `None` if a receipt is outside the shape.
+    pub fn from_sparse(
+        shape: &[usize],
+        sparse: impl IntoIterator<Item = ((usize, usize), Receipt)>,
+    ) -> Option<Self> {
+        let mut receipt_vec = shape.iter().map(|txs| vec![None; *txs]).collect::<Vec<_>>();
+        for ((block, tx), receipt) in sparse {
+            *receipt_vec.get_mut(block)?.get_mut(tx)? = Some(receipt);
+        }
+        Some(Self { receipt_vec })
+    }
+
+    /// Creates a new `Receipts` instance from `(block_number, tx_index, receipt)` tuples sorted by
+    /// block number and transaction index, e.g. from a scan of the receipts table.
+    ///
+    /// The first block is `first_block` and the last one is the block of the last tuple. Blocks
+    /// without tuples are empty and transactions without a tuple are pruned.
+    ///
+    /// Returns [ReceiptError::OutOfOrder] if a tuple is not strictly after the previous one, or
+    /// is before `first_block`.
+    pub fn from_sorted_tuples(
+        first_block: BlockNumber,
+        tuples: impl IntoIterator<Item = (BlockNumber, usize, Receipt)>,
+    ) -> Result<Self, ReceiptError> {
+        let mut receipt_vec: Vec<Vec<Option<Receipt>>> = Vec::new();
+        for (block_number, tx_index, receipt) in tuples {
+            let out_of_order = || ReceiptError::OutOfOrder { block_number, tx_index };
+            let block_index = block_number.checked_sub(first_block).ok_or_else(out_of_order)?;
+            let block_index = block_index as usize;
+            if block_index + 1 < receipt_vec.len() {
+                return Err(out_of_order())
+            }
+            receipt_vec.resize_with(block_index + 1, Vec::new);
+
+            let receipts = &mut receipt_vec[block_index];
+            if tx_index < receipts.len() {
+                return Err(out_of_order())
+            }
+            receipts.resize(tx_index, None);
+            receipts.push(Some(receipt));
+        }
+        Ok(Self { receipt_vec })
+    }
+
+    /// Returns the receipts of the only block, or `None` if there are no blocks or more than one.
+    pub fn single_block(&self) -> Option<&Vec<Option<Receipt>>> {
+        match self.receipt_vec.as_slice() {
+            [receipts] => Some(receipts),
+            _ => None,
+        }
+    }
+
+    /// Returns an iterator over the receipts of every block, paired with the block number, given
+    /// the number of the first block.
+    pub fn iter_with_block_numbers(
+        &self,
+        first_block: BlockNumber,
+    ) -> impl Iterator<Item = (BlockNumber, &Vec<Option<Receipt>>)> + '_ {
+        (first_block..).zip(self.receipt_vec.iter())
+    }
+
+    /// Returns an iterator over the estimated JSON length of the receipts of every block, as an
+    /// array without the pruned receipts.
+    ///
+    /// See [Receipt::estimated_json_len].
+    pub fn estimated_json_len_by_block(&self) -> impl Iterator<Item = usize> + '_ {
+        self.receipt_vec.iter().map(|receipts| {
+            let (count, len) = receipts.iter().flatten().fold((0, 0), |(count, len), receipt| {
+                (count + 1, len + receipt.estimated_json_len())
+            });
+            // brackets and commas between the receipts
+            2 + len + count.saturating_sub(1)
+        })
+    }
+
+    /// Returns the indices of the blocks with identical logs, in the same receipt or across
+    /// different receipts of the block, ignoring pruned receipts.
+    ///
+    /// See [Receipt::has_duplicate_logs].
+    pub fn blocks_with_duplicate_logs(&self) -> impl Iterator<Item = usize> + '_ {
+        self.receipt_vec.iter().enumerate().filter_map(|(block, receipts)| {
+            has_duplicate_logs(receipts.iter().flatten().flat_map(|receipt| &receipt.logs))
+                .then_some(block)
+        })
+    }
+
     /// Push a new vector of receipts into the `Receipts` collection.
     pub fn push(&mut self, receipts: Vec<Option<Receipt>>) {
         self.receipt_vec.push(receipts);
     }
 
+    /// Push the receipts of a new block into the `Receipts` collection and return the block bloom,
+    /// folded from the logs of every receipt while pushing.
+    pub fn push_with_bloom(&mut self, receipts: Vec<Receipt>) -> Bloom {
+        let mut bloom = Bloom::ZERO;
+        let receipts = receipts
+            .into_iter()
+            .map(|receipt| {
+                bloom |= receipt.bloom_slow();
+                Some(receipt)
+            })
+            .collect();
+        self.receipt_vec.push(receipts);
+        bloom
+    }
+
+    /// Returns the receipts of the block at index, each paired with its bloom.
+    ///
+    /// Returns `None` if there is no such block, or if any of its receipts is pruned.
+    pub fn block_with_blooms(&self, index: usize) -> Option<Vec<ReceiptWithBloom>> {
+        self.receipt_vec
+            .get(index)?
+            .iter()
+            .map(|receipt| receipt.clone().map(Receipt::with_bloom))
+            .collect()
+    }
+
     /// Retrieves the receipt root for all recorded receipts from index.
     #[cfg(not(feature = "optimism"))]
     pub fn root_slow(&self, index: usize) -> Option<B256> {
@@ -118,6 +644,147 @@ impl Receipts {
         ))
     }
 
+    /// Generates the merkle proof of the receipt at `tx_index` in the receipt root of the block at
+    /// `block_index`. See [Receipts::root_slow].
+    ///
+    /// Returns `None` if any receipt of the block is pruned, or if there is no such receipt.
+    #[cfg(not(feature = "optimism"))]
+    pub fn receipt_proof(&self, block_index: usize, tx_index: usize) -> Option<ReceiptProof> {
+        let receipts = self
+            .receipt_vec
+            .get(block_index)?
+            .iter()
+            .map(Option::as_ref)
+            .collect::<Option<Vec<_>>>()?;
+        let receipt = receipts.get(tx_index)?;
+
+        let mut value = BytesMut::new();
+        ReceiptWithBloomRef::from(*receipt).encode_inner(&mut value, false);
+
+        let (root, proof) =
+            crate::proofs::ordered_trie_proof_with_encoder(&receipts, tx_index, |r, buf| {
+                ReceiptWithBloomRef::from(*r).encode_inner(buf, false)
+            });
+
+        Some(ReceiptProof { tx_index, root, value: value.freeze().into(), proof })
+    }
+
+    /// Checks that the cumulative gas used never decreases across the receipts of the block at
+    /// index. Pruned receipts are skipped.
+    ///
+    /// Returns the index of the first offending transaction otherwise.
+    pub fn validate_cumulative_gas(&self, block_index: usize) -> Result<(), ReceiptError> {
+        let receipts =
+            self.receipt_vec.get(block_index).ok_or(ReceiptError::MissingBlock(block_index))?;
+
+        let mut previous = 0;
+        for (tx_index, receipt) in receipts.iter().enumerate() {
+            let Some(receipt) = receipt else { continue };
+            if receipt.cumulative_gas_used < previous {
+                return Err(ReceiptError::DecreasingCumulativeGas {
+                    tx_index,
+                    previous,
+                    cumulative_gas_used: receipt.cumulative_gas_used,
+                })
+            }
+            previous = receipt.cumulative_gas_used;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the number of logs emitted in the block at index. Pruned receipts are skipped.
+    ///
+    /// Returns `None` if there is no block at index.
+    pub fn block_logs_len(&self, index: usize) -> Option<usize> {
+        self.receipt_vec
+            .get(index)
+            .map(|receipts| receipts.iter().flatten().map(Receipt::logs_len).sum())
+    }
+
+    /// Returns `false` if the bloom of the block at index rules out a log emitted by `address` with
+    /// all of `topics`. See [Receipt::bloom_may_contain].
+    ///
+    /// Since pruned receipts can't be ruled out, blocks with pruned receipts may always contain the
+    /// log. Returns `None` if there is no block at index.
+    pub fn block_bloom_may_contain(
+        &self,
+        index: usize,
+        address: &Address,
+        topics: &[B256],
+    ) -> Option<bool> {
+        let receipts = self.receipt_vec.get(index)?;
+        let Some(bloom) = receipts
+            .iter()
+            .try_fold(Bloom::ZERO, |bloom, receipt| Some(bloom | receipt.as_ref()?.bloom_slow()))
+        else {
+            return Some(true)
+        };
+        Some(bloom_may_contain(&bloom, address, topics))
+    }
+
+    /// Returns the total length of the log data emitted in the block at index. Pruned receipts
+    /// are skipped.
+    ///
+    /// Returns `None` if there is no block at index.
+    pub fn block_log_data_len(&self, index: usize) -> Option<usize> {
+        self.receipt_vec
+            .get(index)
+            .map(|receipts| receipts.iter().flatten().map(Receipt::total_log_data_len).sum())
+    }
+
+    /// Builds an index from every log topic of the block at index to the positions of the logs
+    /// emitted with it, as `(transaction index, log index)` pairs in emission order.
+    ///
+    /// Useful to serve repeated topic filtered queries over the same block without scanning all of
+    /// its logs. Pruned receipts are skipped.
+    ///
+    /// Returns `None` if there is no block at index.
+    pub fn build_topic_index(
+        &self,
+        block_index: usize,
+    ) -> Option<HashMap<B256, Vec<(usize, usize)>>> {
+        let receipts = self.receipt_vec.get(block_index)?;
+
+        let mut index: HashMap<B256, Vec<(usize, usize)>> = HashMap::new();
+        for (tx_index, receipt) in receipts.iter().enumerate() {
+            let Some(receipt) = receipt else { continue };
+            for (log_index, log) in receipt.logs.iter().enumerate() {
+                for topic in &log.topics {
+                    let positions = index.entry(*topic).or_default();
+                    // A topic may appear more than once in the same log.
+                    if positions.last() != Some(&(tx_index, log_index)) {
+                        positions.push((tx_index, log_index));
+                    }
+                }
+            }
+        }
+
+        Some(index)
+    }
+
+    /// Returns all logs emitted in the block at index, in canonical `(transaction index, log
+    /// index)` order, along with the cumulative gas used of their receipts.
+    ///
+    /// Returns `None` if there is no block at index, or if any of its receipts is pruned, since the
+    /// logs can't be proven against the receipt root then. See [Receipts::receipt_proof].
+    pub fn ordered_block_logs(&self, index: usize) -> Option<Vec<BlockLogEntry>> {
+        let receipts = self.receipt_vec.get(index)?;
+
+        let mut entries = Vec::with_capacity(self.block_logs_len(index)?);
+        for (tx_index, receipt) in receipts.iter().enumerate() {
+            let receipt = receipt.as_ref()?;
+            entries.extend(receipt.logs.iter().enumerate().map(|(log_index, log)| BlockLogEntry {
+                tx_index,
+                log_index,
+                cumulative_gas_used: receipt.cumulative_gas_used,
+                log: log.clone(),
+            }));
+        }
+
+        Some(entries)
+    }
+
     /// Retrieves gas spent by transactions as a vector of tuples (transaction index, gas used).
     pub fn gas_spent_by_tx(&self) -> Result<Vec<(u64, u64)>, PruneSegmentError> {
         self.last()
@@ -136,6 +803,344 @@ impl Receipts {
             })
             .unwrap_or(Ok(vec![]))
     }
+
+    /// Retrieves the gas used by every transaction of the block at index, i.e. the difference
+    /// between the cumulative gas used of its receipt and of the previous one.
+    ///
+    /// Decreasing cumulative gas used, see [Receipts::validate_cumulative_gas], saturates to zero.
+    /// Returns an empty vector if there is no block at index.
+    pub fn per_tx_gas_used(&self, block_index: usize) -> Result<Vec<u64>, PruneSegmentError> {
+        let Some(receipts) = self.receipt_vec.get(block_index) else { return Ok(vec![]) };
+
+        let mut previous = 0;
+        receipts
+            .iter()
+            .map(|receipt| {
+                let cumulative_gas_used =
+                    receipt.as_ref().ok_or(PruneSegmentError::ReceiptsPruned)?.cumulative_gas_used;
+                let gas_used = cumulative_gas_used.saturating_sub(previous);
+                previous = cumulative_gas_used;
+                Ok(gas_used)
+            })
+            .collect()
+    }
+
+    /// Returns the gas used by the block at index, i.e. the cumulative gas used of its last
+    /// receipt, or zero if the block has no receipts.
+    ///
+    /// Returns `None` if there is no block at index, or if its last receipt is pruned, in which
+    /// case the total is unknown.
+    pub fn block_gas_used(&self, block_index: usize) -> Option<u64> {
+        match self.receipt_vec.get(block_index)?.last() {
+            Some(receipt) => receipt.as_ref().map(|receipt| receipt.cumulative_gas_used),
+            None => Some(0),
+        }
+    }
+
+    /// Returns the gas used by all blocks, see [Receipts::block_gas_used].
+    pub fn range_gas_used(&self) -> Result<u64, PruneSegmentError> {
+        (0..self.receipt_vec.len())
+            .map(|block_index| {
+                self.block_gas_used(block_index).ok_or(PruneSegmentError::ReceiptsPruned)
+            })
+            .sum()
+    }
+
+    /// Returns `true` if both have the same blocks, and every block has the same receipts in any
+    /// order, i.e. compares the receipts of each block as a multiset. Pruned receipts are compared
+    /// like any other receipt.
+    ///
+    /// This is a diagnostic aid for differential testing only: the order of receipts is part of
+    /// consensus, e.g. through the receipts root, so blocks equal this way may still differ.
+    pub fn eq_as_multiset(&self, other: &Receipts) -> bool {
+        self.receipt_vec.len() == other.receipt_vec.len() &&
+            self.receipt_vec.iter().zip(&other.receipt_vec).all(|(ours, theirs)| {
+                if ours.len() != theirs.len() {
+                    return false
+                }
+                // pair every receipt with a distinct equal one on the other side
+                let mut matched = vec![false; theirs.len()];
+                ours.iter().all(|receipt| {
+                    let Some(pos) = theirs
+                        .iter()
+                        .enumerate()
+                        .position(|(i, other)| !matched[i] && other == receipt)
+                    else {
+                        return false
+                    };
+                    matched[pos] = true;
+                    true
+                })
+            })
+    }
+
+    /// Compares these receipts against `other` and returns every field that differs, indexed by
+    /// block and transaction position.
+    ///
+    /// Receipts that are missing or pruned on both sides are considered equal.
+    pub fn diff(&self, other: &Receipts) -> Vec<ReceiptDiff> {
+        let mut diffs = Vec::new();
+
+        for block_idx in 0..self.len().max(other.len()) {
+            let ours = self.get(block_idx).map(Vec::as_slice).unwrap_or_default();
+            let theirs = other.get(block_idx).map(Vec::as_slice).unwrap_or_default();
+
+            for tx_idx in 0..ours.len().max(theirs.len()) {
+                let mut push = |kind| diffs.push(ReceiptDiff { block_idx, tx_idx, kind });
+
+                let (ours, theirs) = match (ours.get(tx_idx), theirs.get(tx_idx)) {
+                    (Some(Some(ours)), Some(Some(theirs))) => (ours, theirs),
+                    (None | Some(None), None | Some(None)) => continue,
+                    _ => {
+                        push(ReceiptDiffKind::Presence);
+                        continue
+                    }
+                };
+
+                if ours.tx_type != theirs.tx_type {
+                    push(ReceiptDiffKind::TxType);
+                }
+                if ours.success != theirs.success {
+                    push(ReceiptDiffKind::Status);
+                }
+                if ours.cumulative_gas_used != theirs.cumulative_gas_used {
+                    push(ReceiptDiffKind::CumulativeGasUsed);
+                }
+                if ours.logs.len() != theirs.logs.len() {
+                    push(ReceiptDiffKind::LogCount);
+                }
+                for (log_idx, (ours, theirs)) in ours.logs.iter().zip(&theirs.logs).enumerate() {
+                    if ours != theirs {
+                        push(ReceiptDiffKind::Log(log_idx));
+                    }
+                }
+            }
+        }
+
+        diffs
+    }
+
+    /// Merges `other` into these receipts, filling every pruned or missing receipt with the one of
+    /// `other` at the same block and transaction position, if present.
+    ///
+    /// Fails with [ReceiptError::Conflict] if both sides have a receipt at the same position and
+    /// they differ, in which case these receipts are left unchanged. Returns the number of
+    /// receipts filled otherwise.
+    pub fn merge_prefer_present(&mut self, other: Receipts) -> Result<usize, ReceiptError> {
+        for (block_index, (ours, theirs)) in self.iter().zip(other.iter()).enumerate() {
+            for (tx_index, (ours, theirs)) in ours.iter().zip(theirs).enumerate() {
+                if let (Some(ours), Some(theirs)) = (ours, theirs) {
+                    if ours != theirs {
+                        return Err(ReceiptError::Conflict { block_index, tx_index })
+                    }
+                }
+            }
+        }
+
+        let mut filled = 0;
+        for (block_index, theirs) in other.into_iter().enumerate() {
+            if block_index == self.len() {
+                self.push(Vec::new());
+            }
+            let ours = &mut self[block_index];
+            for (tx_index, theirs) in theirs.into_iter().enumerate() {
+                if tx_index == ours.len() {
+                    ours.push(None);
+                }
+                if ours[tx_index].is_none() && theirs.is_some() {
+                    ours[tx_index] = theirs;
+                    filled += 1;
+                }
+            }
+        }
+
+        Ok(filled)
+    }
+}
+
+/// Returns `true` if any two logs have the same address, topics and data.
+fn has_duplicate_logs<'a>(logs: impl Iterator<Item = &'a Log>) -> bool {
+    let mut seen = HashSet::new();
+    logs.into_iter().any(|log| !seen.insert((&log.address, &log.topics, &log.data)))
+}
+
+/// Returns the length of the number as a [Compact] variable length integer.
+fn varuint_len(number: usize) -> usize {
+    let bits = (usize::BITS - number.leading_zeros()).max(1) as usize;
+    (bits + 6) / 7
+}
+
+/// Returns the length of the number as a JSON decimal.
+fn json_number_len(number: u64) -> usize {
+    number.checked_ilog10().map_or(1, |log| log as usize + 1)
+}
+
+/// Builder of the [Receipts] of a single block, accumulating the receipt of every transaction.
+///
+/// Unlike [Receipts::push_with_bloom], the block bloom is only computed when requested, so blocks
+/// that are discarded before sealing, e.g. on a reorg, don't pay for it.
+#[derive(Clone, Debug, Default)]
+pub struct ReceiptsBuilder {
+    /// Receipts of the block, in transaction order.
+    receipts: Vec<Receipt>,
+}
+
+#[cfg(test)]
+thread_local! {
+    /// Number of blooms computed by [ReceiptsBuilder::bloom] on the current thread.
+    static RECEIPTS_BUILDER_BLOOMS: std::cell::Cell<usize> = std::cell::Cell::new(0);
+}
+
+impl ReceiptsBuilder {
+    /// Create a new, empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new builder with space for the receipts of at least `txs` transactions.
+    pub fn with_capacity(txs: usize) -> Self {
+        Self { receipts: Vec::with_capacity(txs) }
+    }
+
+    /// Push the receipt of the next transaction of the block.
+    pub fn push(&mut self, receipt: Receipt) {
+        self.receipts.push(receipt);
+    }
+
+    /// Returns the number of receipts pushed so far.
+    pub fn len(&self) -> usize {
+        self.receipts.len()
+    }
+
+    /// Returns `true` if no receipts were pushed.
+    pub fn is_empty(&self) -> bool {
+        self.receipts.is_empty()
+    }
+
+    /// Calculates the block bloom from the logs of every receipt pushed so far.
+    pub fn bloom(&self) -> Bloom {
+        #[cfg(test)]
+        RECEIPTS_BUILDER_BLOOMS.with(|blooms| blooms.set(blooms.get() + 1));
+
+        logs_bloom(self.receipts.iter().flat_map(|receipt| &receipt.logs))
+    }
+
+    /// Returns the [Receipts] of the block, without computing its bloom.
+    pub fn build(self) -> Receipts {
+        Receipts::from_block_receipt(self.receipts)
+    }
+
+    /// Returns the [Receipts] of the block, together with its bloom if `with_bloom` is set.
+    pub fn build_with_bloom(self, with_bloom: bool) -> (Receipts, Option<Bloom>) {
+        let bloom = with_bloom.then(|| self.bloom());
+        (self.build(), bloom)
+    }
+}
+
+impl Extend<Receipt> for ReceiptsBuilder {
+    fn extend<I: IntoIterator<Item = Receipt>>(&mut self, iter: I) {
+        self.receipts.extend(iter);
+    }
+}
+
+/// Merkle proof of inclusion of a receipt in the receipt root of a block, as returned by
+/// [`Receipts::receipt_proof`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReceiptProof {
+    /// Index of the receipt in the block.
+    pub tx_index: usize,
+    /// Receipt root of the block.
+    pub root: B256,
+    /// Encoded receipt, as stored in the trie leaf.
+    pub value: Bytes,
+    /// Array of rlp-serialized merkle trie nodes, starting from the root node and following the
+    /// path of the rlp encoded receipt index.
+    pub proof: Vec<Bytes>,
+}
+
+/// A log emitted in a block, as returned by [`Receipts::ordered_block_logs`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockLogEntry {
+    /// Index of the transaction that emitted the log within the block.
+    pub tx_index: usize,
+    /// Index of the log within the logs of its receipt.
+    pub log_index: usize,
+    /// Cumulative gas used of the receipt of the transaction.
+    pub cumulative_gas_used: u64,
+    /// The log.
+    pub log: Log,
+}
+
+/// Receipts validation error, see [`Receipts::validate_cumulative_gas`],
+/// [`Receipts::from_sorted_tuples`], [`Receipts::merge_prefer_present`] and
+/// [`Receipt::try_from_compact`].
+#[derive(Debug, thiserror::Error, PartialEq, Eq, Clone)]
+pub enum ReceiptError {
+    /// There are no receipts for the block at the given index.
+    #[error("no receipts for block at index {0}")]
+    MissingBlock(usize),
+    /// The cumulative gas used is lower than the one of a previous receipt in the block.
+    #[error(
+        "cumulative gas used of transaction {tx_index} decreased from {previous} to {cumulative_gas_used}"
+    )]
+    DecreasingCumulativeGas {
+        /// Index of the offending transaction within the block.
+        tx_index: usize,
+        /// Cumulative gas used of the previous receipt.
+        previous: u64,
+        /// Cumulative gas used of the offending receipt.
+        cumulative_gas_used: u64,
+    },
+    /// A receipt is not sorted after the previous one.
+    #[error("receipt of transaction {tx_index} in block {block_number} is out of order")]
+    OutOfOrder {
+        /// Number of the block of the offending receipt.
+        block_number: BlockNumber,
+        /// Index of the offending transaction within the block.
+        tx_index: usize,
+    },
+    /// Two receipts at the same position differ.
+    #[error("conflicting receipts for transaction {tx_index} of block at index {block_index}")]
+    Conflict {
+        /// Index of the block within the [`Receipts`].
+        block_index: usize,
+        /// Index of the transaction within the block.
+        tx_index: usize,
+    },
+    /// A receipt was encoded with an unknown version of its [Compact] encoding.
+    #[error("unsupported receipt encoding version {0}")]
+    UnsupportedCodecVersion(u8),
+    /// The compressed fields of a receipt failed to decompress.
+    #[error("failed to decompress receipt")]
+    Decompression,
+}
+
+/// A single difference between two [`Receipts`], as returned by [`Receipts::diff`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReceiptDiff {
+    /// Index of the block within the [`Receipts`].
+    pub block_idx: usize,
+    /// Index of the transaction within the block.
+    pub tx_idx: usize,
+    /// The part of the receipt that differs.
+    pub kind: ReceiptDiffKind,
+}
+
+/// The part of a [`Receipt`] that differs in a [`ReceiptDiff`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReceiptDiffKind {
+    /// The receipt is only present on one side.
+    Presence,
+    /// The transaction type differs.
+    TxType,
+    /// The status code differs.
+    Status,
+    /// The cumulative gas used differs.
+    CumulativeGasUsed,
+    /// The number of logs differs.
+    LogCount,
+    /// The log at the given index differs.
+    Log(usize),
 }
 
 impl Deref for Receipts {
@@ -200,12 +1205,71 @@ impl ReceiptWithBloom {
         (self.receipt, self.bloom)
     }
 
+    /// Returns `false` if the cached bloom filter rules out a log emitted by `address` with all of
+    /// `topics`. See [Receipt::bloom_may_contain].
+    pub fn bloom_may_contain(&self, address: &Address, topics: &[B256]) -> bool {
+        bloom_may_contain(&self.bloom, address, topics)
+    }
+
+    /// Appends a log to the receipt, adding its bits to the cached bloom filter instead of
+    /// recomputing it from all logs.
+    pub fn push_log(&mut self, log: Log) {
+        self.bloom |= logs_bloom([&log]);
+        self.receipt.logs.push(log);
+    }
+
     #[inline]
     fn as_encoder(&self) -> ReceiptWithBloomEncoder<'_> {
         ReceiptWithBloomEncoder { receipt: &self.receipt, bloom: &self.bloom }
     }
 }
 
+/// [`Receipt`] paired with metadata of its transaction that the consensus receipt doesn't carry,
+/// so it doesn't have to be derived again when the receipt is served.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct ReceiptWithMeta {
+    /// Main receipt body
+    pub receipt: Receipt,
+    /// Blob gas used by the transaction, only set for EIP-4844 transactions.
+    pub blob_gas_used: Option<u64>,
+}
+
+impl ReceiptWithMeta {
+    /// Create new [ReceiptWithMeta] without metadata.
+    pub fn new(receipt: Receipt) -> Self {
+        Self { receipt, blob_gas_used: None }
+    }
+
+    /// Attaches the blob gas used by the transaction.
+    ///
+    /// Returns `None` if the receipt isn't of an EIP-4844 transaction, the only ones using blob
+    /// gas.
+    pub fn with_blob_gas_used(mut self, blob_gas_used: u64) -> Option<Self> {
+        if !self.receipt.is_eip4844() {
+            return None
+        }
+        self.blob_gas_used = Some(blob_gas_used);
+        Some(self)
+    }
+
+    /// Consume the structure, returning only the receipt
+    pub fn into_receipt(self) -> Receipt {
+        self.receipt
+    }
+}
+
+impl From<Receipt> for ReceiptWithMeta {
+    fn from(receipt: Receipt) -> Self {
+        Self::new(receipt)
+    }
+}
+
+/// Returns `false` if `bloom` rules out a log emitted by `address` with all of `topics`.
+fn bloom_may_contain(bloom: &Bloom, address: &Address, topics: &[B256]) -> bool {
+    bloom.contains_input(BloomInput::Raw(address.as_slice())) &&
+        topics.iter().all(|topic| bloom.contains_input(BloomInput::Raw(topic.as_slice())))
+}
+
 #[cfg(any(test, feature = "arbitrary"))]
 impl proptest::arbitrary::Arbitrary for Receipt {
     type Parameters = ();
@@ -250,6 +1314,15 @@ impl proptest::arbitrary::Arbitrary for Receipt {
     type Strategy = proptest::strategy::BoxedStrategy<Receipt>;
 }
 
+/// Returns a [Strategy] generating [Receipts] of up to `max_blocks` blocks, each with up to
+/// `max_txs` arbitrary receipts. Receipts are randomly pruned, i.e. `None`.
+#[cfg(any(test, feature = "arbitrary"))]
+pub fn arb_receipts(max_blocks: usize, max_txs: usize) -> impl Strategy<Value = Receipts> {
+    use proptest::{arbitrary::any, collection::vec, option};
+
+    vec(vec(option::of(any::<Receipt>()), 0..=max_txs), 0..=max_blocks).prop_map(Receipts::from_vec)
+}
+
 #[cfg(any(test, feature = "arbitrary"))]
 impl<'a> arbitrary::Arbitrary<'a> for Receipt {
     fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
@@ -288,8 +1361,105 @@ impl ReceiptWithBloom {
         self.as_encoder().encode_inner(out, with_header)
     }
 
+    /// Returns the length of the RLP payload of the receipt fields, excluding the list header and
+    /// the EIP-2718 type prefix of typed receipts.
+    pub fn rlp_payload_length(&self) -> usize {
+        self.as_encoder().receipt_rlp_header().payload_length
+    }
+
+    /// Returns the [TxType] of the RLP encoded receipt at the start of `buf`, without decoding or
+    /// consuming it.
+    ///
+    /// Only the leading bytes are inspected: the list header of a legacy receipt, or the string
+    /// header and the type byte of a typed receipt.
+    pub fn peek_tx_type(buf: &[u8]) -> alloy_rlp::Result<TxType> {
+        // a receipt is either encoded as a string (non legacy) or a list (legacy).
+        let rlp_type = *buf
+            .first()
+            .ok_or(alloy_rlp::Error::Custom("cannot decode a receipt from empty bytes"))?;
+
+        match rlp_type.cmp(&alloy_rlp::EMPTY_LIST_CODE) {
+            Ordering::Less => {
+                // skip the string header
+                let buf = &mut &*buf;
+                let _header = alloy_rlp::Header::decode(buf)?;
+                let receipt_type = *buf.first().ok_or(alloy_rlp::Error::Custom(
+                    "typed receipt cannot be decoded from an empty slice",
+                ))?;
+                // a typed receipt cannot carry the legacy type
+                match TxType::try_from(receipt_type) {
+                    Ok(TxType::Legacy) | Err(_) => {
+                        Err(alloy_rlp::Error::Custom("invalid receipt type"))
+                    }
+                    Ok(tx_type) => Ok(tx_type),
+                }
+            }
+            Ordering::Equal => {
+                Err(alloy_rlp::Error::Custom("an empty list is not a valid receipt encoding"))
+            }
+            Ordering::Greater => Ok(TxType::Legacy),
+        }
+    }
+
+    /// Decodes a receipt like [Decodable::decode], but ignores the trailing bytes that some
+    /// non-standard encoders append to the payload of typed receipts.
+    ///
+    /// The buffer is advanced past the whole declared payload. Legacy receipts are decoded
+    /// strictly.
+    pub fn decode_lenient(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        let tx_type = Self::peek_tx_type(buf)?;
+        if tx_type == TxType::Legacy {
+            return Self::decode_receipt(buf, tx_type, false)
+        }
+
+        let header = alloy_rlp::Header::decode(buf)?;
+        if header.payload_length == 0 {
+            return Err(alloy_rlp::Error::Custom(
+                "typed receipt cannot be decoded from an empty slice",
+            ))
+        }
+        if buf.len() < header.payload_length {
+            return Err(alloy_rlp::Error::InputTooShort)
+        }
+        // skip the type byte, which was checked when peeking the type
+        let mut payload = &buf[1..header.payload_length];
+        let this = Self::decode_receipt(&mut payload, tx_type, true)?;
+        buf.advance(header.payload_length);
+        Ok(this)
+    }
+
+    /// Encodes a list of block receipts the same way as `Vec<Vec<ReceiptWithBloom>>`, which is the
+    /// layout of a network `Receipts` message.
+    ///
+    /// The receipts of each block are encoded into separate buffers on the rayon pool and
+    /// concatenated in order afterwards, which speeds up the assembly of large responses. Smaller
+    /// inputs are encoded serially. rayon is a hard dependency of this crate, so this isn't
+    /// behind a feature.
+    pub fn encode_blocks_par(blocks: &[Vec<ReceiptWithBloom>], out: &mut dyn BufMut) {
+        let lengths = blocks.iter().map(Encodable::length).collect::<Vec<_>>();
+        let payload_length: usize = lengths.iter().sum();
+        if payload_length < PAR_ENCODE_THRESHOLD {
+            return blocks.encode(out)
+        }
+
+        let encoded = blocks
+            .par_iter()
+            .zip(lengths.par_iter())
+            .map(|(receipts, length)| {
+                let mut buf = Vec::with_capacity(*length);
+                receipts.encode(&mut buf);
+                buf
+            })
+            .collect::<Vec<_>>();
+
+        alloy_rlp::Header { list: true, payload_length }.encode(out);
+        for buf in encoded {
+            out.put_slice(&buf);
+        }
+    }
+
     /// Decodes the receipt payload
-    fn decode_receipt(buf: &mut &[u8], tx_type: TxType) -> alloy_rlp::Result<Self> {
+    fn decode_receipt(buf: &mut &[u8], tx_type: TxType, lenient: bool) -> alloy_rlp::Result<Self> {
         let b = &mut &**buf;
         let rlp_head = alloy_rlp::Header::decode(b)?;
         if !rlp_head.list {
@@ -334,7 +1504,14 @@ impl ReceiptWithBloom {
 
         let this = Self { receipt, bloom };
         let consumed = started_len - b.len();
-        if consumed != rlp_head.payload_length {
+        if lenient && consumed < rlp_head.payload_length {
+            // skip the trailing bytes of the declared payload
+            let trailing = rlp_head.payload_length - consumed;
+            if b.len() < trailing {
+                return Err(alloy_rlp::Error::InputTooShort)
+            }
+            b.advance(trailing);
+        } else if consumed != rlp_head.payload_length {
             return Err(alloy_rlp::Error::ListLengthMismatch {
                 expected: rlp_head.payload_length,
                 got: consumed,
@@ -347,55 +1524,23 @@ impl ReceiptWithBloom {
 
 impl Encodable for ReceiptWithBloom {
     fn encode(&self, out: &mut dyn BufMut) {
-        self.encode_inner(out, true)
-    }
-    fn length(&self) -> usize {
-        self.as_encoder().length()
-    }
-}
-
-impl Decodable for ReceiptWithBloom {
-    fn decode(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
-        // a receipt is either encoded as a string (non legacy) or a list (legacy).
-        // We should not consume the buffer if we are decoding a legacy receipt, so let's
-        // check if the first byte is between 0x80 and 0xbf.
-        let rlp_type = *buf
-            .first()
-            .ok_or(alloy_rlp::Error::Custom("cannot decode a receipt from empty bytes"))?;
-
-        match rlp_type.cmp(&alloy_rlp::EMPTY_LIST_CODE) {
-            Ordering::Less => {
-                // strip out the string header
-                let _header = alloy_rlp::Header::decode(buf)?;
-                let receipt_type = *buf.first().ok_or(alloy_rlp::Error::Custom(
-                    "typed receipt cannot be decoded from an empty slice",
-                ))?;
-                match receipt_type {
-                    0x01 => {
-                        buf.advance(1);
-                        Self::decode_receipt(buf, TxType::EIP2930)
-                    }
-                    0x02 => {
-                        buf.advance(1);
-                        Self::decode_receipt(buf, TxType::EIP1559)
-                    }
-                    0x03 => {
-                        buf.advance(1);
-                        Self::decode_receipt(buf, TxType::EIP4844)
-                    }
-                    #[cfg(feature = "optimism")]
-                    0x7E => {
-                        buf.advance(1);
-                        Self::decode_receipt(buf, TxType::DEPOSIT)
-                    }
-                    _ => Err(alloy_rlp::Error::Custom("invalid receipt type")),
-                }
-            }
-            Ordering::Equal => {
-                Err(alloy_rlp::Error::Custom("an empty list is not a valid receipt encoding"))
-            }
-            Ordering::Greater => Self::decode_receipt(buf, TxType::Legacy),
+        self.encode_inner(out, true)
+    }
+    fn length(&self) -> usize {
+        self.as_encoder().length()
+    }
+}
+
+impl Decodable for ReceiptWithBloom {
+    fn decode(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        let tx_type = Self::peek_tx_type(buf)?;
+        // We should not consume the buffer if we are decoding a legacy receipt, otherwise strip
+        // out the string header and the type byte.
+        if tx_type != TxType::Legacy {
+            let _header = alloy_rlp::Header::decode(buf)?;
+            buf.advance(1);
         }
+        Self::decode_receipt(buf, tx_type, false)
     }
 }
 
@@ -419,6 +1564,30 @@ impl<'a> ReceiptWithBloomRef<'a> {
         self.as_encoder().encode_inner(out, with_header)
     }
 
+    /// Encodes the receipts of a block as an RLP list, using the precomputed `blooms` instead of
+    /// recomputing them from the logs of every receipt.
+    ///
+    /// The output is the same as encoding the list of [ReceiptWithBloom].
+    ///
+    /// # Panics
+    ///
+    /// If `blooms` is not aligned with `receipts`.
+    pub fn encode_list_with_blooms(receipts: &[Receipt], blooms: &[Bloom], out: &mut dyn BufMut) {
+        assert_eq!(receipts.len(), blooms.len(), "one bloom per receipt");
+        let encoders = || {
+            receipts
+                .iter()
+                .zip(blooms)
+                .map(|(receipt, bloom)| ReceiptWithBloomEncoder { receipt, bloom })
+        };
+
+        let payload_length = encoders().map(|encoder| encoder.length()).sum();
+        alloy_rlp::Header { list: true, payload_length }.encode(out);
+        for encoder in encoders() {
+            encoder.encode_inner(out, true);
+        }
+    }
+
     #[inline]
     fn as_encoder(&self) -> ReceiptWithBloomEncoder<'_> {
         ReceiptWithBloomEncoder { receipt: self.receipt, bloom: &self.bloom }
@@ -469,9 +1638,9 @@ impl<'a> ReceiptWithBloomEncoder<'a> {
         rlp_head
     }
 
-    /// Encodes the receipt data.
-    fn encode_fields(&self, out: &mut dyn BufMut) {
-        self.receipt_rlp_header().encode(out);
+    /// Encodes the receipt data, behind the given rlp header of its payload.
+    fn encode_fields(&self, rlp_head: alloy_rlp::Header, out: &mut dyn BufMut) {
+        rlp_head.encode(out);
         self.receipt.success.encode(out);
         self.receipt.cumulative_gas_used.encode(out);
         self.bloom.encode(out);
@@ -488,38 +1657,25 @@ impl<'a> ReceiptWithBloomEncoder<'a> {
     }
 
     /// Encode receipt with or without the header data.
+    ///
+    /// The receipt data is written directly to `out`, so the length of typed receipts is computed
+    /// up front instead of buffering their payload.
     fn encode_inner(&self, out: &mut dyn BufMut, with_header: bool) {
-        if matches!(self.receipt.tx_type, TxType::Legacy) {
-            self.encode_fields(out);
+        let rlp_head = self.receipt_rlp_header();
+        if self.receipt.is_legacy() {
+            self.encode_fields(rlp_head, out);
             return
         }
 
-        let mut payload = BytesMut::new();
-        self.encode_fields(&mut payload);
-
         if with_header {
-            let payload_length = payload.len() + 1;
+            let payload_length =
+                length_of_length(rlp_head.payload_length) + rlp_head.payload_length + 1;
             let header = alloy_rlp::Header { list: false, payload_length };
             header.encode(out);
         }
 
-        match self.receipt.tx_type {
-            TxType::EIP2930 => {
-                out.put_u8(0x01);
-            }
-            TxType::EIP1559 => {
-                out.put_u8(0x02);
-            }
-            TxType::EIP4844 => {
-                out.put_u8(0x03);
-            }
-            #[cfg(feature = "optimism")]
-            TxType::DEPOSIT => {
-                out.put_u8(0x7E);
-            }
-            _ => unreachable!("legacy handled; qed."),
-        }
-        out.put_slice(payload.as_ref());
+        out.put_u8(self.receipt.tx_type.into());
+        self.encode_fields(rlp_head, out);
     }
 
     /// Returns the length of the receipt data.
@@ -549,7 +1705,7 @@ impl<'a> Encodable for ReceiptWithBloomEncoder<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::hex_literal::hex;
+    use crate::{constants::eip4844::DATA_GAS_PER_BLOB, hex_literal::hex};
     use alloy_primitives::{address, b256, bytes, Bytes};
     use alloy_rlp::{Decodable, Encodable};
 
@@ -702,5 +1858,1152 @@ mod tests {
         receipt.clone().to_compact(&mut data);
         let (decoded, _) = Receipt::from_compact(&data[..], data.len());
         assert_eq!(decoded, receipt);
+        assert!(receipt.compact_size_hint() >= data.len());
+
+        assert_eq!(receipt.logs_len(), 2);
+        assert_eq!(receipt.total_log_data_len(), 2 * 0xffffff);
+
+        let receipts = Receipts::from_vec(vec![vec![Some(receipt.clone()), None, Some(receipt)]]);
+        assert_eq!(receipts.block_logs_len(0), Some(4));
+        assert_eq!(receipts.block_log_data_len(0), Some(4 * 0xffffff));
+        assert_eq!(receipts.block_log_data_len(1), None);
+    }
+
+    #[test]
+    fn encode_matches_buffered_payload() {
+        let log = |len| Log {
+            address: address!("0000000000000000000000000000000000000011"),
+            topics: vec![b256!("000000000000000000000000000000000000000000000000000000000000dead")],
+            data: Bytes::from(vec![1; len]),
+        };
+
+        for tx_type in [TxType::Legacy, TxType::EIP2930, TxType::EIP1559, TxType::EIP4844] {
+            for logs in [vec![], vec![log(3)], vec![log(0xffff), log(0x10000)]] {
+                let receipt = new_receipt(tx_type, 16747627, logs).with_bloom();
+
+                // the payload of typed receipts used to be buffered before being copied to `out`
+                let encoder = receipt.as_encoder();
+                let mut payload = BytesMut::new();
+                encoder.encode_fields(encoder.receipt_rlp_header(), &mut payload);
+                let mut expected = BytesMut::new();
+                if tx_type != TxType::Legacy {
+                    let payload_length = payload.len() + 1;
+                    alloy_rlp::Header { list: false, payload_length }.encode(&mut expected);
+                    expected.put_u8(tx_type.into());
+                }
+                expected.put_slice(&payload);
+
+                let mut out = BytesMut::new();
+                receipt.encode(&mut out);
+                assert_eq!(out, expected);
+                assert_eq!(out.len(), receipt.length());
+            }
+        }
+    }
+
+    #[test]
+    fn encode_blocks_par_matches_serial() {
+        let receipt = |tx_type: TxType, cumulative_gas_used: u64, logs: Vec<Log>| {
+            new_receipt(tx_type, cumulative_gas_used, logs).with_bloom()
+        };
+        let log = Log {
+            address: address!("0000000000000000000000000000000000000011"),
+            topics: vec![b256!("000000000000000000000000000000000000000000000000000000000000dead")],
+            data: bytes!("0100ff"),
+        };
+
+        let blocks = vec![
+            vec![
+                receipt(TxType::Legacy, 21000, vec![]),
+                receipt(TxType::EIP1559, 42000, vec![log.clone()]),
+            ],
+            vec![],
+            vec![receipt(TxType::EIP2930, 100000, vec![log.clone(), log])],
+            vec![receipt(TxType::EIP4844, 21000, vec![])],
+        ];
+
+        // enough copies of the blocks to be encoded on the rayon pool
+        let many_blocks = blocks.iter().cycle().take(2000).cloned().collect::<Vec<_>>();
+        assert!(blocks.length() < PAR_ENCODE_THRESHOLD);
+        assert!(many_blocks.length() > PAR_ENCODE_THRESHOLD);
+
+        for blocks in [vec![], blocks, many_blocks] {
+            let mut serial = vec![];
+            blocks.encode(&mut serial);
+
+            let mut parallel = vec![];
+            ReceiptWithBloom::encode_blocks_par(&blocks, &mut parallel);
+
+            assert_eq!(parallel, serial);
+            assert_eq!(Vec::<Vec<ReceiptWithBloom>>::decode(&mut &parallel[..]).unwrap(), blocks);
+        }
+    }
+
+    #[test]
+    fn encode_list_with_blooms_matches_recompute() {
+        let log = Log {
+            address: address!("0000000000000000000000000000000000000011"),
+            topics: vec![b256!("000000000000000000000000000000000000000000000000000000000000dead")],
+            data: bytes!("0100ff"),
+        };
+        let receipts = [
+            (TxType::Legacy, 21000, vec![]),
+            (TxType::EIP1559, 42000, vec![log.clone()]),
+            (TxType::EIP2930, 100000, vec![log.clone(), log]),
+        ]
+        .into_iter()
+        .map(|(tx_type, cumulative_gas_used, logs)| new_receipt(tx_type, cumulative_gas_used, logs))
+        .collect::<Vec<_>>();
+
+        let mut recomputed = vec![];
+        receipts
+            .iter()
+            .cloned()
+            .map(Receipt::with_bloom)
+            .collect::<Vec<_>>()
+            .encode(&mut recomputed);
+
+        let blooms = receipts.iter().map(Receipt::bloom_slow).collect::<Vec<_>>();
+        let mut cached = vec![];
+        ReceiptWithBloomRef::encode_list_with_blooms(&receipts, &blooms, &mut cached);
+
+        assert_eq!(cached, recomputed);
+
+        // Empty block
+        let mut cached = vec![];
+        ReceiptWithBloomRef::encode_list_with_blooms(&[], &[], &mut cached);
+        assert_eq!(cached, vec![alloy_rlp::EMPTY_LIST_CODE]);
+    }
+
+    #[cfg(not(feature = "optimism"))]
+    #[test]
+    fn receipt_proof() {
+        let contains = |haystack: &[u8], needle: &[u8]| {
+            haystack.windows(needle.len()).any(|window| window == needle)
+        };
+
+        let receipts = Receipts::from_block_receipt(
+            (0..20).map(|i| new_receipt(TxType::Legacy, 21000 * (i + 1), vec![])).collect(),
+        );
+        let root = receipts.root_slow(0).unwrap();
+
+        for tx_index in [0, 1, 10, 19] {
+            let proof = receipts.receipt_proof(0, tx_index).unwrap();
+            assert_eq!(proof.tx_index, tx_index);
+            assert_eq!(proof.root, root);
+
+            // The first node is the root node
+            assert_eq!(crate::keccak256(&proof.proof[0]), root);
+            // Every other node is referenced by its parent
+            for nodes in proof.proof.windows(2) {
+                let node = &nodes[1];
+                if node.len() < 32 {
+                    assert!(contains(&nodes[0], node));
+                } else {
+                    assert!(contains(&nodes[0], crate::keccak256(node).as_slice()));
+                }
+            }
+            // The last node is the leaf holding the receipt
+            assert!(contains(proof.proof.last().unwrap(), &proof.value));
+        }
+
+        // No such receipt
+        assert_eq!(receipts.receipt_proof(0, 20), None);
+        assert_eq!(receipts.receipt_proof(1, 0), None);
+
+        // Pruned receipts
+        let mut pruned = receipts.clone();
+        pruned[0][3] = None;
+        assert_eq!(pruned.receipt_proof(0, 0), None);
+    }
+
+    #[test]
+    fn validate_cumulative_gas() {
+        let receipt =
+            |cumulative_gas_used| Some(new_receipt(TxType::Legacy, cumulative_gas_used, vec![]));
+
+        let receipts = Receipts::from_vec(vec![
+            vec![receipt(21000), None, receipt(42000), receipt(42000)],
+            vec![receipt(21000), receipt(63000), None, receipt(42000)],
+            vec![],
+        ]);
+
+        assert_eq!(receipts.validate_cumulative_gas(0), Ok(()));
+        assert_eq!(
+            receipts.validate_cumulative_gas(1),
+            Err(ReceiptError::DecreasingCumulativeGas {
+                tx_index: 3,
+                previous: 63000,
+                cumulative_gas_used: 42000
+            })
+        );
+        assert_eq!(receipts.validate_cumulative_gas(2), Ok(()));
+        assert_eq!(receipts.validate_cumulative_gas(3), Err(ReceiptError::MissingBlock(3)));
+    }
+
+    #[test]
+    fn push_with_bloom() {
+        let receipt = |address, topic| {
+            new_receipt(
+                TxType::EIP1559,
+                21000,
+                vec![Log { address, topics: vec![topic], data: Bytes::default() }],
+            )
+        };
+        let block = vec![
+            receipt(
+                address!("0000000000000000000000000000000000000011"),
+                b256!("000000000000000000000000000000000000000000000000000000000000dead"),
+            ),
+            receipt(
+                address!("0000000000000000000000000000000000000022"),
+                b256!("000000000000000000000000000000000000000000000000000000000000beef"),
+            ),
+        ];
+        let expected =
+            block.iter().fold(Bloom::ZERO, |bloom, receipt| bloom | receipt.bloom_slow());
+
+        let mut receipts = Receipts::new();
+        assert_eq!(receipts.push_with_bloom(block.clone()), expected);
+        assert_eq!(receipts.push_with_bloom(vec![]), Bloom::ZERO);
+        assert_eq!(*receipts, vec![block.into_iter().map(Some).collect(), vec![]]);
+    }
+
+    proptest::proptest! {
+        #![proptest_config(proptest::prelude::ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn compact_v0_has_no_version_byte(receipt in proptest::arbitrary::any::<Receipt>()) {
+            let mut buf = vec![];
+            receipt_v0(&receipt).to_compact(&mut buf);
+            assert_ne!(buf[0] & RECEIPT_VERSION_MARKER, RECEIPT_VERSION_MARKER);
+            assert_eq!(Receipt::compact_version(&buf), Ok(0));
+            assert_eq!(Receipt::from_compact(&buf, buf.len()).0, receipt);
+        }
+
+        #[test]
+        fn bloom_may_contain_no_false_negatives(receipts in arb_receipts(4, 4)) {
+            for (index, block) in receipts.iter().enumerate() {
+                for receipt in block.iter().flatten() {
+                    let with_bloom = receipt.clone().with_bloom();
+                    for log in &receipt.logs {
+                        // the log itself, and every log matching its address and a subset of its
+                        // topics
+                        for topics in (0..=log.topics.len()).map(|len| &log.topics[..len]) {
+                            assert!(receipt.bloom_may_contain(&log.address, topics));
+                            assert!(with_bloom.bloom_may_contain(&log.address, topics));
+                            assert_eq!(
+                                receipts.block_bloom_may_contain(index, &log.address, topics),
+                                Some(true)
+                            );
+                        }
+                    }
+                }
+            }
+            assert_eq!(receipts.block_bloom_may_contain(receipts.len(), &Address::ZERO, &[]), None);
+        }
+
+        #[test]
+        fn arb_receipts_roundtrip(receipts in arb_receipts(8, 8)) {
+            // Compact encoding, including pruned receipts
+            let mut buf = vec![];
+            let len = receipts.receipt_vec.clone().to_compact(&mut buf);
+            let (decoded, _) = Vec::<Vec<Option<Receipt>>>::from_compact(&buf, len);
+            assert_eq!(Receipts::from_vec(decoded), receipts);
+
+            // RLP encoding of the blocks without pruned receipts
+            for block in receipts.iter() {
+                let Some(block) = block.iter().cloned().collect::<Option<Vec<_>>>() else { continue };
+                let block = block.into_iter().map(Receipt::with_bloom).collect::<Vec<_>>();
+
+                let mut encoded = vec![];
+                block.encode(&mut encoded);
+                assert_eq!(Vec::<ReceiptWithBloom>::decode(&mut &encoded[..]).unwrap(), block);
+            }
+        }
+    }
+
+    #[test]
+    fn compact_versioned() {
+        // the longest possible length of `cumulative_gas_used`
+        let receipt = new_receipt(
+            TxType::EIP1559,
+            u64::MAX,
+            vec![Log {
+                address: address!("0000000000000000000000000000000000000011"),
+                topics: vec![b256!(
+                    "000000000000000000000000000000000000000000000000000000000000dead"
+                )],
+                data: bytes!("0100ff"),
+            }],
+        );
+
+        // small receipts are not compressed
+        for (receipt, compressed) in [(Receipt::default(), false), (receipt, true)] {
+            // v0, as found in existing databases
+            let mut v0 = vec![];
+            receipt_v0(&receipt).to_compact(&mut v0);
+            assert_eq!(Receipt::compact_version(&v0), Ok(0));
+            assert_eq!(Receipt::from_compact(&v0, v0.len()).0, receipt);
+
+            // v1
+            let mut v1 = vec![];
+            let len = receipt.clone().to_compact(&mut v1);
+            assert_eq!(len, v1.len());
+            assert_eq!(Receipt::compact_version(&v1), Ok(RECEIPT_CODEC_VERSION));
+            assert_eq!(v1[0] & RECEIPT_ZSTD_BIT != 0, compressed);
+            assert_eq!(Receipt::from_compact(&v1, len).0, receipt);
+
+            // unknown version
+            let version = RECEIPT_CODEC_VERSION + 1;
+            v1[0] = (v1[0] & !RECEIPT_VERSION_BITS) | version;
+            assert_eq!(
+                Receipt::compact_version(&v1),
+                Err(ReceiptError::UnsupportedCodecVersion(version))
+            );
+            assert_eq!(
+                Receipt::try_from_compact(&v1, len).err(),
+                Some(ReceiptError::UnsupportedCodecVersion(version))
+            );
+        }
+    }
+
+    /// Returns a successful receipt, without the fields of deposit transactions.
+    fn new_receipt(tx_type: TxType, cumulative_gas_used: u64, logs: Vec<Log>) -> Receipt {
+        Receipt {
+            tx_type,
+            success: true,
+            cumulative_gas_used,
+            logs,
+            #[cfg(feature = "optimism")]
+            deposit_nonce: None,
+            #[cfg(feature = "optimism")]
+            deposit_receipt_version: None,
+        }
+    }
+
+    /// Returns the fields of the receipt in the version 0 of its [Compact] encoding.
+    fn receipt_v0(receipt: &Receipt) -> ReceiptV0 {
+        ReceiptV0 {
+            tx_type: receipt.tx_type,
+            success: receipt.success,
+            cumulative_gas_used: receipt.cumulative_gas_used,
+            logs: receipt.logs.clone(),
+            #[cfg(feature = "optimism")]
+            deposit_nonce: receipt.deposit_nonce,
+            #[cfg(feature = "optimism")]
+            deposit_receipt_version: receipt.deposit_receipt_version,
+        }
+    }
+
+    #[test]
+    fn bloom_may_contain() {
+        let address = address!("0000000000000000000000000000000000000011");
+        let topic = b256!("000000000000000000000000000000000000000000000000000000000000dead");
+        let receipt = new_receipt(
+            TxType::EIP1559,
+            21000,
+            vec![Log { address, topics: vec![topic], data: Bytes::default() }],
+        );
+        let other_address = address!("0000000000000000000000000000000000000022");
+        let other_topic = b256!("000000000000000000000000000000000000000000000000000000000000beef");
+
+        assert!(receipt.bloom_may_contain(&address, &[]));
+        assert!(receipt.bloom_may_contain(&address, &[topic]));
+        assert!(!receipt.bloom_may_contain(&other_address, &[]));
+        assert!(!receipt.bloom_may_contain(&address, &[topic, other_topic]));
+
+        let mut receipts = Receipts::from_block_receipt(vec![receipt.clone()]);
+        receipts.push(vec![Some(receipt), None]);
+        assert_eq!(receipts.block_bloom_may_contain(0, &other_address, &[]), Some(false));
+        // pruned receipts can't be ruled out
+        assert_eq!(receipts.block_bloom_may_contain(1, &other_address, &[]), Some(true));
+        assert_eq!(receipts.block_bloom_may_contain(2, &address, &[]), None);
+    }
+
+    #[test]
+    fn receipts_builder_lazy_bloom() {
+        let receipt = |address, topic| {
+            new_receipt(
+                TxType::EIP1559,
+                21000,
+                vec![Log { address, topics: vec![topic], data: Bytes::default() }],
+            )
+        };
+        let block = vec![
+            receipt(
+                address!("0000000000000000000000000000000000000011"),
+                b256!("000000000000000000000000000000000000000000000000000000000000dead"),
+            ),
+            receipt(
+                address!("0000000000000000000000000000000000000022"),
+                b256!("000000000000000000000000000000000000000000000000000000000000beef"),
+            ),
+        ];
+        // counts the blooms computed by the builders
+        let blooms = || RECEIPTS_BUILDER_BLOOMS.with(|blooms| blooms.get());
+
+        let mut builder = ReceiptsBuilder::with_capacity(block.len());
+        builder.extend(block.clone());
+        assert_eq!(builder.len(), 2);
+        assert_eq!(builder.build(), Receipts::from_block_receipt(block.clone()));
+        assert_eq!(blooms(), 0);
+
+        // building without the bloom doesn't compute it
+        let mut builder = ReceiptsBuilder::new();
+        builder.extend(block.clone());
+        let (receipts, block_bloom) = builder.build_with_bloom(false);
+        assert_eq!(receipts, Receipts::from_block_receipt(block.clone()));
+        assert_eq!(block_bloom, None);
+        assert_eq!(blooms(), 0);
+
+        // the bloom is computed once when requested
+        let mut builder = ReceiptsBuilder::new();
+        builder.extend(block.clone());
+        let (receipts, block_bloom) = builder.build_with_bloom(true);
+        assert_eq!(blooms(), 1);
+
+        let mut expected = Receipts::new();
+        let expected_bloom = expected.push_with_bloom(block);
+        assert_eq!(block_bloom, Some(expected_bloom));
+        assert_eq!(receipts, expected);
+    }
+
+    #[test]
+    fn tx_type_predicates() {
+        let types = [
+            TxType::Legacy,
+            TxType::EIP2930,
+            TxType::EIP1559,
+            TxType::EIP4844,
+            #[cfg(feature = "optimism")]
+            TxType::DEPOSIT,
+        ];
+        for tx_type in types {
+            let receipt = Receipt { tx_type, ..Default::default() };
+            let predicates = [
+                (receipt.is_legacy(), TxType::Legacy),
+                (receipt.is_eip2930(), TxType::EIP2930),
+                (receipt.is_eip1559(), TxType::EIP1559),
+                (receipt.is_eip4844(), TxType::EIP4844),
+                #[cfg(feature = "optimism")]
+                (receipt.is_deposit(), TxType::DEPOSIT),
+            ];
+            let matching = predicates.iter().filter(|(is_type, _)| *is_type).collect::<Vec<_>>();
+            assert_eq!(matching, [&(true, tx_type)]);
+        }
+    }
+
+    #[test]
+    fn per_tx_gas_used() {
+        let receipt =
+            |cumulative_gas_used| new_receipt(TxType::Legacy, cumulative_gas_used, vec![]);
+
+        let mut receipts = Receipts::from_block_receipt(vec![receipt(21000)]);
+        receipts.push_with_bloom(vec![receipt(21000), receipt(71000), receipt(92000)]);
+        receipts.push(vec![Some(receipt(21000)), None]);
+
+        assert_eq!(receipts.per_tx_gas_used(0), Ok(vec![21000]));
+        assert_eq!(receipts.per_tx_gas_used(1), Ok(vec![21000, 50000, 21000]));
+        assert_eq!(receipts.per_tx_gas_used(2), Err(PruneSegmentError::ReceiptsPruned));
+        assert_eq!(receipts.per_tx_gas_used(3), Ok(vec![]));
+    }
+
+    #[test]
+    fn block_and_range_gas_used() {
+        let receipt =
+            |cumulative_gas_used| new_receipt(TxType::Legacy, cumulative_gas_used, vec![]);
+
+        let mut receipts = Receipts::from_block_receipt(vec![receipt(21000)]);
+        receipts.push_with_bloom(vec![receipt(21000), receipt(71000), receipt(92000)]);
+        receipts.push(vec![]);
+        // only the last receipt is needed for the block total
+        receipts.push(vec![None, Some(receipt(42000))]);
+
+        assert_eq!(receipts.block_gas_used(0), Some(21000));
+        assert_eq!(receipts.block_gas_used(1), Some(92000));
+        assert_eq!(receipts.block_gas_used(2), Some(0));
+        assert_eq!(receipts.block_gas_used(3), Some(42000));
+        assert_eq!(receipts.block_gas_used(4), None);
+        assert_eq!(receipts.range_gas_used(), Ok(155000));
+
+        // a pruned tail hides the block total
+        receipts.push(vec![Some(receipt(21000)), None]);
+        assert_eq!(receipts.block_gas_used(4), None);
+        assert_eq!(receipts.range_gas_used(), Err(PruneSegmentError::ReceiptsPruned));
+
+        assert_eq!(Receipts::new().range_gas_used(), Ok(0));
+    }
+
+    #[test]
+    fn compact_trailing_empty() {
+        let receipt = new_receipt(TxType::Legacy, 21000, vec![]);
+
+        let mut receipts = Receipts::from_vec(vec![
+            vec![Some(receipt.clone())],
+            vec![],
+            vec![None],
+            vec![],
+            vec![Some(receipt.clone())],
+            vec![],
+            vec![],
+        ]);
+        receipts.compact_trailing_empty();
+        assert_eq!(
+            receipts,
+            Receipts::from_vec(vec![
+                vec![Some(receipt.clone())],
+                vec![],
+                vec![None],
+                vec![],
+                vec![Some(receipt)],
+            ])
+        );
+
+        let mut receipts = Receipts::from_vec(vec![vec![], vec![]]);
+        receipts.compact_trailing_empty();
+        assert!(receipts.is_empty());
+    }
+
+    #[test]
+    fn count_present_and_pruned() {
+        let receipt = new_receipt(TxType::Legacy, 21000, vec![]);
+
+        let receipts = Receipts::from_vec(vec![
+            vec![Some(receipt.clone()), None],
+            vec![],
+            vec![None, None, Some(receipt.clone())],
+            vec![Some(receipt)],
+        ]);
+        assert_eq!(receipts.count_present(), 3);
+        assert_eq!(receipts.count_pruned(), 3);
+        let total = receipts.iter().map(Vec::len).sum::<usize>();
+        assert_eq!(receipts.count_present() + receipts.count_pruned(), total);
+
+        assert_eq!(Receipts::new().count_present(), 0);
+        assert_eq!(Receipts::new().count_pruned(), 0);
+    }
+
+    #[test]
+    fn iter_with_block_numbers() {
+        let receipt =
+            |cumulative_gas_used| new_receipt(TxType::Legacy, cumulative_gas_used, vec![]);
+
+        let receipts =
+            Receipts::from_vec(vec![vec![Some(receipt(1))], vec![], vec![None, Some(receipt(3))]]);
+        let numbered = receipts.iter_with_block_numbers(100).collect::<Vec<_>>();
+        assert_eq!(
+            numbered,
+            vec![
+                (100, &vec![Some(receipt(1))]),
+                (101, &vec![]),
+                (102, &vec![None, Some(receipt(3))]),
+            ]
+        );
+
+        assert_eq!(Receipts::new().iter_with_block_numbers(100).next(), None);
+    }
+
+    #[test]
+    fn sparse_roundtrip() {
+        let receipt =
+            |cumulative_gas_used| new_receipt(TxType::EIP1559, cumulative_gas_used, vec![]);
+
+        let receipts = Receipts::from_vec(vec![
+            vec![None, Some(receipt(1)), None],
+            vec![],
+            vec![None, None],
+            vec![Some(receipt(2)), None, None, Some(receipt(3))],
+        ]);
+        let sparse = receipts.to_sparse();
+        assert_eq!(sparse, vec![((0, 1), receipt(1)), ((3, 0), receipt(2)), ((3, 3), receipt(3))]);
+
+        let shape = receipts.iter().map(Vec::len).collect::<Vec<_>>();
+        assert_eq!(Receipts::from_sparse(&shape, sparse.clone()), Some(receipts));
+
+        // receipts outside the shape
+        assert_eq!(Receipts::from_sparse(&shape[..3], sparse.clone()), None);
+        assert_eq!(Receipts::from_sparse(&[2, 0, 0, 4], sparse), None);
+    }
+
+    #[test]
+    fn duplicate_logs() {
+        let log = |topic: u8, data: &'static [u8]| Log {
+            address: Address::with_last_byte(1),
+            topics: vec![B256::with_last_byte(topic)],
+            data: Bytes::from_static(data),
+        };
+        let receipt = |logs| new_receipt(TxType::EIP1559, 21000, logs);
+
+        // logs that only differ by topic or data are distinct
+        let distinct = receipt(vec![log(1, b"a"), log(2, b"a"), log(1, b"b")]);
+        assert!(!distinct.has_duplicate_logs());
+        assert!(!receipt(vec![]).has_duplicate_logs());
+
+        let duplicate = receipt(vec![log(1, b"a"), log(2, b"a"), log(1, b"a")]);
+        assert!(duplicate.has_duplicate_logs());
+
+        let receipts = Receipts::from_vec(vec![
+            vec![Some(distinct.clone())],
+            vec![Some(duplicate)],
+            // the same logs in different receipts of the block
+            vec![Some(distinct.clone()), Some(distinct.clone())],
+            // the duplicate receipt is pruned
+            vec![Some(distinct), None],
+        ]);
+        assert_eq!(receipts.blocks_with_duplicate_logs().collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn single_block() {
+        let receipt = new_receipt(TxType::Legacy, 21000, vec![]);
+
+        assert_eq!(Receipts::new().single_block(), None);
+
+        let receipts = Receipts::from_block_receipt(vec![receipt.clone()]);
+        assert_eq!(receipts.single_block(), Some(&vec![Some(receipt.clone())]));
+        assert_eq!(Receipts::from_vec(vec![vec![]]).single_block(), Some(&vec![]));
+
+        let receipts = Receipts::from_vec(vec![vec![Some(receipt)], vec![None]]);
+        assert_eq!(receipts.single_block(), None);
+    }
+
+    #[test]
+    fn estimated_json_len() {
+        let log = |topics: usize, data_len: usize| Log {
+            address: Address::with_last_byte(1),
+            topics: vec![B256::with_last_byte(2); topics],
+            data: Bytes::from(vec![0xab; data_len]),
+        };
+        let receipts = [
+            (TxType::Legacy, true, 0, vec![]),
+            (TxType::EIP1559, false, 21_000, vec![log(0, 0)]),
+            (TxType::EIP2930, true, 1_234_567, vec![log(1, 32), log(4, 1000), log(2, 3)]),
+            (TxType::EIP4844, true, u64::MAX, vec![log(3, 100_000)]),
+        ]
+        .into_iter()
+        .map(|(tx_type, success, cumulative_gas_used, logs)| Receipt {
+            tx_type,
+            success,
+            cumulative_gas_used,
+            logs,
+            #[cfg(feature = "optimism")]
+            deposit_nonce: None,
+            #[cfg(feature = "optimism")]
+            deposit_receipt_version: Some(1),
+        })
+        .collect::<Vec<_>>();
+
+        // the estimate is an upper bound, only off by the longest transaction type and status
+        for receipt in &receipts {
+            let actual = serde_json::to_string(receipt).unwrap().len();
+            let estimate = receipt.estimated_json_len();
+            assert!(estimate >= actual && estimate - actual <= 2, "{estimate} vs {actual}");
+        }
+
+        let receipts = Receipts::from_vec(vec![
+            receipts.iter().cloned().map(Some).collect(),
+            vec![],
+            vec![None, Some(receipts[2].clone())],
+        ]);
+        let lens = receipts.estimated_json_len_by_block().collect::<Vec<_>>();
+        let actual = receipts
+            .iter()
+            .map(|block| {
+                serde_json::to_string(&block.iter().flatten().collect::<Vec<_>>()).unwrap().len()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(lens.len(), actual.len());
+        for (estimate, actual) in lens.into_iter().zip(actual) {
+            assert!(estimate >= actual && estimate - actual <= 8, "{estimate} vs {actual}");
+        }
+    }
+
+    #[test]
+    fn receipt_with_blob_gas_used() {
+        let receipt = new_receipt(TxType::EIP4844, 21000, vec![]);
+
+        let with_meta =
+            ReceiptWithMeta::from(receipt.clone()).with_blob_gas_used(DATA_GAS_PER_BLOB).unwrap();
+        assert_eq!(with_meta.blob_gas_used, Some(DATA_GAS_PER_BLOB));
+        assert_eq!(with_meta.into_receipt(), receipt);
+
+        // only blob transactions use blob gas
+        let receipt = Receipt { tx_type: TxType::EIP1559, ..receipt };
+        assert_eq!(ReceiptWithMeta::new(receipt).with_blob_gas_used(DATA_GAS_PER_BLOB), None);
+    }
+
+    #[test]
+    fn shrink_to_fit() {
+        let receipt = new_receipt(TxType::Legacy, 21000, vec![]);
+
+        let mut receipts = Receipts::with_capacity(16);
+        assert!(receipts.capacity() >= 16);
+        for _ in 0..10 {
+            let mut block = Vec::with_capacity(32);
+            block.extend(std::iter::repeat(Some(receipt.clone())).take(4));
+            receipts.push(block);
+        }
+
+        // drop the receipts of the reverted blocks
+        receipts.truncate(2);
+        receipts.shrink_to_fit();
+        assert_eq!(receipts.capacity(), 2);
+        assert!(receipts.iter().all(|block| block.len() == 4 && block.capacity() == 4));
+    }
+
+    #[test]
+    fn peek_tx_type() {
+        let mut tx_types = vec![TxType::Legacy, TxType::EIP2930, TxType::EIP1559, TxType::EIP4844];
+        #[cfg(feature = "optimism")]
+        tx_types.push(TxType::DEPOSIT);
+
+        for tx_type in tx_types {
+            let receipt = new_receipt(tx_type, 21000, vec![]).with_bloom();
+
+            let mut encoded = vec![];
+            receipt.encode(&mut encoded);
+            assert_eq!(ReceiptWithBloom::peek_tx_type(&encoded), Ok(tx_type));
+            assert_eq!(ReceiptWithBloom::decode(&mut &encoded[..]).unwrap(), receipt);
+        }
+
+        assert!(ReceiptWithBloom::peek_tx_type(&[]).is_err());
+        assert!(ReceiptWithBloom::peek_tx_type(&[alloy_rlp::EMPTY_LIST_CODE]).is_err());
+        assert!(ReceiptWithBloom::peek_tx_type(&[0x81, 0x05]).is_err());
+    }
+
+    #[test]
+    fn decode_lenient_padded_typed_receipt() {
+        let receipt = new_receipt(
+            TxType::EIP1559,
+            21000,
+            vec![Log {
+                address: address!("0000000000000000000000000000000000000011"),
+                topics: vec![],
+                data: bytes!("0100ff"),
+            }],
+        )
+        .with_bloom();
+
+        // a typed receipt with padding after the fields, followed by another item
+        let mut fields = vec![];
+        receipt.receipt.success.encode(&mut fields);
+        receipt.receipt.cumulative_gas_used.encode(&mut fields);
+        receipt.bloom.encode(&mut fields);
+        receipt.receipt.logs.encode(&mut fields);
+        fields.extend_from_slice(&[0xde, 0xad]);
+        let mut list = vec![];
+        alloy_rlp::Header { list: true, payload_length: fields.len() }.encode(&mut list);
+        list.extend(fields);
+        let mut padded = vec![];
+        alloy_rlp::Header { list: false, payload_length: list.len() + 1 }.encode(&mut padded);
+        padded.push(0x02);
+        padded.extend(list);
+        padded.push(0xff);
+
+        assert!(matches!(
+            ReceiptWithBloom::decode(&mut &padded[..]),
+            Err(alloy_rlp::Error::ListLengthMismatch { .. })
+        ));
+        let buf = &mut &padded[..];
+        assert_eq!(ReceiptWithBloom::decode_lenient(buf).unwrap(), receipt);
+        assert_eq!(*buf, &[0xff][..]);
+
+        // well-formed receipts decode the same
+        let mut encoded = vec![];
+        receipt.encode(&mut encoded);
+        assert_eq!(ReceiptWithBloom::decode_lenient(&mut &encoded[..]).unwrap(), receipt);
+    }
+
+    #[test]
+    fn rlp_payload_length() {
+        for tx_type in [TxType::Legacy, TxType::EIP2930, TxType::EIP1559, TxType::EIP4844] {
+            let receipt = new_receipt(
+                tx_type,
+                0x1u64,
+                vec![Log {
+                    address: address!("0000000000000000000000000000000000000011"),
+                    topics: vec![b256!(
+                        "000000000000000000000000000000000000000000000000000000000000dead"
+                    )],
+                    data: bytes!("0100ff"),
+                }],
+            )
+            .with_bloom();
+            let payload_length = receipt.rlp_payload_length();
+
+            let mut encoded = vec![];
+            receipt.encode_inner(&mut encoded, false);
+
+            // Typed receipts are prefixed with their type
+            let mut buf = &encoded[..];
+            if tx_type != TxType::Legacy {
+                assert_eq!(buf[0], u8::from(tx_type));
+                buf = &buf[1..];
+            }
+
+            let header = alloy_rlp::Header::decode(&mut buf).unwrap();
+            assert!(header.list);
+            assert_eq!(header.payload_length, payload_length);
+            assert_eq!(buf.len(), payload_length);
+        }
+    }
+
+    #[test]
+    fn build_topic_index() {
+        let topics = [
+            b256!("000000000000000000000000000000000000000000000000000000000000dead"),
+            b256!("000000000000000000000000000000000000000000000000000000000000beef"),
+            b256!("000000000000000000000000000000000000000000000000000000000000f00d"),
+        ];
+        let log = |topics: Vec<B256>| Log {
+            address: address!("0000000000000000000000000000000000000011"),
+            topics,
+            data: Bytes::default(),
+        };
+        let receipt = |logs| Some(new_receipt(TxType::EIP1559, 21000, logs));
+
+        let receipts = Receipts::from_vec(vec![vec![
+            receipt(vec![log(vec![topics[0], topics[1]]), log(vec![topics[1], topics[1]])]),
+            None,
+            receipt(vec![]),
+            receipt(vec![log(vec![]), log(vec![topics[0]])]),
+        ]]);
+
+        let index = receipts.build_topic_index(0).unwrap();
+        for topic in topics {
+            let scanned = receipts[0]
+                .iter()
+                .enumerate()
+                .filter_map(|(tx_index, receipt)| Some((tx_index, receipt.as_ref()?)))
+                .flat_map(|(tx_index, receipt)| {
+                    receipt
+                        .logs
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, log)| log.topics.contains(&topic))
+                        .map(move |(log_index, _)| (tx_index, log_index))
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(index.get(&topic).cloned().unwrap_or_default(), scanned);
+        }
+        assert_eq!(index[&topics[0]], vec![(0, 0), (3, 1)]);
+        assert!(!index.contains_key(&topics[2]));
+
+        assert_eq!(receipts.build_topic_index(1), None);
+    }
+
+    #[test]
+    fn receipts_diff() {
+        let receipt = new_receipt(
+            TxType::EIP1559,
+            21000,
+            vec![Log {
+                address: address!("0000000000000000000000000000000000000011"),
+                topics: vec![b256!(
+                    "000000000000000000000000000000000000000000000000000000000000dead"
+                )],
+                data: bytes!("0100ff"),
+            }],
+        );
+        let ours = Receipts::from_vec(vec![
+            vec![Some(receipt.clone())],
+            vec![Some(receipt.clone()), Some(receipt.clone())],
+        ]);
+        assert!(ours.diff(&ours).is_empty());
+
+        let mut theirs = ours.clone();
+        theirs[0][0].as_mut().unwrap().cumulative_gas_used = 42000;
+        theirs[1][1].as_mut().unwrap().logs[0].topics[0] =
+            b256!("000000000000000000000000000000000000000000000000000000000000beef");
+
+        assert_eq!(
+            ours.diff(&theirs),
+            vec![
+                ReceiptDiff { block_idx: 0, tx_idx: 0, kind: ReceiptDiffKind::CumulativeGasUsed },
+                ReceiptDiff { block_idx: 1, tx_idx: 1, kind: ReceiptDiffKind::Log(0) },
+            ]
+        );
+
+        // Pruned on one side only
+        theirs[1][0] = None;
+        assert_eq!(
+            ours.diff(&theirs)[1],
+            ReceiptDiff { block_idx: 1, tx_idx: 0, kind: ReceiptDiffKind::Presence }
+        );
+    }
+
+    #[test]
+    fn receipts_block_with_blooms() {
+        let receipt =
+            |cumulative_gas_used, logs| new_receipt(TxType::EIP1559, cumulative_gas_used, logs);
+        let log = Log {
+            address: address!("0000000000000000000000000000000000000011"),
+            topics: vec![b256!("000000000000000000000000000000000000000000000000000000000000dead")],
+            data: bytes!("0100ff"),
+        };
+        let block = vec![receipt(21000, vec![log.clone()]), receipt(42000, vec![log])];
+        let receipts = Receipts::from_vec(vec![
+            block.iter().cloned().map(Some).collect(),
+            vec![],
+            vec![Some(receipt(21000, vec![])), None],
+        ]);
+
+        assert_eq!(
+            receipts.block_with_blooms(0),
+            Some(block.into_iter().map(Receipt::with_bloom).collect())
+        );
+        assert_eq!(receipts.block_with_blooms(1), Some(vec![]));
+        assert_eq!(receipts.block_with_blooms(2), None);
+        assert_eq!(receipts.block_with_blooms(3), None);
+    }
+
+    #[test]
+    fn receipts_ordered_block_logs() {
+        let receipt = |cumulative_gas_used, logs| {
+            Some(new_receipt(TxType::EIP1559, cumulative_gas_used, logs))
+        };
+        let log = |byte: u8| Log {
+            address: address!("0000000000000000000000000000000000000011"),
+            topics: vec![],
+            data: Bytes::from(vec![byte]),
+        };
+
+        let receipts = Receipts::from_vec(vec![
+            vec![
+                receipt(21000, vec![log(0), log(1)]),
+                receipt(42000, vec![]),
+                receipt(63000, vec![log(2)]),
+                receipt(84000, vec![log(3), log(4), log(5)]),
+            ],
+            vec![],
+            vec![receipt(21000, vec![log(0)]), None],
+        ]);
+
+        let entries = receipts.ordered_block_logs(0).unwrap();
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| (entry.tx_index, entry.log_index, entry.cumulative_gas_used))
+                .collect::<Vec<_>>(),
+            vec![
+                (0, 0, 21000),
+                (0, 1, 21000),
+                (2, 0, 63000),
+                (3, 0, 84000),
+                (3, 1, 84000),
+                (3, 2, 84000)
+            ]
+        );
+        assert!(entries.iter().enumerate().all(|(i, entry)| entry.log == log(i as u8)));
+
+        assert_eq!(receipts.ordered_block_logs(1), Some(vec![]));
+        assert_eq!(receipts.ordered_block_logs(2), None);
+        assert_eq!(receipts.ordered_block_logs(3), None);
+    }
+
+    #[test]
+    fn receipt_to_compact_with_level() {
+        let words = ["transfer", "approve", "swap", "mint", "burn", "deposit", "withdraw", "sync"];
+        let mut seed = 1u64;
+        let data = (0..2000)
+            .map(|_| {
+                seed = (seed * 1103515245 + 12345) % (1 << 31);
+                format!("{} ", words[(seed >> 16) as usize % words.len()])
+            })
+            .collect::<String>();
+        let receipt = new_receipt(
+            TxType::EIP1559,
+            21000,
+            vec![Log {
+                address: address!("0000000000000000000000000000000000000011"),
+                topics: vec![],
+                data: Bytes::from(data.into_bytes()),
+            }],
+        );
+
+        let mut default = vec![];
+        receipt.clone().to_compact(&mut default);
+        let mut high = vec![];
+        assert_eq!(receipt.to_compact_with_level(&mut high, 19).unwrap(), high.len());
+        assert!(high.len() < default.len(), "{} >= {}", high.len(), default.len());
+
+        // decodes with the standard decompressor
+        assert_eq!(Receipt::from_compact(&high, high.len()).0, receipt);
+
+        // the compressor of the thread is not affected
+        let mut after = vec![];
+        receipt.clone().to_compact(&mut after);
+        assert_eq!(after, default);
+    }
+
+    #[test]
+    fn receipts_merge_prefer_present() {
+        let receipt =
+            |cumulative_gas_used| new_receipt(TxType::EIP1559, cumulative_gas_used, vec![]);
+
+        // fill
+        let mut receipts = Receipts::from_vec(vec![vec![Some(receipt(1)), None], vec![None]]);
+        let other = Receipts::from_vec(vec![
+            vec![None, Some(receipt(2))],
+            vec![None, Some(receipt(4))],
+            vec![Some(receipt(5))],
+        ]);
+        assert_eq!(receipts.merge_prefer_present(other), Ok(3));
+        assert_eq!(
+            receipts,
+            Receipts::from_vec(vec![
+                vec![Some(receipt(1)), Some(receipt(2))],
+                vec![None, Some(receipt(4))],
+                vec![Some(receipt(5))],
+            ])
+        );
+
+        // no-op
+        let expected = receipts.clone();
+        assert_eq!(receipts.merge_prefer_present(expected.clone()), Ok(0));
+        assert_eq!(receipts.merge_prefer_present(Receipts::new()), Ok(0));
+        assert_eq!(receipts, expected);
+
+        // conflict
+        let other =
+            Receipts::from_vec(vec![vec![None, None], vec![Some(receipt(3)), Some(receipt(0))]]);
+        assert_eq!(
+            receipts.merge_prefer_present(other),
+            Err(ReceiptError::Conflict { block_index: 1, tx_index: 1 })
+        );
+        assert_eq!(receipts, expected);
+    }
+
+    #[test]
+    fn receipt_compact_size_hint() {
+        let log = |topics, data: Vec<u8>| Log {
+            address: address!("0000000000000000000000000000000000000011"),
+            topics: vec![
+                b256!("000000000000000000000000000000000000000000000000000000000000dead");
+                topics
+            ],
+            data: Bytes::from(data),
+        };
+        let receipt = |cumulative_gas_used, logs| Receipt {
+            tx_type: TxType::EIP1559,
+            success: true,
+            cumulative_gas_used,
+            logs,
+            #[cfg(feature = "optimism")]
+            deposit_nonce: Some(u64::MAX),
+            #[cfg(feature = "optimism")]
+            deposit_receipt_version: Some(u64::MAX),
+        };
+
+        for receipt in [
+            Receipt::default(),
+            receipt(u64::MAX, vec![]),
+            receipt(21000, vec![log(0, vec![])]),
+            receipt(21000, vec![log(4, (0..=255).collect()), log(1, vec![0; 200])]),
+        ] {
+            let mut data = vec![];
+            receipt.clone().to_compact(&mut data);
+            assert!(receipt.compact_size_hint() >= data.len(), "{receipt:?}");
+        }
+
+        assert_eq!(varuint_len(0), 1);
+        assert_eq!(varuint_len(0x7f), 1);
+        assert_eq!(varuint_len(0x80), 2);
+        assert_eq!(varuint_len(usize::MAX), (usize::BITS as usize + 6) / 7);
+    }
+
+    #[test]
+    fn receipt_status_code() {
+        let mut receipt = new_receipt(TxType::Legacy, 21000, vec![]);
+        assert_eq!(receipt.status_code(), 1);
+
+        receipt.success = false;
+        assert_eq!(receipt.status_code(), 0);
+    }
+
+    #[test]
+    fn receipts_from_sorted_tuples() {
+        let receipt =
+            |cumulative_gas_used| new_receipt(TxType::Legacy, cumulative_gas_used, vec![]);
+
+        // Gaps in blocks and transactions
+        let receipts = Receipts::from_sorted_tuples(
+            10,
+            [(10, 0, receipt(1)), (10, 2, receipt(2)), (12, 1, receipt(3)), (12, 2, receipt(4))],
+        )
+        .unwrap();
+        assert_eq!(
+            receipts,
+            Receipts::from_vec(vec![
+                vec![Some(receipt(1)), None, Some(receipt(2))],
+                vec![],
+                vec![None, Some(receipt(3)), Some(receipt(4))],
+            ])
+        );
+        assert_eq!(Receipts::from_sorted_tuples(10, []).unwrap(), Receipts::new());
+
+        // Out of order and duplicate tuples, and tuples before the first block
+        for (tuples, block_number, tx_index) in [
+            (vec![(10, 1, receipt(1)), (10, 0, receipt(2))], 10, 0),
+            (vec![(11, 0, receipt(1)), (10, 3, receipt(2))], 10, 3),
+            (vec![(10, 1, receipt(1)), (10, 1, receipt(2))], 10, 1),
+            (vec![(9, 0, receipt(1))], 9, 0),
+        ] {
+            assert_eq!(
+                Receipts::from_sorted_tuples(10, tuples),
+                Err(ReceiptError::OutOfOrder { block_number, tx_index })
+            );
+        }
+    }
+
+    #[test]
+    fn receipt_with_bloom_push_log() {
+        let mut receipt = new_receipt(TxType::EIP1559, 21000, vec![]).with_bloom();
+        assert_eq!(receipt.bloom, Bloom::ZERO);
+
+        let logs = [
+            Log {
+                address: address!("0000000000000000000000000000000000000011"),
+                topics: vec![
+                    b256!("000000000000000000000000000000000000000000000000000000000000dead"),
+                    b256!("000000000000000000000000000000000000000000000000000000000000beef"),
+                ],
+                data: bytes!("0100ff"),
+            },
+            Log {
+                address: address!("0000000000000000000000000000000000000022"),
+                topics: vec![],
+                data: bytes!(""),
+            },
+        ];
+        for log in logs {
+            receipt.push_log(log);
+            assert_eq!(receipt.bloom, receipt.receipt.bloom_slow());
+        }
+        assert_eq!(receipt.receipt.logs.len(), 2);
+    }
+
+    #[test]
+    fn receipts_eq_as_multiset() {
+        let receipt =
+            |tx_type, cumulative_gas_used| new_receipt(tx_type, cumulative_gas_used, vec![]);
+        let a = Some(receipt(TxType::Legacy, 21000));
+        let b = Some(receipt(TxType::EIP1559, 42000));
+
+        let ours = Receipts::from_vec(vec![vec![a.clone(), b.clone(), None], vec![a.clone()]]);
+        let theirs = Receipts::from_vec(vec![vec![None, b.clone(), a.clone()], vec![a.clone()]]);
+        assert!(ours.eq_as_multiset(&theirs));
+        assert!(theirs.eq_as_multiset(&ours));
+        assert_ne!(ours, theirs);
+
+        // same receipts with different multiplicities
+        let theirs =
+            Receipts::from_vec(vec![vec![a.clone(), a.clone(), b.clone()], vec![a.clone()]]);
+        assert!(!ours.eq_as_multiset(&theirs));
+
+        // receipts moved to another block
+        let theirs = Receipts::from_vec(vec![vec![a.clone(), b, None, a]]);
+        assert!(!ours.eq_as_multiset(&theirs));
     }
 }
@@ -4,7 +4,11 @@ use crate::{
 };
 use derive_more::Display;
 use serde::{Deserialize, Serialize};
-use std::{ffi::OsStr, ops::RangeInclusive, str::FromStr};
+use std::{
+    ffi::OsStr,
+    ops::{Range, RangeInclusive},
+    str::FromStr,
+};
 use strum::{AsRefStr, EnumString};
 
 #[derive(
@@ -54,6 +58,15 @@ impl SnapshotSegment {
         }
     }
 
+    /// Returns `true` if the rows of the segment are keyed by block number, or `false` if they are
+    /// keyed by transaction number.
+    pub const fn is_block_based(&self) -> bool {
+        match self {
+            SnapshotSegment::Headers => true,
+            SnapshotSegment::Transactions | SnapshotSegment::Receipts => false,
+        }
+    }
+
     /// Returns the default file name for the provided segment and range.
     pub fn filename(
         &self,
@@ -133,7 +146,7 @@ impl SnapshotSegment {
 }
 
 /// A segment header that contains information common to all segments. Used for storage.
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
 pub struct SegmentHeader {
     /// Block range of the snapshot segment
     block_range: RangeInclusive<BlockNumber>,
@@ -141,6 +154,11 @@ pub struct SegmentHeader {
     tx_range: RangeInclusive<TxNumber>,
     /// Segment type
     segment: SnapshotSegment,
+    /// Cumulative number of transactions at the end of every block of the segment, in block
+    /// order, if known. See [`SegmentHeader::tx_range_for_block`].
+    cumulative_tx_counts: Vec<u64>,
+    /// Whether the second column of a transactions segment holds the senders of the transactions.
+    has_senders: bool,
 }
 
 impl SegmentHeader {
@@ -150,7 +168,63 @@ impl SegmentHeader {
         tx_range: RangeInclusive<TxNumber>,
         segment: SnapshotSegment,
     ) -> Self {
-        Self { block_range, tx_range, segment }
+        Self {
+            block_range,
+            tx_range,
+            segment,
+            cumulative_tx_counts: Vec::new(),
+            has_senders: false,
+        }
+    }
+
+    /// Marks the second column of a transactions segment as holding the senders of the
+    /// transactions.
+    pub fn with_senders(mut self) -> Self {
+        self.has_senders = true;
+        self
+    }
+
+    /// Returns `true` if the second column of a transactions segment holds the senders of the
+    /// transactions. See [`SegmentHeader::with_senders`].
+    pub fn has_senders(&self) -> bool {
+        self.has_senders
+    }
+
+    /// Sets the number of transactions of every block of the segment, in block order.
+    pub fn with_block_tx_counts(mut self, tx_counts: impl IntoIterator<Item = u64>) -> Self {
+        self.cumulative_tx_counts.clear();
+        self.extend_block_tx_counts(tx_counts);
+        self
+    }
+
+    /// Appends the number of transactions of the next blocks of the segment, e.g. after
+    /// [extending](SegmentHeader::extend) it.
+    pub fn extend_block_tx_counts(&mut self, tx_counts: impl IntoIterator<Item = u64>) {
+        let mut total = self.cumulative_tx_counts.last().copied().unwrap_or_default();
+        self.cumulative_tx_counts.extend(tx_counts.into_iter().map(|tx_count| {
+            total += tx_count;
+            total
+        }));
+    }
+
+    /// Returns the range of transactions of the given block.
+    ///
+    /// Returns `None` if the block is not part of the segment, or if the number of transactions of
+    /// the block is unknown.
+    pub fn tx_range_for_block(&self, num: BlockNumber) -> Option<Range<TxNumber>> {
+        if !self.block_range.contains(&num) {
+            return None
+        }
+
+        let index = (num - self.block_start()) as usize;
+        let end = *self.cumulative_tx_counts.get(index)?;
+        let start = index.checked_sub(1).map_or(0, |previous| self.cumulative_tx_counts[previous]);
+        Some(self.tx_start() + start..self.tx_start() + end)
+    }
+
+    /// Returns the segment type.
+    pub fn segment(&self) -> SnapshotSegment {
+        self.segment
     }
 
     /// Returns the first block number of the segment.
@@ -168,11 +242,38 @@ impl SegmentHeader {
         *self.tx_range.start()
     }
 
+    /// Returns the last transaction number of the segment.
+    pub fn tx_end(&self) -> TxNumber {
+        *self.tx_range.end()
+    }
+
+    /// Extends the segment up to the end of the given block and transaction ranges, e.g. after
+    /// appending them to its snapshot.
+    pub fn extend(
+        &mut self,
+        block_range: RangeInclusive<BlockNumber>,
+        tx_range: RangeInclusive<TxNumber>,
+    ) {
+        self.block_range = self.block_start()..=*block_range.end();
+        self.tx_range = self.tx_start()..=*tx_range.end();
+    }
+
     /// Returns the row offset which depends on whether the segment is block or transaction based.
     pub fn start(&self) -> u64 {
-        match self.segment {
-            SnapshotSegment::Headers => self.block_start(),
-            SnapshotSegment::Transactions | SnapshotSegment::Receipts => self.tx_start(),
+        if self.segment.is_block_based() {
+            self.block_start()
+        } else {
+            self.tx_start()
+        }
+    }
+
+    /// Returns the number of the last row which depends on whether the segment is block or
+    /// transaction based.
+    pub fn end(&self) -> u64 {
+        if self.segment.is_block_based() {
+            self.block_end()
+        } else {
+            self.tx_end()
         }
     }
 }
@@ -190,6 +291,33 @@ pub struct SegmentConfig {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_tx_range_for_block() {
+        let mut header = SegmentHeader::new(10..=14, 100..=106, SnapshotSegment::Transactions)
+            .with_block_tx_counts([2, 0, 3, 1, 1]);
+
+        assert_eq!(header.tx_range_for_block(9), None);
+        assert_eq!(header.tx_range_for_block(10), Some(100..102));
+        // empty block
+        assert_eq!(header.tx_range_for_block(11), Some(102..102));
+        assert_eq!(header.tx_range_for_block(12), Some(102..105));
+        assert_eq!(header.tx_range_for_block(13), Some(105..106));
+        assert_eq!(header.tx_range_for_block(14), Some(106..107));
+        assert_eq!(header.tx_range_for_block(15), None);
+
+        // the transaction counts of appended blocks are unknown until they are extended
+        header.extend(15..=16, 107..=110);
+        assert_eq!(header.tx_range_for_block(14), Some(106..107));
+        assert_eq!(header.tx_range_for_block(15), None);
+        header.extend_block_tx_counts([4, 0]);
+        assert_eq!(header.tx_range_for_block(15), Some(107..111));
+        assert_eq!(header.tx_range_for_block(16), Some(111..111));
+
+        // unknown transaction counts
+        let header = SegmentHeader::new(10..=14, 100..=106, SnapshotSegment::Transactions);
+        assert_eq!(header.tx_range_for_block(10), None);
+    }
+
     #[test]
     fn test_filename() {
         let test_vectors = [
@@ -27,7 +27,8 @@ pub use pooled::{PooledTransactionsElement, PooledTransactionsElementEcRecovered
 pub use sidecar::{BlobTransaction, BlobTransactionSidecar, BlobTransactionValidationError};
 pub use signature::Signature;
 pub use tx_type::{
-    TxType, EIP1559_TX_TYPE_ID, EIP2930_TX_TYPE_ID, EIP4844_TX_TYPE_ID, LEGACY_TX_TYPE_ID,
+    TxType, UnknownTxType, EIP1559_TX_TYPE_ID, EIP2930_TX_TYPE_ID, EIP4844_TX_TYPE_ID,
+    LEGACY_TX_TYPE_ID,
 };
 pub use tx_value::TxValue;
 pub use variant::TransactionSignedVariant;
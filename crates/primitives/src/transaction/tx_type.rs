@@ -57,6 +57,28 @@ impl From<TxType> for u8 {
     }
 }
 
+/// Error returned when converting a byte that is not a known transaction type identifier into a
+/// [TxType].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("unknown transaction type identifier: {0}")]
+pub struct UnknownTxType(pub u8);
+
+impl TryFrom<u8> for TxType {
+    type Error = UnknownTxType;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            LEGACY_TX_TYPE_ID => Ok(TxType::Legacy),
+            EIP2930_TX_TYPE_ID => Ok(TxType::EIP2930),
+            EIP1559_TX_TYPE_ID => Ok(TxType::EIP1559),
+            EIP4844_TX_TYPE_ID => Ok(TxType::EIP4844),
+            #[cfg(feature = "optimism")]
+            DEPOSIT_TX_TYPE_ID => Ok(TxType::DEPOSIT),
+            _ => Err(UnknownTxType(value)),
+        }
+    }
+}
+
 impl From<TxType> for U8 {
     fn from(value: TxType) -> Self {
         U8::from(u8::from(value))
@@ -116,6 +138,27 @@ impl Compact for TxType {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_txtype_u8_roundtrip() {
+        let cases = vec![
+            (TxType::Legacy, LEGACY_TX_TYPE_ID),
+            (TxType::EIP2930, EIP2930_TX_TYPE_ID),
+            (TxType::EIP1559, EIP1559_TX_TYPE_ID),
+            (TxType::EIP4844, EIP4844_TX_TYPE_ID),
+            #[cfg(feature = "optimism")]
+            (TxType::DEPOSIT, DEPOSIT_TX_TYPE_ID),
+        ];
+
+        for (tx_type, id) in cases {
+            assert_eq!(u8::from(tx_type), id);
+            assert_eq!(tx_type as u8, id);
+            assert_eq!(TxType::try_from(id), Ok(tx_type));
+        }
+
+        assert_eq!(TxType::try_from(4), Err(UnknownTxType(4)));
+        assert_eq!(TxType::try_from(u8::MAX), Err(UnknownTxType(u8::MAX)));
+    }
+
     #[test]
     fn test_txtype_to_compat() {
         let cases = vec![
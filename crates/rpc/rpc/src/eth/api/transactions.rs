@@ -1217,10 +1217,8 @@ pub(crate) fn build_transaction_receipt_with_block_receipts(
     }
 
     // get number of logs in the block
-    let mut num_logs = 0;
-    for prev_receipt in all_receipts.iter().take(meta.index as usize) {
-        num_logs += prev_receipt.logs.len();
-    }
+    let num_logs: usize =
+        all_receipts.iter().take(meta.index as usize).map(Receipt::logs_len).sum();
 
     for (tx_log_idx, log) in receipt.logs.into_iter().enumerate() {
         let rpclog = Log {
@@ -5,11 +5,15 @@ use crate::eth::{
     error::{EthApiError, EthResult, RpcInvalidTransactionError},
 };
 use derive_more::{Deref, DerefMut};
+use parking_lot::{RwLock, RwLockReadGuard};
 use reth_primitives::{constants::GWEI_TO_WEI, BlockNumberOrTag, B256, U256};
 use reth_provider::BlockReaderIdExt;
 use schnellru::{ByLength, LruMap};
 use serde::{Deserialize, Serialize};
-use std::fmt::{self, Debug, Formatter};
+use std::{
+    fmt::{self, Debug, Formatter},
+    sync::Arc,
+};
 use tokio::sync::Mutex;
 use tracing::warn;
 
@@ -49,6 +53,21 @@ pub struct GasPriceOracleConfig {
 
     /// The minimum gas price, under which the sample will be ignored
     pub ignore_price: Option<U256>,
+
+    /// Whether blocks without any sampled transaction are skipped.
+    ///
+    /// By default, an empty block contributes the last estimated price to the sample. If set,
+    /// empty blocks are ignored and older blocks are checked instead, until `blocks` populated
+    /// blocks are found or the maximum block history is reached.
+    #[serde(default)]
+    pub skip_empty_blocks: bool,
+
+    /// The minimum gas price to suggest.
+    ///
+    /// Unlike `ignore_price`, which filters the sampled prices, this clamps the final estimate,
+    /// after `max_price` is applied.
+    #[serde(default)]
+    pub min_suggested_price: U256,
 }
 
 impl Default for GasPriceOracleConfig {
@@ -61,6 +80,8 @@ impl Default for GasPriceOracleConfig {
             default: None,
             max_price: Some(DEFAULT_MAX_PRICE),
             ignore_price: Some(DEFAULT_IGNORE_PRICE),
+            skip_empty_blocks: false,
+            min_suggested_price: U256::ZERO,
         }
     }
 }
@@ -81,6 +102,20 @@ impl GasPriceOracleConfig {
             default: None,
             max_price: max_price.map(U256::from).or(Some(DEFAULT_MAX_PRICE)),
             ignore_price: ignore_price.map(U256::from).or(Some(DEFAULT_IGNORE_PRICE)),
+            skip_empty_blocks: false,
+            min_suggested_price: U256::ZERO,
+        }
+    }
+
+    /// Returns the configured percentile, capped at 100.
+    fn sanitized_percentile(&self) -> u32 {
+        self.percentile.min(100)
+    }
+
+    /// Logs a warning if the configured percentile is out of range.
+    fn warn_invalid_percentile(&self) {
+        if self.percentile > 100 {
+            warn!(prev_percentile = ?self.percentile, "Invalid configured gas price percentile, assuming 100.");
         }
     }
 }
@@ -92,10 +127,8 @@ pub struct GasPriceOracle<Provider> {
     provider: Provider,
     /// The cache for blocks
     cache: EthStateCache,
-    /// The config for the oracle
-    oracle_config: GasPriceOracleConfig,
-    /// The price under which the sample will be ignored.
-    ignore_price: Option<u128>,
+    /// The config for the oracle, shared so it can be adjusted at runtime.
+    oracle_config: Arc<RwLock<GasPriceOracleConfig>>,
     /// Stores the latest calculated price and its block hash and Cache stores the lowest effective
     /// tip values of recent blocks
     inner: Mutex<GasPriceOracleInner>,
@@ -108,31 +141,49 @@ where
     /// Creates and returns the [GasPriceOracle].
     pub fn new(
         provider: Provider,
-        mut oracle_config: GasPriceOracleConfig,
+        oracle_config: GasPriceOracleConfig,
         cache: EthStateCache,
     ) -> Self {
-        // sanitize the percentile to be less than 100
-        if oracle_config.percentile > 100 {
-            warn!(prev_percentile = ?oracle_config.percentile, "Invalid configured gas price percentile, assuming 100.");
-            oracle_config.percentile = 100;
-        }
-        let ignore_price = oracle_config.ignore_price.map(|price| price.saturating_to());
+        Self::with_shared_config(provider, Arc::new(RwLock::new(oracle_config)), cache)
+    }
+
+    /// Creates and returns the [GasPriceOracle] reading its parameters from a shared config.
+    ///
+    /// The config is consulted on every estimate, so changes made through the shared handle apply
+    /// to subsequent estimates. The size of the block values cache is fixed on creation.
+    pub fn with_shared_config(
+        provider: Provider,
+        oracle_config: Arc<RwLock<GasPriceOracleConfig>>,
+        cache: EthStateCache,
+    ) -> Self {
+        let config = oracle_config.read().clone();
+        config.warn_invalid_percentile();
 
         // this is the number of blocks that we will cache the values for
-        let cached_values = (oracle_config.blocks * 5).max(oracle_config.max_block_history as u32);
+        let cached_values = (config.blocks * 5).max(config.max_block_history as u32);
         let inner = Mutex::new(GasPriceOracleInner {
             last_price: Default::default(),
+            last_config: config,
             lowest_effective_tip_cache: EffectiveTipLruCache(LruMap::new(ByLength::new(
                 cached_values,
             ))),
         });
 
-        Self { provider, oracle_config, cache, ignore_price, inner }
+        Self { provider, oracle_config, cache, inner }
+    }
+
+    /// Returns a reference to the current configuration of the gas price oracle.
+    ///
+    /// The returned guard holds the shared config's read lock until it is dropped.
+    pub fn config(&self) -> RwLockReadGuard<'_, GasPriceOracleConfig> {
+        self.oracle_config.read()
     }
 
-    /// Returns the configuration of the gas price oracle.
-    pub fn config(&self) -> &GasPriceOracleConfig {
-        &self.oracle_config
+    /// Returns the shared configuration handle of the gas price oracle.
+    ///
+    /// Updates made through it are picked up by the next estimate.
+    pub fn shared_config(&self) -> Arc<RwLock<GasPriceOracleConfig>> {
+        self.oracle_config.clone()
     }
 
     /// Suggests a gas price estimate based on recent blocks, using the configured percentile.
@@ -142,10 +193,21 @@ where
             .sealed_header_by_number_or_tag(BlockNumberOrTag::Latest)?
             .ok_or(EthApiError::UnknownBlockNumber)?;
 
+        let config = self.config().clone();
         let mut inner = self.inner.lock().await;
 
-        // if we have stored a last price, then we check whether or not it was for the same head
-        if inner.last_price.block_hash == header.hash {
+        if inner.last_config != config {
+            config.warn_invalid_percentile();
+        }
+
+        // the cached block values were sampled with the previous ignore price
+        if inner.last_config.ignore_price != config.ignore_price {
+            inner.lowest_effective_tip_cache.clear();
+        }
+
+        // if we have stored a last price, then we check whether or not it was for the same head and
+        // the same configuration
+        if inner.last_price.block_hash == header.hash && inner.last_config == config {
             return Ok(inner.last_price.price)
         }
 
@@ -159,11 +221,12 @@ where
         let mut populated_blocks = 0;
 
         // we only check a maximum of 2 * max_block_history, or the number of blocks in the chain
-        let max_blocks = if self.oracle_config.max_block_history * 2 > header.number {
+        let max_blocks = if config.max_block_history * 2 > header.number {
             header.number
         } else {
-            self.oracle_config.max_block_history * 2
+            config.max_block_history * 2
         };
+        let ignore_price = config.ignore_price.map(|price| price.saturating_to());
 
         for _ in 0..max_blocks {
             // Check if current hash is in cache
@@ -173,7 +236,7 @@ where
                 } else {
                     // Otherwise we fetch it using get_block_values
                     let (parent_hash, block_values) = self
-                        .get_block_values(current_hash, SAMPLE_NUMBER)
+                        .get_block_values(current_hash, SAMPLE_NUMBER, ignore_price)
                         .await?
                         .ok_or(EthApiError::UnknownBlockNumber)?;
                     inner
@@ -183,14 +246,16 @@ where
                 };
 
             if block_values.is_empty() {
-                results.push(U256::from(inner.last_price.price));
+                if !config.skip_empty_blocks {
+                    results.push(U256::from(inner.last_price.price));
+                }
             } else {
                 results.extend(block_values);
                 populated_blocks += 1;
             }
 
             // break when we have enough populated blocks
-            if populated_blocks >= self.oracle_config.blocks {
+            if populated_blocks >= config.blocks {
                 break
             }
 
@@ -202,25 +267,29 @@ where
         if !results.is_empty() {
             results.sort_unstable();
             price = *results
-                .get((results.len() - 1) * self.oracle_config.percentile as usize / 100)
+                .get((results.len() - 1) * config.sanitized_percentile() as usize / 100)
                 .expect("gas price index is a percent of nonzero array length, so a value always exists; qed");
         }
 
         // constrain to the max price
-        if let Some(max_price) = self.oracle_config.max_price {
+        if let Some(max_price) = config.max_price {
             if price > max_price {
                 price = max_price;
             }
         }
 
+        // constrain to the min suggested price
+        price = price.max(config.min_suggested_price);
+
         inner.last_price = GasPriceOracleResult { block_hash: header.hash, price };
+        inner.last_config = config;
 
         Ok(price)
     }
 
-    /// Get the `limit` lowest effective tip values for the given block. If the oracle has a
-    /// configured `ignore_price` threshold, then tip values under that threshold will be ignored
-    /// before returning a result.
+    /// Get the `limit` lowest effective tip values for the given block. If an `ignore_price`
+    /// threshold is given, then tip values under that threshold will be ignored before returning a
+    /// result.
     ///
     /// If the block cannot be found, then this will return `None`.
     ///
@@ -229,6 +298,7 @@ where
         &self,
         block_hash: B256,
         limit: usize,
+        ignore_price: Option<u128>,
     ) -> EthResult<Option<(B256, Vec<U256>)>> {
         // check the cache (this will hit the disk if the block is not cached)
         let mut block = match self.cache.get_block(block_hash).await? {
@@ -247,7 +317,7 @@ where
         for tx in block.body.iter() {
             let mut effective_gas_tip = None;
             // ignore transactions with a tip under the configured threshold
-            if let Some(ignore_under) = self.ignore_price {
+            if let Some(ignore_under) = ignore_price {
                 let tip = tx.effective_tip_per_gas(base_fee_per_gas);
                 effective_gas_tip = Some(tip);
                 if tip < Some(ignore_under) {
@@ -284,6 +354,8 @@ where
 #[derive(Debug)]
 struct GasPriceOracleInner {
     last_price: GasPriceOracleResult,
+    /// The config used to calculate `last_price`
+    last_config: GasPriceOracleConfig,
     lowest_effective_tip_cache: EffectiveTipLruCache,
 }
 
@@ -317,7 +389,9 @@ impl Default for GasPriceOracleResult {
 
 #[cfg(test)]
 mod tests {
-    use reth_primitives::constants::GWEI_TO_WEI;
+    use reth_interfaces::test_utils::generators::{self, generate_keys, sign_tx_with_key_pair};
+    use reth_primitives::{constants::GWEI_TO_WEI, Block, Header, Transaction, TxLegacy};
+    use reth_provider::test_utils::MockEthProvider;
 
     use super::*;
 
@@ -331,4 +405,57 @@ mod tests {
     fn ignore_price_sanity() {
         assert_eq!(DEFAULT_IGNORE_PRICE, U256::from(2u64));
     }
+
+    #[tokio::test]
+    async fn shared_config_changes_estimate() {
+        let provider = MockEthProvider::default();
+        let block = Block::default();
+        provider.add_block(block.header.hash_slow(), block);
+
+        let cache = EthStateCache::spawn(provider.clone(), Default::default());
+        let oracle = GasPriceOracle::new(provider, Default::default(), cache);
+
+        // there are no populated blocks, so the last price is used
+        assert_eq!(oracle.suggest_tip_cap().await.unwrap(), U256::from(GWEI_TO_WEI));
+
+        let max_price = U256::from(1_000u64);
+        oracle.shared_config().write().max_price = Some(max_price);
+        assert_eq!(oracle.suggest_tip_cap().await.unwrap(), max_price);
+
+        let min_suggested_price = U256::from(2_000u64);
+        oracle.shared_config().write().min_suggested_price = min_suggested_price;
+        assert_eq!(oracle.suggest_tip_cap().await.unwrap(), min_suggested_price);
+    }
+
+    #[tokio::test]
+    async fn shared_config_ignore_price_resamples_blocks() {
+        let provider = MockEthProvider::default();
+        let genesis = Block::default();
+        let genesis_hash = genesis.header.hash_slow();
+        provider.add_block(genesis_hash, genesis);
+
+        // a populated block with tips of 10 and 100 wei
+        let key_pair = generate_keys(&mut generators::rng(), 1)[0];
+        let body = [10, 100]
+            .into_iter()
+            .map(|gas_price| {
+                let tx = Transaction::Legacy(TxLegacy { gas_price, ..Default::default() });
+                sign_tx_with_key_pair(key_pair, tx)
+            })
+            .collect();
+        let block = Block {
+            header: Header { number: 1, parent_hash: genesis_hash, ..Default::default() },
+            body,
+            ..Default::default()
+        };
+        provider.add_block(block.header.hash_slow(), block);
+
+        let cache = EthStateCache::spawn(provider.clone(), Default::default());
+        let oracle = GasPriceOracle::new(provider, Default::default(), cache);
+        assert_eq!(oracle.suggest_tip_cap().await.unwrap(), U256::from(10u64));
+
+        // the block is sampled again without the tips under the new ignore price
+        oracle.shared_config().write().ignore_price = Some(U256::from(50u64));
+        assert_eq!(oracle.suggest_tip_cap().await.unwrap(), U256::from(100u64));
+    }
 }
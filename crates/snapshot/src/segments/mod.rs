@@ -10,7 +10,8 @@ mod receipts;
 pub use receipts::Receipts;
 
 use reth_db::{
-    cursor::DbCursorRO, database::Database, table::Table, transaction::DbTx, RawKey, RawTable,
+    cursor::DbCursorRO, database::Database, table::Table, tables, transaction::DbTx, RawKey,
+    RawTable,
 };
 use reth_interfaces::provider::ProviderResult;
 use reth_nippy_jar::NippyJar;
@@ -67,11 +68,21 @@ pub(crate) fn prepare_jar<DB: Database, const COLUMNS: usize>(
     prepare_compression: impl Fn() -> ProviderResult<Rows<COLUMNS>>,
 ) -> ProviderResult<NippyJar<SegmentHeader>> {
     let tx_range = provider.transaction_range_by_block_range(block_range.clone())?;
-    let mut nippy_jar = NippyJar::new(
-        COLUMNS,
-        &directory.as_ref().join(segment.filename(&block_range, &tx_range).as_str()),
-        SegmentHeader::new(block_range, tx_range, segment),
-    );
+    let path = directory.as_ref().join(segment.filename(&block_range, &tx_range).as_str());
+
+    let mut segment_header = SegmentHeader::new(block_range.clone(), tx_range, segment);
+    if !segment.is_block_based() {
+        // Allows looking up the transactions of a block without the database
+        let tx_counts = provider
+            .tx_ref()
+            .cursor_read::<tables::BlockBodyIndices>()?
+            .walk_range(block_range)?
+            .map(|row| row.map(|(_, indices)| indices.tx_count()))
+            .collect::<Result<Vec<_>, _>>()?;
+        segment_header = segment_header.with_block_tx_counts(tx_counts);
+    }
+
+    let mut nippy_jar = NippyJar::new(COLUMNS, &path, segment_header);
 
     nippy_jar = match segment_config.compression {
         Compression::Lz4 => nippy_jar.with_lz4(),
@@ -98,3 +109,59 @@ pub(crate) fn prepare_jar<DB: Database, const COLUMNS: usize>(
 
     Ok(nippy_jar)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_interfaces::test_utils::{generators, generators::random_block_range};
+    use reth_primitives::B256;
+    use reth_provider::BlockReader;
+    use reth_stages::test_utils::TestStageDB;
+
+    #[test]
+    fn prepare_jar_block_tx_counts() {
+        let db = TestStageDB::default();
+        let snapshots_dir = tempfile::TempDir::new().unwrap();
+        let mut rng = generators::rng();
+
+        // Blocks with varying transaction counts, including empty ones
+        let blocks = random_block_range(&mut rng, 0..=9, B256::ZERO, 0..4);
+        db.insert_blocks(blocks.iter(), None).expect("insert blocks");
+
+        let provider = db.factory.provider().unwrap();
+        for segment in [SnapshotSegment::Transactions, SnapshotSegment::Receipts] {
+            let jar = prepare_jar::<_, 1>(
+                &provider,
+                snapshots_dir.path(),
+                segment,
+                segment.config(),
+                2..=7,
+                10,
+                || unreachable!("no dictionaries"),
+            )
+            .unwrap();
+
+            let header = jar.user_header();
+            for block in blocks.iter() {
+                let expected = (2..=7)
+                    .contains(&block.number)
+                    .then(|| provider.block_body_indices(block.number).unwrap().unwrap())
+                    .map(|indices| indices.tx_num_range());
+                assert_eq!(header.tx_range_for_block(block.number), expected);
+            }
+        }
+
+        // Block based segments don't need them
+        let jar = prepare_jar::<_, 1>(
+            &provider,
+            snapshots_dir.path(),
+            SnapshotSegment::Headers,
+            SnapshotSegment::Headers.config(),
+            2..=7,
+            10,
+            || unreachable!("no dictionaries"),
+        )
+        .unwrap();
+        assert_eq!(jar.user_header().tx_range_for_block(2), None);
+    }
+}
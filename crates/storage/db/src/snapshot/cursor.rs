@@ -2,7 +2,7 @@ use super::mask::{ColumnSelectorOne, ColumnSelectorThree, ColumnSelectorTwo};
 use crate::table::Decompress;
 use derive_more::{Deref, DerefMut};
 use reth_interfaces::provider::ProviderResult;
-use reth_nippy_jar::{MmapHandle, NippyJar, NippyJarCursor};
+use reth_nippy_jar::{compression::Decompressor, MmapHandle, NippyJar, NippyJarCursor};
 use reth_primitives::{snapshot::SegmentHeader, B256};
 
 /// Cursor of a snapshot segment.
@@ -15,10 +15,21 @@ impl<'a> SnapshotCursor<'a> {
         Ok(Self(NippyJarCursor::with_handle(jar, mmap_handle)?))
     }
 
-    /// Returns the current `BlockNumber` or `TxNumber` of the cursor depending on the kind of
-    /// snapshot segment.
+    /// Returns a new [`SnapshotCursor`] that reuses the given decompressors. See
+    /// [`NippyJarCursor::with_decompressors`].
+    pub fn with_decompressors(
+        jar: &'a NippyJar<SegmentHeader>,
+        mmap_handle: MmapHandle,
+        decompressors: Vec<Decompressor<'a>>,
+    ) -> ProviderResult<Self> {
+        Ok(Self(NippyJarCursor::with_decompressors(jar, mmap_handle, decompressors)?))
+    }
+
+    /// Returns the `BlockNumber` or `TxNumber` of the last row read by the cursor depending on the
+    /// kind of snapshot segment.
     pub fn number(&self) -> u64 {
-        self.row_index() + self.jar().user_header().start()
+        // The cursor moves to the next row after every read.
+        self.row_index().saturating_sub(1) + self.jar().user_header().start()
     }
 
     /// Gets a row of values.
@@ -41,6 +52,16 @@ impl<'a> SnapshotCursor<'a> {
         Ok(row)
     }
 
+    /// Gets the raw value of one column from a row by its block/tx number, without decompressing
+    /// it.
+    pub fn get_one_raw<M: ColumnSelectorOne>(&self, num: u64) -> Option<&'_ [u8]> {
+        let offset = self.jar().user_header().start();
+        if offset > num {
+            return None
+        }
+        self.raw_column_by_number((num - offset) as usize, M::MASK.trailing_zeros() as usize)
+    }
+
     /// Gets one column value from a row.
     pub fn get_one<M: ColumnSelectorOne>(
         &mut self,
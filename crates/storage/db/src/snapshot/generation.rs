@@ -1,12 +1,14 @@
 use crate::{
     abstraction::cursor::DbCursorRO,
     table::{Key, Table},
+    tables,
     transaction::DbTx,
     RawKey, RawTable,
 };
 
 use reth_interfaces::provider::ProviderResult;
 use reth_nippy_jar::{ColumnResult, NippyJar, PHFKey};
+use reth_primitives::{snapshot::SegmentHeader, BlockNumber, TxNumber};
 use reth_tracing::tracing::*;
 use serde::{Deserialize, Serialize};
 use std::{error::Error as StdError, ops::RangeInclusive};
@@ -95,6 +97,85 @@ macro_rules! generate_snapshot_func {
 
                     Ok(())
                 }
+
+                /// Appends rows from specified tables to an existing snapshot, extends the ranges of its [`SegmentHeader`] and renames its files after them. Each table's `Value` iterator represents a column.
+                ///
+                /// **Ensure the range contains the same number of rows.**
+                ///
+                /// * `tx`: Database transaction.
+                /// * `block_range`: Blocks to append, right after the last one of the snapshot.
+                /// * `tx_range`: Transactions of the blocks to append. Rows are taken from it or from `block_range` depending on whether the segment is block based.
+                /// * `additional`: Additional columns which can't be straight straightforwardly walked on.
+                /// * `keys`: Iterator of keys (eg. `TxHash` or `BlockHash`) of **all** rows of the snapshot, including the existing ones, ordered by column insertion.
+                /// * `row_count`: Total rows to append to `NippyJar`. Must match row count of the appended range.
+                /// * `nippy_jar`: Loaded snapshot object to append to.
+                #[allow(non_snake_case)]
+                pub fn [<append_snapshot$(_ $tbl)+>]<
+                    $($tbl: Table<Key=u64>,)+
+                >
+                (
+                    tx: &impl DbTx,
+                    block_range: RangeInclusive<BlockNumber>,
+                    tx_range: RangeInclusive<TxNumber>,
+                    additional: Option<Vec<Box<dyn Iterator<Item = Result<Vec<u8>, Box<dyn StdError + Send + Sync>>>>>>,
+                    keys: Option<impl Iterator<Item = ColumnResult<impl PHFKey>>>,
+                    row_count: usize,
+                    nippy_jar: &mut NippyJar<SegmentHeader>
+                ) -> ProviderResult<()>
+                {
+                    let additional = additional.unwrap_or_default();
+                    let segment = nippy_jar.user_header().segment();
+                    let range = if segment.is_block_based() { block_range.clone() } else { tx_range.clone() };
+                    debug!(target: "reth::snapshot", ?segment, ?range, "Appending to snapshot {:?} and {} more columns.", vec![$($tbl::NAME,)+], additional.len());
+
+                    let mut user_header = nippy_jar.user_header().clone();
+                    // Transaction counts are only kept if the ones of the existing blocks are known
+                    let has_tx_counts = user_header.tx_range_for_block(user_header.block_end()).is_some();
+                    user_header.extend(block_range.clone(), tx_range);
+                    if has_tx_counts {
+                        let tx_counts = tx
+                            .cursor_read::<tables::BlockBodyIndices>()?
+                            .walk_range(block_range)?
+                            .map(|row| row.map(|(_, indices)| indices.tx_count()))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        user_header.extend_block_tx_counts(tx_counts);
+                    }
+
+                    let range: RangeInclusive<RawKey<u64>> = RawKey::new(*range.start())..=RawKey::new(*range.end());
+
+                    // Creates the cursors for the columns
+                    $(
+                        let mut [< $tbl _cursor>] = tx.cursor_read::<RawTable<$tbl>>()?;
+                        let [< $tbl _iter>] = [< $tbl _cursor>]
+                            .walk_range(range.clone())?
+                            .into_iter()
+                            .map(|row|
+                                row
+                                    .map(|(_key, val)| val.into_value())
+                                    .map_err(|e| Box::new(e) as Box<dyn StdError + Send + Sync>)
+                            );
+
+                    )+
+
+                    let col_iterators: Vec<Box<dyn Iterator<Item = Result<Vec<u8>,_>>>> = vec![
+                        $(Box::new([< $tbl _iter>]),)+
+                    ];
+
+                    nippy_jar.append(col_iterators.into_iter().chain(additional).collect(), row_count as u64, keys, user_header)?;
+
+                    // Name the files after the extended ranges
+                    let user_header = nippy_jar.user_header();
+                    let filename = segment.filename(
+                        &(user_header.block_start()..=user_header.block_end()),
+                        &(user_header.tx_start()..=user_header.tx_end()),
+                    );
+                    let path = nippy_jar.data_path().with_file_name(filename);
+                    nippy_jar.rename(&path)?;
+
+                    debug!(target: "reth::snapshot", jar=?nippy_jar, "Snapshot file appended.");
+
+                    Ok(())
+                }
             }
         )+
     };
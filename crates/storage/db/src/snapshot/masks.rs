@@ -3,7 +3,7 @@ use crate::{
     add_snapshot_mask,
     snapshot::mask::{ColumnSelectorOne, ColumnSelectorTwo, HeaderMask},
     table::Table,
-    CanonicalHeaders, HeaderTD, Receipts, Transactions,
+    CanonicalHeaders, HeaderTD, Receipts, Transactions, TxSenders,
 };
 use reth_primitives::{BlockHash, Header};
 
@@ -13,6 +13,7 @@ add_snapshot_mask!(HeaderMask, Header, 0b001);
 add_snapshot_mask!(HeaderMask, <HeaderTD as Table>::Value, 0b010);
 add_snapshot_mask!(HeaderMask, BlockHash, 0b100);
 
+add_snapshot_mask!(HeaderMask, Header, <HeaderTD as Table>::Value, 0b011);
 add_snapshot_mask!(HeaderMask, Header, BlockHash, 0b101);
 add_snapshot_mask!(
     HeaderMask,
@@ -25,4 +26,12 @@ add_snapshot_mask!(
 add_snapshot_mask!(ReceiptMask, <Receipts as Table>::Value, 0b1);
 
 // TRANSACTION MASKS
-add_snapshot_mask!(TransactionMask, <Transactions as Table>::Value, 0b1);
+add_snapshot_mask!(TransactionMask, <Transactions as Table>::Value, 0b01);
+add_snapshot_mask!(TransactionMask, <TxSenders as Table>::Value, 0b10);
+
+add_snapshot_mask!(
+    TransactionMask,
+    <Transactions as Table>::Value,
+    <TxSenders as Table>::Value,
+    0b11
+);
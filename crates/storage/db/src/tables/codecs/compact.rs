@@ -34,7 +34,6 @@ impl_compression_for_compact!(
     Header,
     Account,
     Log,
-    Receipt,
     TxType,
     StorageEntry,
     Nibbles,
@@ -83,6 +82,27 @@ macro_rules! impl_compression_fixed_compact {
 
 impl_compression_fixed_compact!(B256, Address);
 
+impl Compress for Receipt {
+    type Compressed = Vec<u8>;
+
+    fn compress_to_buf<B: bytes::BufMut + AsMut<[u8]>>(self, buf: &mut B) {
+        let _ = Compact::to_compact(self, buf);
+    }
+}
+
+impl Decompress for Receipt {
+    fn decompress<B: AsRef<[u8]>>(value: B) -> Result<Receipt, crate::DatabaseError> {
+        let value = value.as_ref();
+        match Receipt::try_from_compact(value, value.len()) {
+            Ok((obj, _)) => Ok(obj),
+            Err(ReceiptError::UnsupportedCodecVersion(version)) => {
+                Err(crate::DatabaseError::UnsupportedVersion(version))
+            }
+            Err(_) => Err(crate::DatabaseError::Decode),
+        }
+    }
+}
+
 /// Adds wrapper structs for some primitive types so they can use StructFlags from Compact, when
 /// used as pure table values.
 macro_rules! add_wrapper_struct {
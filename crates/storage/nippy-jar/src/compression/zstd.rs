@@ -2,7 +2,6 @@ use crate::{compression::Compression, NippyJarError};
 use derive_more::Deref;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::{
-    fs::File,
     io::{Read, Write},
     sync::Arc,
 };
@@ -56,6 +55,15 @@ impl Zstd {
         self
     }
 
+    /// Returns `true` if there's a loaded dictionary for every column, which is required to
+    /// decompress data when using dictionaries.
+    pub(crate) fn has_loaded_dictionaries(&self) -> bool {
+        self.dictionaries.as_ref().map_or(false, |dictionaries| {
+            dictionaries.len() == self.columns &&
+                dictionaries.iter().all(|dictionary| dictionary.loaded().is_some())
+        })
+    }
+
     /// Creates a list of [`Decompressor`] if using dictionaries.
     pub fn decompressors(&self) -> Result<Vec<Decompressor<'_>>, NippyJarError> {
         if let Some(dictionaries) = &self.dictionaries {
@@ -89,7 +97,7 @@ impl Zstd {
     pub fn compress_with_dictionary(
         column_value: &[u8],
         buffer: &mut Vec<u8>,
-        handle: &mut File,
+        handle: &mut impl Write,
         compressor: Option<&mut Compressor<'_>>,
     ) -> Result<(), NippyJarError> {
         if let Some(compressor) = compressor {
@@ -8,7 +8,6 @@ use sucds::int_vectors::Access;
 use zstd::bulk::Decompressor;
 
 /// Simple cursor implementation to retrieve data from [`NippyJar`].
-#[derive(Clone)]
 pub struct NippyJarCursor<'a, H = ()> {
     /// [`NippyJar`] which holds most of the required configuration to read from the file.
     jar: &'a NippyJar<H>,
@@ -18,6 +17,22 @@ pub struct NippyJarCursor<'a, H = ()> {
     internal_buffer: Vec<u8>,
     /// Cursor row position.
     row: u64,
+    /// Decompressors reused across reads, one per column, if the jar uses zstd dictionaries.
+    /// Otherwise, a decompressor is created on every read.
+    decompressors: Vec<Decompressor<'a>>,
+}
+
+impl<'a, H> Clone for NippyJarCursor<'a, H> {
+    /// Clones the cursor, without its decompressors.
+    fn clone(&self) -> Self {
+        Self {
+            jar: self.jar,
+            mmap_handle: self.mmap_handle.clone(),
+            internal_buffer: self.internal_buffer.clone(),
+            row: self.row,
+            decompressors: Vec::new(),
+        }
+    }
 }
 
 impl<'a, H: std::fmt::Debug> std::fmt::Debug for NippyJarCursor<'a, H>
@@ -34,6 +49,7 @@ where
     H: Send + Sync + Serialize + for<'b> Deserialize<'b> + std::fmt::Debug + 'static,
 {
     pub fn new(jar: &'a NippyJar<H>) -> Result<Self, NippyJarError> {
+        Self::ensure_decompressible(jar)?;
         let max_row_size = jar.max_row_size;
         Ok(NippyJarCursor {
             jar,
@@ -41,6 +57,7 @@ where
             // Makes sure that we have enough buffer capacity to decompress any row of data.
             internal_buffer: Vec::with_capacity(max_row_size),
             row: 0,
+            decompressors: Vec::new(),
         })
     }
 
@@ -48,6 +65,23 @@ where
         jar: &'a NippyJar<H>,
         mmap_handle: MmapHandle,
     ) -> Result<Self, NippyJarError> {
+        Self::with_decompressors(jar, mmap_handle, Vec::new())
+    }
+
+    /// Creates a cursor that reuses the given decompressors, e.g. taken from a pool built with
+    /// [`NippyJar::build_decompressors`], instead of creating one on every read.
+    ///
+    /// `decompressors` must either be empty or have one decompressor per column.
+    pub fn with_decompressors(
+        jar: &'a NippyJar<H>,
+        mmap_handle: MmapHandle,
+        decompressors: Vec<Decompressor<'a>>,
+    ) -> Result<Self, NippyJarError> {
+        Self::ensure_decompressible(jar)?;
+        if !decompressors.is_empty() && decompressors.len() != jar.columns() {
+            return Err(NippyJarError::ColumnLenMismatch(jar.columns(), decompressors.len()))
+        }
+
         let max_row_size = jar.max_row_size;
         Ok(NippyJarCursor {
             jar,
@@ -55,9 +89,26 @@ where
             // Makes sure that we have enough buffer capacity to decompress any row of data.
             internal_buffer: Vec::with_capacity(max_row_size),
             row: 0,
+            decompressors,
         })
     }
 
+    /// Returns the decompressors of the cursor, e.g. to give them back to a pool.
+    pub fn into_decompressors(self) -> Vec<Decompressor<'a>> {
+        self.decompressors
+    }
+
+    /// Returns an error if the data of the jar can't be decompressed, e.g. if it was compressed
+    /// with dictionaries that are not loaded.
+    fn ensure_decompressible(jar: &NippyJar<H>) -> Result<(), NippyJarError> {
+        match jar.compressor() {
+            Some(Compressors::Zstd(z)) if z.use_dict && !z.has_loaded_dictionaries() => {
+                Err(NippyJarError::DictionaryNotLoaded)
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Returns a reference to the related [`NippyJar`]
     pub fn jar(&self) -> &NippyJar<H> {
         self.jar
@@ -68,9 +119,14 @@ where
         self.row
     }
 
-    /// Resets cursor to the beginning.
+    /// Resets cursor to the beginning and discards the data decompressed by previous reads.
+    ///
+    /// Every lookup by key or number positions the cursor itself, but a cursor that is reused
+    /// across unrelated queries should be reset before iterating it with
+    /// [`NippyJarCursor::next_row`].
     pub fn reset(&mut self) {
         self.row = 0;
+        self.internal_buffer.clear();
     }
 
     /// Returns a row, searching it by a key used during [`NippyJar::prepare_index`].
@@ -211,35 +267,59 @@ where
         ))
     }
 
-    /// Takes the column index and reads the range value for the corresponding column.
-    fn read_value(
-        &mut self,
-        column: usize,
-        row: &mut Vec<ValueRange>,
-    ) -> Result<(), NippyJarError> {
+    /// Returns the raw value of a column from a row by its number, without decompressing it.
+    ///
+    /// Does not move the cursor. Returns `None` if the row or the column do not exist.
+    pub fn raw_column_by_number(&self, row: usize, column: usize) -> Option<&[u8]> {
+        if column >= self.jar.columns || row * self.jar.columns >= self.jar.offsets.len() {
+            return None
+        }
+
+        Some(&self.mmap_handle[self.column_offset_range(row, column)])
+    }
+
+    /// Returns the range of the (possibly compressed) column value within the data file.
+    fn column_offset_range(&self, row: usize, column: usize) -> Range<usize> {
         // Find out the offset of the column value
-        let offset_pos = self.row as usize * self.jar.columns + column;
+        let offset_pos = row * self.jar.columns + column;
         let value_offset = self.jar.offsets.select(offset_pos).expect("should exist");
 
-        let column_offset_range = if self.jar.offsets.len() == (offset_pos + 1) {
+        if self.jar.offsets.len() == (offset_pos + 1) {
             // It's the last column of the last row
             value_offset..self.mmap_handle.len()
         } else {
             let next_value_offset = self.jar.offsets.select(offset_pos + 1).expect("should exist");
             value_offset..next_value_offset
-        };
+        }
+    }
+
+    /// Takes the column index and reads the range value for the corresponding column.
+    fn read_value(
+        &mut self,
+        column: usize,
+        row: &mut Vec<ValueRange>,
+    ) -> Result<(), NippyJarError> {
+        let column_offset_range = self.column_offset_range(self.row as usize, column);
 
         if let Some(compression) = self.jar.compressor() {
             let from = self.internal_buffer.len();
             match compression {
+                Compressors::Zstd(_) if !self.decompressors.is_empty() => {
+                    Zstd::decompress_with_dictionary(
+                        &self.mmap_handle[column_offset_range],
+                        &mut self.internal_buffer,
+                        &mut self.decompressors[column],
+                    )?;
+                }
                 Compressors::Zstd(z) if z.use_dict => {
-                    // If we are here, then for sure we have the necessary dictionaries and they're
-                    // loaded (happens during deserialization). Otherwise, there's an issue
-                    // somewhere else and we can't recover here anyway.
-                    let dictionaries = z.dictionaries.as_ref().expect("dictionaries to exist")
-                        [column]
-                        .loaded()
-                        .expect("dictionary to be loaded");
+                    // Dictionaries are loaded during deserialization, and checked when creating
+                    // the cursor.
+                    let dictionaries = z
+                        .dictionaries
+                        .as_ref()
+                        .and_then(|dictionaries| dictionaries.get(column))
+                        .and_then(|dictionary| dictionary.loaded())
+                        .ok_or(NippyJarError::DictionaryNotLoaded)?;
                     let mut decompressor = Decompressor::with_prepared_dictionary(dictionaries)?;
                     Zstd::decompress_with_dictionary(
                         &self.mmap_handle[column_offset_range],
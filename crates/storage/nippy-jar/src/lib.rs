@@ -11,16 +11,20 @@
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 
 use memmap2::Mmap;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::{
     clone::Clone,
     error::Error as StdError,
     fs::File,
-    io::{Seek, Write},
+    io::{self, Seek, Write},
     marker::Sync,
-    ops::Deref,
+    ops::{Deref, Range},
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Arc,
+    },
 };
 use sucds::{
     int_vectors::PrefixSummedEliasFano,
@@ -77,7 +81,8 @@ pub type ColumnResult<T> = Result<T, Box<dyn StdError + Send + Sync>>;
 /// stored.
 ///
 /// Ultimately, the `freeze` function yields two files: a data file containing both the data and its
-/// configuration, and an index file that houses the offsets and offsets_index.
+/// configuration, and an index file that houses the offsets, offsets_index and the checksum of the
+/// data.
 #[derive(Serialize, Deserialize)]
 #[cfg_attr(test, derive(PartialEq))]
 pub struct NippyJar<H = ()> {
@@ -104,6 +109,10 @@ pub struct NippyJar<H = ()> {
     /// resizing of the output buffer.
     #[serde(skip)]
     max_row_size: usize,
+    /// CRC32 checksum of the rows, as stored in the data file, if known. See
+    /// [`NippyJar::checksum`].
+    #[serde(skip)]
+    checksum: Option<u32>,
     /// Data path for file. Index file will be `{path}.idx`
     #[serde(skip)]
     path: Option<PathBuf>,
@@ -124,6 +133,7 @@ impl<H: std::fmt::Debug> std::fmt::Debug for NippyJar<H> {
             .field("offsets (size in bytes)", &self.offsets.size_in_bytes())
             .field("path", &self.path)
             .field("max_row_size", &self.max_row_size)
+            .field("checksum", &self.checksum)
             .finish_non_exhaustive()
     }
 }
@@ -156,6 +166,7 @@ where
             user_header,
             columns,
             max_row_size: 0,
+            checksum: None,
             compressor: None,
             filter: None,
             phf: None,
@@ -201,6 +212,19 @@ where
         &self.user_header
     }
 
+    /// Gets a mutable reference to the user header.
+    ///
+    /// Changes are only persisted to file on the next [`NippyJar::freeze`] or
+    /// [`NippyJar::append`].
+    pub fn user_header_mut(&mut self) -> &mut H {
+        &mut self.user_header
+    }
+
+    /// Gets the number of data columns in the jar.
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
     /// Gets a reference to `self.offsets`.
     pub fn offsets_size(&self) -> usize {
         self.offsets.size_in_bytes()
@@ -221,6 +245,22 @@ where
         self.compressor.as_ref()
     }
 
+    /// Builds `n` sets of decompressors, one per column, to be shared by concurrent readers.
+    ///
+    /// Each set can be given to a single [`NippyJarCursor::with_decompressors`] at a time. Sets are
+    /// empty if the jar doesn't use zstd dictionaries, since decompressing then needs no setup.
+    pub fn build_decompressors(
+        &self,
+        n: usize,
+    ) -> Result<Vec<Vec<compression::Decompressor<'_>>>, NippyJarError> {
+        match self.compressor() {
+            Some(Compressors::Zstd(zstd)) if zstd.use_dict => {
+                (0..n).map(|_| zstd.decompressors()).collect()
+            }
+            _ => Ok((0..n).map(|_| Vec::new()).collect()),
+        }
+    }
+
     /// Gets a mutable reference to the compressor.
     pub fn compressor_mut(&mut self) -> Option<&mut Compressors> {
         self.compressor.as_mut()
@@ -246,7 +286,11 @@ where
         let mut offsets_reader = mmap.as_ref();
         obj.offsets = EliasFano::deserialize_from(&mut offsets_reader)?;
         obj.offsets_index = PrefixSummedEliasFano::deserialize_from(&mut offsets_reader)?;
-        obj.max_row_size = bincode::deserialize_from(offsets_reader)?;
+        obj.max_row_size = bincode::deserialize_from(&mut offsets_reader)?;
+        // Index files written before checksums were recorded end after the maximum row size
+        if !offsets_reader.is_empty() {
+            obj.checksum = Some(bincode::deserialize_from(offsets_reader)?);
+        }
 
         Ok(obj)
     }
@@ -265,11 +309,60 @@ where
             .join(format!("{}.idx", data_path.file_name().expect("exists").to_string_lossy()))
     }
 
+    /// Moves the data and index files of the jar to a new data `path`.
+    pub fn rename(&mut self, path: &Path) -> Result<(), NippyJarError> {
+        let (data_path, index_path) = (self.data_path(), self.index_path());
+        self.path = Some(path.to_path_buf());
+
+        std::fs::rename(data_path, self.data_path())?;
+        std::fs::rename(index_path, self.index_path())?;
+        Ok(())
+    }
+
     /// Returns a [`MmapHandle`] of the data file
     pub fn open_data(&self) -> Result<MmapHandle, NippyJarError> {
         MmapHandle::new(self.data_path())
     }
 
+    /// Returns the CRC32 checksum of the rows as stored, possibly compressed, in the data file.
+    ///
+    /// It's computed while the rows are written and recorded in the index file, so it can be
+    /// checked against [`NippyJar::compute_checksum`] to detect a corrupted data file. Jars
+    /// written before checksums were recorded have none.
+    pub fn checksum(&self) -> Option<u32> {
+        self.checksum
+    }
+
+    /// Computes the CRC32 checksum of the rows as currently stored in the data file, see
+    /// [`NippyJar::checksum`].
+    pub fn compute_checksum(&self) -> Result<u32, NippyJarError> {
+        let data = self.open_data()?;
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(&data[self.data_start(&data)..]);
+        Ok(hasher.finalize())
+    }
+
+    /// Returns the offset of the first row in the given data file of the jar, right after its
+    /// configuration.
+    fn data_start(&self, data: &MmapHandle) -> usize {
+        self.offsets.select(0).unwrap_or(data.len())
+    }
+
+    /// Loads the pages of the given data file of the jar that hold the rows in `rows` into memory,
+    /// see [`MmapHandle::warm`]. Rows past the last one are ignored.
+    pub fn warm_rows(&self, data: &MmapHandle, rows: Range<usize>) {
+        let len = self.offsets.len();
+        let start = rows.start.saturating_mul(self.columns);
+        let end = rows.end.saturating_mul(self.columns).min(len);
+        if start >= end {
+            return
+        }
+
+        let start = self.offsets.select(start).expect("exists");
+        let end = if end == len { data.len() } else { self.offsets.select(end).expect("exists") };
+        data.warm(start..end);
+    }
+
     /// If required, prepares any compression algorithm to an early pass of the data.
     pub fn prepare_compression(
         &mut self,
@@ -293,7 +386,19 @@ where
         values: impl IntoIterator<Item = ColumnResult<T>>,
         row_count: usize,
     ) -> Result<(), NippyJarError> {
-        debug!(target: "nippy-jar", ?row_count, "Preparing index.");
+        self.build_index(values, row_count, 0)
+    }
+
+    /// Builds the perfect hashing function and the offsets index from `values`. Only the values
+    /// from row `filter_from` onwards are added to the filter, since the previous ones might have
+    /// been added already.
+    fn build_index<T: PHFKey>(
+        &mut self,
+        values: impl IntoIterator<Item = ColumnResult<T>>,
+        row_count: usize,
+        filter_from: usize,
+    ) -> Result<(), NippyJarError> {
+        debug!(target: "nippy-jar", ?row_count, ?filter_from, "Preparing index.");
 
         let values = values.into_iter().collect::<Result<Vec<_>, _>>()?;
 
@@ -315,7 +420,7 @@ where
             debug!(target: "nippy-jar", ?row_count, "Creating filter and offsets_index.");
 
             for (row_num, v) in values.into_iter().enumerate() {
-                if let Some(filter) = self.filter.as_mut() {
+                if let Some(filter) = self.filter.as_mut().filter(|_| row_num >= filter_from) {
                     filter.add(v.as_ref())?;
                 }
 
@@ -341,6 +446,149 @@ where
         let mut file = self.freeze_check(&columns)?;
         self.freeze_config(&mut file)?;
 
+        // Write all rows while taking all row start offsets
+        let mut offsets = Vec::with_capacity(total_rows as usize * self.columns);
+        let mut hasher = crc32fast::Hasher::new();
+        let max_row_size =
+            self.write_rows(&mut file, &mut hasher, columns, total_rows, &mut offsets)?;
+        self.max_row_size = self.max_row_size.max(max_row_size);
+        self.checksum = Some(hasher.finalize());
+
+        // Write offsets and offset index to file
+        let index_path = self.index_path();
+        self.freeze_offsets(offsets, &index_path)?;
+
+        debug!(target: "nippy-jar", jar=?self, "Finished.");
+
+        Ok(())
+    }
+
+    /// Appends `total_rows` rows to an already frozen jar and replaces its user header with
+    /// `user_header`, e.g. to extend the range it describes, rewriting both its data and index
+    /// files.
+    ///
+    /// Existing values are copied over as they are, without decompressing them. If the jar has a
+    /// filter or a perfect hashing function, `keys` must contain the keys of **all** rows,
+    /// including the existing ones, since the function can only be built over the whole set. Only
+    /// the keys of the appended rows are added to the filter.
+    ///
+    /// The jar and its files are left untouched if appending fails.
+    ///
+    /// Jars compressed with zstd dictionaries are not supported, since loaded dictionaries can't be
+    /// used for compression.
+    pub fn append<T: PHFKey>(
+        &mut self,
+        columns: Vec<impl IntoIterator<Item = ColumnResult<Vec<u8>>>>,
+        total_rows: u64,
+        keys: Option<impl IntoIterator<Item = ColumnResult<T>>>,
+        user_header: H,
+    ) -> Result<(), NippyJarError> {
+        if columns.len() != self.columns {
+            return Err(NippyJarError::ColumnLenMismatch(self.columns, columns.len()))
+        }
+
+        if let Some(Compressors::Zstd(zstd)) = &self.compressor {
+            if zstd.use_dict {
+                return Err(NippyJarError::CompressorNotAllowed)
+            }
+        }
+
+        // The appended jar is built on a copy of the configuration, as it would be loaded from
+        // file, and only replaces this one once its files are written.
+        let mut jar: Self = bincode::deserialize(&bincode::serialize(&*self)?)?;
+        jar.user_header = user_header;
+        jar.max_row_size = self.max_row_size;
+        jar.path = self.path.clone();
+
+        let existing_rows = self.offsets.len() / self.columns;
+        match keys {
+            Some(keys) => {
+                jar.build_index(keys, existing_rows + total_rows as usize, existing_rows)?
+            }
+            None if self.filter.is_some() || self.phf.is_some() => {
+                return Err(NippyJarError::PHFMissingKeys)
+            }
+            None => jar.offsets_index = self.offsets_index.clone(),
+        }
+
+        if let Err(err) = self.rewrite(&mut jar, columns, total_rows) {
+            // Leftovers of the failed rewrite are of no use.
+            let _ = std::fs::remove_file(tmp_path(&self.data_path()));
+            let _ = std::fs::remove_file(tmp_path(&self.index_path()));
+            return Err(err)
+        }
+        *self = jar;
+
+        debug!(target: "nippy-jar", jar=?self, "Finished appending.");
+
+        Ok(())
+    }
+
+    /// Writes the configuration of `jar`, the appended copy of this jar, followed by the existing
+    /// values of this jar and `total_rows` new rows, to temporary data and index files which then
+    /// replace the current ones.
+    fn rewrite(
+        &self,
+        jar: &mut Self,
+        columns: Vec<impl IntoIterator<Item = ColumnResult<Vec<u8>>>>,
+        total_rows: u64,
+    ) -> Result<(), NippyJarError> {
+        let existing_rows = self.offsets.len() / self.columns;
+
+        let data = self.open_data()?;
+        let data_start = self.data_start(&data);
+        let (data_path, index_path) = (self.data_path(), self.index_path());
+        let (tmp_data_path, tmp_index_path) = (tmp_path(&data_path), tmp_path(&index_path));
+
+        debug!(target: "nippy-jar", path=?tmp_data_path, ?existing_rows, ?total_rows, "Rewriting data file.");
+
+        // Write the new configuration to a temporary file, followed by the existing values.
+        let mut file = File::create(&tmp_data_path)?;
+        jar.freeze_config(&mut file)?;
+        let new_data_start = file.stream_position()? as usize;
+        file.write_all(&data[data_start..])?;
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(&data[data_start..]);
+
+        // Existing offsets are shifted by the change in size of the configuration.
+        let mut offsets =
+            Vec::with_capacity(self.offsets.len() + total_rows as usize * self.columns);
+        for index in 0..self.offsets.len() {
+            let offset = self.offsets.select(index).expect("exists");
+            offsets.push(offset - data_start + new_data_start);
+        }
+
+        let max_row_size =
+            jar.write_rows(&mut file, &mut hasher, columns, total_rows, &mut offsets)?;
+        jar.max_row_size = jar.max_row_size.max(max_row_size);
+        jar.checksum = Some(hasher.finalize());
+        file.sync_all()?;
+        drop(data);
+
+        jar.freeze_offsets(offsets, &tmp_index_path)?;
+
+        // Both files are complete, so the data file can be replaced before its index.
+        std::fs::rename(tmp_data_path, data_path)?;
+        std::fs::rename(tmp_index_path, index_path)?;
+        Ok(())
+    }
+
+    /// Writes `total_rows` rows to `file`, pushing the offset of every written column value to
+    /// `offsets` and updating `hasher` with the written bytes.
+    ///
+    /// Returns the maximum uncompressed row size of the written rows.
+    fn write_rows(
+        &self,
+        file: &mut File,
+        hasher: &mut crc32fast::Hasher,
+        columns: Vec<impl IntoIterator<Item = ColumnResult<Vec<u8>>>>,
+        total_rows: u64,
+        offsets: &mut Vec<usize>,
+    ) -> Result<usize, NippyJarError> {
+        if total_rows == 0 {
+            return Ok(0)
+        }
+
         // Special case for zstd that might use custom dictionaries/compressors per column
         // If any other compression algorithm is added and uses a similar flow, then revisit
         // implementation
@@ -353,14 +601,14 @@ where
         // dict)
         let mut tmp_buf = Vec::with_capacity(1_000_000);
 
-        // Write all rows while taking all row start offsets
         let mut row_number = 0u64;
-        let mut offsets = Vec::with_capacity(total_rows as usize * self.columns);
+        let mut max_row_size = 0;
         let mut column_iterators =
             columns.into_iter().map(|v| v.into_iter()).collect::<Vec<_>>().into_iter();
 
         debug!(target: "nippy-jar", compressor=?self.compressor, "Writing rows.");
 
+        let mut file = ChecksumWriter { file, hasher };
         loop {
             let mut iterators = Vec::with_capacity(self.columns);
 
@@ -368,7 +616,7 @@ where
             // TODO: iter_mut if we remove the IntoIterator interface.
             let mut uncompressed_row_size = 0;
             for (column_number, mut column_iter) in column_iterators.enumerate() {
-                offsets.push(file.stream_position()? as usize);
+                offsets.push(file.file.stream_position()? as usize);
 
                 match column_iter.next() {
                     Some(Ok(value)) => {
@@ -408,7 +656,7 @@ where
 
             tmp_buf.clear();
             row_number += 1;
-            self.max_row_size = self.max_row_size.max(uncompressed_row_size);
+            max_row_size = max_row_size.max(uncompressed_row_size);
 
             if row_number == total_rows {
                 break
@@ -417,19 +665,11 @@ where
             column_iterators = iterators.into_iter();
         }
 
-        // drops immutable borrow
-        drop(maybe_zstd_compressors);
-
-        // Write offsets and offset index to file
-        self.freeze_offsets(offsets)?;
-
-        debug!(target: "nippy-jar", jar=?self, "Finished.");
-
-        Ok(())
+        Ok(max_row_size)
     }
 
-    /// Freezes offsets and its own index.
-    fn freeze_offsets(&mut self, offsets: Vec<usize>) -> Result<(), NippyJarError> {
+    /// Freezes offsets and its own index to the index file at `path`.
+    fn freeze_offsets(&mut self, offsets: Vec<usize>, path: &Path) -> Result<(), NippyJarError> {
         if !offsets.is_empty() {
             debug!(target: "nippy-jar", "Encoding offsets list.");
 
@@ -442,12 +682,14 @@ where
             self.offsets = builder.build().enable_rank();
         }
 
-        debug!(target: "nippy-jar", path=?self.index_path(), "Writing offsets and offsets index to file.");
+        debug!(target: "nippy-jar", ?path, "Writing offsets and offsets index to file.");
 
-        let mut file = File::create(self.index_path())?;
+        let mut file = File::create(path)?;
         self.offsets.serialize_into(&mut file)?;
         self.offsets_index.serialize_into(&mut file)?;
-        self.max_row_size.serialize_into(file)?;
+        self.max_row_size.serialize_into(&mut file)?;
+        bincode::serialize_into(&mut file, &self.checksum.unwrap_or_default())?;
+        file.sync_all()?;
         Ok(())
     }
 
@@ -484,6 +726,59 @@ where
     }
 }
 
+/// Returns the path of the temporary file that is written before replacing the file at `path`.
+fn tmp_path(path: &Path) -> PathBuf {
+    path.with_file_name(format!("{}.tmp", path.file_name().expect("exists").to_string_lossy()))
+}
+
+impl<H> NippyJar<H>
+where
+    H: Send + Sync + Serialize + for<'a> Deserialize<'a> + std::fmt::Debug + 'static,
+{
+    /// Decompresses every row of the jar, and returns the sorted numbers of the rows that fail to
+    /// decompress, e.g. because their data is corrupted.
+    ///
+    /// Rows of a jar without compression are never reported, see [`NippyJar::checksum`] to detect
+    /// their corruption.
+    pub fn verify(&self) -> Result<Vec<u64>, NippyJarError> {
+        let rows = (self.offsets.len() / self.columns) as u64;
+        self.verify_rows(self.open_data()?, 0..rows)
+    }
+
+    /// Same as [`NippyJar::verify`], but spreads the rows over the rayon thread pool.
+    ///
+    /// Rows are verified in chunks, each one with its own cursor and decompressors, so no
+    /// decompression state is shared between threads.
+    pub fn verify_par(&self) -> Result<Vec<u64>, NippyJarError> {
+        let mmap_handle = self.open_data()?;
+        let rows = (self.offsets.len() / self.columns) as u64;
+        let chunks = rayon::current_num_threads() as u64 * 4;
+        let chunk_size = ((rows + chunks - 1) / chunks).max(1);
+
+        // Chunks are collected in order, so the rows are sorted.
+        Ok((0..rows)
+            .step_by(chunk_size as usize)
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .map(|start| {
+                self.verify_rows(mmap_handle.clone(), start..(start + chunk_size).min(rows))
+            })
+            .collect::<Result<Vec<_>, _>>()?
+            .concat())
+    }
+
+    /// Returns the numbers of the rows in `rows` that fail to decompress.
+    fn verify_rows(
+        &self,
+        mmap_handle: MmapHandle,
+        rows: Range<u64>,
+    ) -> Result<Vec<u64>, NippyJarError> {
+        let decompressors = self.build_decompressors(1)?.pop().unwrap_or_default();
+        let mut cursor = NippyJarCursor::with_decompressors(self, mmap_handle, decompressors)?;
+        Ok(rows.filter(|row| cursor.row_by_number(*row as usize).is_err()).collect())
+    }
+}
+
 impl<H> InclusionFilter for NippyJar<H>
 where
     H: Send + Sync + Serialize + for<'a> Deserialize<'a>,
@@ -522,6 +817,8 @@ pub struct MmapHandle {
     file: Arc<File>,
     /// Mmap handle.
     mmap: Arc<Mmap>,
+    /// The [MmapAdvice] last applied to the mapping, shared by all clones of the handle.
+    advice: Arc<AtomicU8>,
 }
 
 impl MmapHandle {
@@ -531,7 +828,79 @@ impl MmapHandle {
         // SAFETY: File is read-only and its descriptor is kept alive as long as the mmap handle.
         let mmap = unsafe { Mmap::map(&file)? };
 
-        Ok(Self { file: Arc::new(file), mmap: Arc::new(mmap) })
+        Ok(Self {
+            file: Arc::new(file),
+            mmap: Arc::new(mmap),
+            advice: Arc::new(AtomicU8::new(MmapAdvice::Normal as u8)),
+        })
+    }
+
+    /// Hints the expected access pattern of the mapping to the kernel with `madvise`, unless it's
+    /// already the last applied one.
+    ///
+    /// The advice applies to the whole mapping, so it affects all clones of the handle. It's not
+    /// applied on non-unix platforms.
+    pub fn advise(&self, advice: MmapAdvice) -> Result<(), NippyJarError> {
+        let previous = self.advice.swap(advice as u8, Ordering::Relaxed);
+        if previous == advice as u8 {
+            return Ok(())
+        }
+
+        #[cfg(unix)]
+        if let Err(err) = self.mmap.advise(advice.into()) {
+            self.advice.store(previous, Ordering::Relaxed);
+            return Err(err.into())
+        }
+
+        Ok(())
+    }
+
+    /// Loads the pages of the mapping that hold the bytes in `range` into memory, by reading a
+    /// byte of every page, so a subsequent scan of them doesn't stall on page faults.
+    ///
+    /// Bytes past the end of the mapping are ignored.
+    pub fn warm(&self, range: Range<usize>) {
+        // The mapping starts at a page boundary, so aligning the offset aligns the address.
+        const PAGE_SIZE: usize = 4096;
+
+        let end = range.end.min(self.mmap.len());
+        let start = range.start - range.start % PAGE_SIZE;
+        for offset in (start..end).step_by(PAGE_SIZE) {
+            std::hint::black_box(self.mmap[offset]);
+        }
+    }
+
+    /// Returns the [MmapAdvice] last applied with [`Self::advise`].
+    pub fn advice(&self) -> MmapAdvice {
+        match self.advice.load(Ordering::Relaxed) {
+            x if x == MmapAdvice::Sequential as u8 => MmapAdvice::Sequential,
+            x if x == MmapAdvice::Random as u8 => MmapAdvice::Random,
+            _ => MmapAdvice::Normal,
+        }
+    }
+}
+
+/// Expected access pattern of a memory mapped file. See [`MmapHandle::advise`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum MmapAdvice {
+    /// No specific access pattern, the kernel default.
+    #[default]
+    Normal,
+    /// Pages are accessed in order, e.g. by range scans, so they can be read ahead aggressively.
+    Sequential,
+    /// Pages are accessed in random order, e.g. by point lookups, so read ahead is less useful.
+    Random,
+}
+
+#[cfg(unix)]
+impl From<MmapAdvice> for memmap2::Advice {
+    fn from(advice: MmapAdvice) -> Self {
+        match advice {
+            MmapAdvice::Normal => memmap2::Advice::Normal,
+            MmapAdvice::Sequential => memmap2::Advice::Sequential,
+            MmapAdvice::Random => memmap2::Advice::Random,
+        }
     }
 }
 
@@ -542,6 +911,25 @@ impl Deref for MmapHandle {
     }
 }
 
+/// Writer over the data file that updates the checksum of the rows with every written byte, see
+/// [`NippyJar::checksum`].
+struct ChecksumWriter<'a> {
+    file: &'a mut File,
+    hasher: &'a mut crc32fast::Hasher,
+}
+
+impl Write for ChecksumWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.file.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -735,6 +1123,110 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_decompressor_pool() {
+        let (col1, col2) = test_data(None);
+        let num_rows = col1.len() as u64;
+        let file_path = tempfile::NamedTempFile::new().unwrap();
+
+        let mut nippy = NippyJar::new_without_header(2, file_path.path()).with_zstd(true, 5000);
+        nippy.prepare_compression(vec![col1.clone(), col2.clone()]).unwrap();
+        nippy.freeze(vec![clone_with_result(&col1), clone_with_result(&col2)], num_rows).unwrap();
+
+        let loaded_nippy = NippyJar::load_without_header(file_path.path()).unwrap();
+        let mmap_handle = loaded_nippy.open_data().unwrap();
+        let pool = loaded_nippy.build_decompressors(4).unwrap();
+        assert_eq!(pool.len(), 4);
+        assert!(pool.iter().all(|decompressors| decompressors.len() == 2));
+
+        // Every reader takes a set of decompressors from the pool
+        std::thread::scope(|scope| {
+            let handles = pool
+                .into_iter()
+                .enumerate()
+                .map(|(reader, decompressors)| {
+                    let (loaded_nippy, mmap_handle) = (&loaded_nippy, mmap_handle.clone());
+                    let (col1, col2) = (&col1, &col2);
+                    scope.spawn(move || {
+                        let mut cursor = NippyJarCursor::with_decompressors(
+                            loaded_nippy,
+                            mmap_handle,
+                            decompressors,
+                        )
+                        .unwrap();
+                        for row_index in (reader..col1.len()).step_by(3) {
+                            let row = cursor.row_by_number(row_index).unwrap().unwrap();
+                            assert_eq!(
+                                (row[0], row[1]),
+                                (col1[row_index].as_slice(), col2[row_index].as_slice())
+                            );
+                        }
+                        cursor.into_decompressors()
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            // The decompressors are given back after the lookups
+            for handle in handles {
+                assert_eq!(handle.join().unwrap().len(), 2);
+            }
+        });
+
+        // A set must have a decompressor for every column
+        let mut decompressors = loaded_nippy.build_decompressors(1).unwrap().remove(0);
+        decompressors.pop();
+        assert!(matches!(
+            NippyJarCursor::with_decompressors(&loaded_nippy, mmap_handle, decompressors),
+            Err(NippyJarError::ColumnLenMismatch(2, 1))
+        ));
+    }
+
+    #[test]
+    fn test_verify() {
+        let (col1, col2) = test_data(None);
+        let num_rows = col1.len() as u64;
+        let file_path = tempfile::NamedTempFile::new().unwrap();
+
+        let mut nippy = NippyJar::new_without_header(2, file_path.path()).with_zstd(true, 5000);
+        nippy.prepare_compression(vec![col1.clone(), col2.clone()]).unwrap();
+        nippy.freeze(vec![clone_with_result(&col1), clone_with_result(&col2)], num_rows).unwrap();
+
+        let loaded_nippy = NippyJar::load_without_header(file_path.path()).unwrap();
+        assert!(loaded_nippy.verify().unwrap().is_empty());
+        assert!(loaded_nippy.verify_par().unwrap().is_empty());
+
+        // Overwrite the zstd frame magic of one column value of two rows
+        let bad_rows = vec![3, 70];
+        let mut file = std::fs::OpenOptions::new().write(true).open(file_path.path()).unwrap();
+        for (row, column) in bad_rows.iter().zip([1, 0]) {
+            let offset = loaded_nippy.offsets.select(*row as usize * 2 + column).unwrap();
+            file.seek(std::io::SeekFrom::Start(offset as u64)).unwrap();
+            file.write_all(&[0; 4]).unwrap();
+        }
+        file.sync_all().unwrap();
+
+        assert_eq!(loaded_nippy.verify().unwrap(), bad_rows);
+        for num_threads in [1, 2, 4, 8] {
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(num_threads).build().unwrap();
+            assert_eq!(pool.install(|| loaded_nippy.verify_par()).unwrap(), bad_rows);
+        }
+    }
+
+    #[test]
+    fn test_cursor_without_dictionaries() {
+        let file_path = tempfile::NamedTempFile::new().unwrap();
+
+        // Dictionaries were never prepared, so the data can't be decompressed.
+        let nippy = NippyJar::new_without_header(2, file_path.path()).with_zstd(true, 5000);
+        assert!(matches!(NippyJarCursor::new(&nippy), Err(NippyJarError::DictionaryNotLoaded)));
+
+        // Same for dictionaries that were prepared, but not loaded.
+        let (col1, col2) = test_data(None);
+        let mut nippy = NippyJar::new_without_header(2, file_path.path()).with_zstd(true, 5000);
+        nippy.prepare_compression(vec![col1, col2]).unwrap();
+        assert!(matches!(NippyJarCursor::new(&nippy), Err(NippyJarError::DictionaryNotLoaded)));
+    }
+
     #[test]
     fn test_lz4() {
         let (col1, col2) = test_data(None);
@@ -887,6 +1379,192 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_append() {
+        let (col1, col2) = test_data(None);
+        let num_rows = col1.len();
+        let num_columns = 2;
+        let split = num_rows / 2;
+        let file_path = tempfile::NamedTempFile::new().unwrap();
+
+        // Create file with the first half of the rows
+        {
+            let mut nippy = NippyJar::new_without_header(num_columns, file_path.path())
+                .with_zstd(false, 0)
+                .with_cuckoo_filter(num_rows)
+                .with_fmph();
+
+            nippy.prepare_index(clone_with_result(&col1[..split].to_vec()), split).unwrap();
+            nippy
+                .freeze(
+                    vec![
+                        clone_with_result(&col1[..split].to_vec()),
+                        clone_with_result(&col2[..split].to_vec()),
+                    ],
+                    split as u64,
+                )
+                .unwrap();
+        }
+
+        // Append the second half
+        {
+            let mut nippy = NippyJar::load_without_header(file_path.path()).unwrap();
+            nippy
+                .append(
+                    vec![
+                        clone_with_result(&col1[split..].to_vec()),
+                        clone_with_result(&col2[split..].to_vec()),
+                    ],
+                    (num_rows - split) as u64,
+                    Some(clone_with_result(&col1)),
+                    (),
+                )
+                .unwrap();
+        }
+
+        // Read file
+        {
+            let loaded_nippy = NippyJar::load_without_header(file_path.path()).unwrap();
+            let mut cursor = NippyJarCursor::new(&loaded_nippy).unwrap();
+
+            let mut row_num = 0usize;
+            while let Some(row) = cursor.next_row().unwrap() {
+                assert_eq!((row[0], row[1]), (col1[row_num].as_slice(), col2[row_num].as_slice()));
+                row_num += 1;
+            }
+            assert_eq!(row_num, num_rows);
+
+            for (v0, v1) in col1.iter().zip(col2.iter()) {
+                let row = cursor.row_by_key(v0).unwrap().unwrap();
+                assert_eq!((row[0], row[1]), (v0.as_slice(), v1.as_slice()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_checksum() {
+        let (col1, col2) = test_data(None);
+        let num_rows = col1.len();
+        let split = num_rows / 2;
+
+        for use_dict in [false, true] {
+            let file_path = tempfile::NamedTempFile::new().unwrap();
+            let mut nippy =
+                NippyJar::new_without_header(2, file_path.path()).with_zstd(use_dict, 5000);
+            if use_dict {
+                nippy.prepare_compression(vec![col1.clone(), col2.clone()]).unwrap();
+            }
+            nippy
+                .freeze(
+                    vec![
+                        clone_with_result(&col1[..split].to_vec()),
+                        clone_with_result(&col2[..split].to_vec()),
+                    ],
+                    split as u64,
+                )
+                .unwrap();
+
+            // The checksum computed while writing the rows is recorded in the index file
+            let loaded_nippy = NippyJar::load_without_header(file_path.path()).unwrap();
+            assert!(loaded_nippy.checksum().is_some());
+            assert_eq!(loaded_nippy.checksum(), nippy.checksum());
+            assert_eq!(loaded_nippy.checksum(), Some(loaded_nippy.compute_checksum().unwrap()));
+
+            // Appended rows are part of the checksum
+            if !use_dict {
+                let mut nippy = loaded_nippy;
+                let append = |nippy: &mut NippyJar, total_rows: usize| {
+                    nippy.append(
+                        vec![
+                            clone_with_result(&col1[split..].to_vec()),
+                            clone_with_result(&col2[split..].to_vec()),
+                        ],
+                        total_rows as u64,
+                        None::<Vec<ColumnResult<Vec<u8>>>>,
+                        (),
+                    )
+                };
+
+                // A failed append leaves both the jar and its files untouched
+                let checksum = nippy.checksum();
+                assert!(matches!(
+                    append(&mut nippy, num_rows - split + 1),
+                    Err(NippyJarError::UnexpectedMissingValue(_, _))
+                ));
+                assert_eq!(nippy.checksum(), checksum);
+                assert_eq!(nippy.offsets.len(), split * 2);
+                assert!(!tmp_path(file_path.path()).exists());
+                let loaded_nippy = NippyJar::load_without_header(file_path.path()).unwrap();
+                assert_eq!(loaded_nippy.checksum(), checksum);
+                assert_eq!(loaded_nippy.checksum(), Some(loaded_nippy.compute_checksum().unwrap()));
+
+                append(&mut nippy, num_rows - split).unwrap();
+                let loaded_nippy = NippyJar::load_without_header(file_path.path()).unwrap();
+                assert_eq!(loaded_nippy.checksum(), nippy.checksum());
+                assert_eq!(loaded_nippy.checksum(), Some(loaded_nippy.compute_checksum().unwrap()));
+            }
+
+            // Flip a byte of the last row
+            let mut bytes = std::fs::read(file_path.path()).unwrap();
+            *bytes.last_mut().unwrap() ^= 0xff;
+            std::fs::write(file_path.path(), bytes).unwrap();
+
+            let loaded_nippy = NippyJar::load_without_header(file_path.path()).unwrap();
+            assert_ne!(loaded_nippy.checksum(), Some(loaded_nippy.compute_checksum().unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_cursor_reuse() {
+        let (col1, col2) = test_data(None);
+        let num_rows = col1.len();
+        let num_columns = 2;
+        let file_path = tempfile::NamedTempFile::new().unwrap();
+
+        // Create file
+        {
+            let mut nippy = NippyJar::new_without_header(num_columns, file_path.path())
+                .with_zstd(false, 0)
+                .with_cuckoo_filter(num_rows)
+                .with_fmph();
+
+            nippy.prepare_index(clone_with_result(&col1), num_rows).unwrap();
+            nippy
+                .freeze(vec![clone_with_result(&col1), clone_with_result(&col2)], num_rows as u64)
+                .unwrap();
+        }
+
+        let loaded_nippy = NippyJar::load_without_header(file_path.path()).unwrap();
+        let mut cursor = NippyJarCursor::new(&loaded_nippy).unwrap();
+
+        // Interleave lookups by key and by number on the same cursor
+        for row_num in 0..num_rows {
+            let other_row_num = num_rows - 1 - row_num;
+
+            let row = cursor.row_by_key(&col1[other_row_num]).unwrap().unwrap();
+            assert_eq!(
+                (row[0], row[1]),
+                (col1[other_row_num].as_slice(), col2[other_row_num].as_slice())
+            );
+
+            let row = cursor.row_by_number(row_num).unwrap().unwrap();
+            assert_eq!((row[0], row[1]), (col1[row_num].as_slice(), col2[row_num].as_slice()));
+        }
+
+        // A lookup leaves the cursor past the row found, until it is reset
+        cursor.row_by_key(&col1[num_rows / 2]).unwrap().unwrap();
+        assert_eq!(cursor.row_index(), (num_rows / 2 + 1) as u64);
+        cursor.reset();
+        assert_eq!(cursor.row_index(), 0);
+
+        let mut row_num = 0usize;
+        while let Some(row) = cursor.next_row().unwrap() {
+            assert_eq!((row[0], row[1]), (col1[row_num].as_slice(), col2[row_num].as_slice()));
+            row_num += 1;
+        }
+        assert_eq!(row_num, num_rows);
+    }
+
     #[test]
     fn test_selectable_column_values() {
         let (col1, col2) = test_data(None);
@@ -0,0 +1,100 @@
+use super::SnapshotProvider;
+use crate::{BlockHashReader, DatabaseProvider, HeaderProvider};
+use reth_db::transaction::DbTx;
+use reth_interfaces::provider::{ProviderError, ProviderResult};
+use reth_primitives::{BlockHash, BlockNumber, Header, SealedHeader, B256, U256};
+use std::ops::RangeBounds;
+
+/// A [`SnapshotProvider`] that falls back to a [`DatabaseProvider`] for data outside the
+/// snapshots, see [`SnapshotProvider::with_fallback`].
+///
+/// Point lookups are served by the snapshots if any covers them, and by the database otherwise,
+/// e.g. below the lowest snapshot, in a gap between snapshots, or past the highest snapshot.
+/// Range lookups are served by the database.
+#[derive(Debug)]
+pub struct SnapshotFallbackProvider<'a, TX> {
+    /// The snapshots checked first.
+    snapshot_provider: &'a SnapshotProvider,
+    /// The database used for data outside the snapshots.
+    db_provider: &'a DatabaseProvider<TX>,
+}
+
+impl<'a, TX> SnapshotFallbackProvider<'a, TX> {
+    /// Creates a new [`SnapshotFallbackProvider`].
+    pub fn new(
+        snapshot_provider: &'a SnapshotProvider,
+        db_provider: &'a DatabaseProvider<TX>,
+    ) -> Self {
+        Self { snapshot_provider, db_provider }
+    }
+
+    /// Returns the result of the snapshot lookup, or of the database lookup if no snapshot covers
+    /// the requested data.
+    fn get<T>(
+        &self,
+        snapshot_fn: impl FnOnce(&SnapshotProvider) -> ProviderResult<Option<T>>,
+        db_fn: impl FnOnce(&DatabaseProvider<TX>) -> ProviderResult<Option<T>>,
+    ) -> ProviderResult<Option<T>> {
+        match snapshot_fn(self.snapshot_provider) {
+            Ok(Some(value)) => Ok(Some(value)),
+            Ok(None) |
+            Err(
+                ProviderError::MissingSnapshotBlock(_, _) |
+                ProviderError::MissingSnapshotTx(_, _) |
+                ProviderError::SnapshotBlockBelowLowest(_, _, _) |
+                ProviderError::SnapshotTxBelowLowest(_, _, _) |
+                ProviderError::SnapshotBlockInGap(_, _) |
+                ProviderError::SnapshotTxInGap(_, _),
+            ) => db_fn(self.db_provider),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+impl<'a, TX: DbTx> HeaderProvider for SnapshotFallbackProvider<'a, TX> {
+    fn header(&self, block_hash: &BlockHash) -> ProviderResult<Option<Header>> {
+        self.get(|snapshot| snapshot.header(block_hash), |db| db.header(block_hash))
+    }
+
+    fn header_by_number(&self, num: BlockNumber) -> ProviderResult<Option<Header>> {
+        self.get(|snapshot| snapshot.header_by_number(num), |db| db.header_by_number(num))
+    }
+
+    fn header_td(&self, block_hash: &BlockHash) -> ProviderResult<Option<U256>> {
+        self.get(|snapshot| snapshot.header_td(block_hash), |db| db.header_td(block_hash))
+    }
+
+    fn header_td_by_number(&self, num: BlockNumber) -> ProviderResult<Option<U256>> {
+        self.get(|snapshot| snapshot.header_td_by_number(num), |db| db.header_td_by_number(num))
+    }
+
+    fn headers_range(&self, range: impl RangeBounds<BlockNumber>) -> ProviderResult<Vec<Header>> {
+        self.db_provider.headers_range(range)
+    }
+
+    fn sealed_header(&self, num: BlockNumber) -> ProviderResult<Option<SealedHeader>> {
+        self.get(|snapshot| snapshot.sealed_header(num), |db| db.sealed_header(num))
+    }
+
+    fn sealed_headers_while(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+        predicate: impl FnMut(&SealedHeader) -> bool,
+    ) -> ProviderResult<Vec<SealedHeader>> {
+        self.db_provider.sealed_headers_while(range, predicate)
+    }
+}
+
+impl<'a, TX: DbTx> BlockHashReader for SnapshotFallbackProvider<'a, TX> {
+    fn block_hash(&self, num: u64) -> ProviderResult<Option<B256>> {
+        self.get(|snapshot| snapshot.block_hash(num), |db| db.block_hash(num))
+    }
+
+    fn canonical_hashes_range(
+        &self,
+        start: BlockNumber,
+        end: BlockNumber,
+    ) -> ProviderResult<Vec<B256>> {
+        self.db_provider.canonical_hashes_range(start, end)
+    }
+}
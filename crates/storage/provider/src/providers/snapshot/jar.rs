@@ -5,13 +5,16 @@ use crate::{
 use reth_db::{
     codecs::CompactU256,
     snapshot::{HeaderMask, ReceiptMask, SnapshotCursor, TransactionMask},
+    table::Table,
+    TxSenders,
 };
 use reth_interfaces::provider::{ProviderError, ProviderResult};
+use reth_nippy_jar::compression::Decompressor;
 use reth_primitives::{
     Address, BlockHash, BlockHashOrNumber, BlockNumber, ChainInfo, Header, Receipt, SealedHeader,
     TransactionMeta, TransactionSigned, TransactionSignedNoHash, TxHash, TxNumber, B256, U256,
 };
-use std::ops::{Deref, Range, RangeBounds};
+use std::ops::{Bound, Deref, Range, RangeBounds};
 
 /// Provider over a specific `NippyJar` and range.
 #[derive(Debug)]
@@ -44,6 +47,171 @@ impl<'a> SnapshotJarProvider<'a> {
         SnapshotCursor::new(self.value(), self.mmap_handle())
     }
 
+    /// Provides a cursor that reuses the given decompressors, taken from a pool built with
+    /// [`Self::build_decompressors`].
+    pub fn cursor_with_decompressors<'b>(
+        &'b self,
+        decompressors: Vec<Decompressor<'a>>,
+    ) -> ProviderResult<SnapshotCursor<'a>>
+    where
+        'b: 'a,
+    {
+        SnapshotCursor::with_decompressors(self.value(), self.mmap_handle(), decompressors)
+    }
+
+    /// Builds `n` sets of decompressors for concurrent readers of the snapshot, e.g. to pre-warm
+    /// the pool of an RPC server. Each set can be given to a single cursor at a time, see
+    /// [`Self::cursor_with_decompressors`].
+    pub fn build_decompressors<'b>(&'b self, n: usize) -> ProviderResult<Vec<Vec<Decompressor<'a>>>>
+    where
+        'b: 'a,
+    {
+        Ok(self.value().build_decompressors(n)?)
+    }
+
+    /// Returns the raw, still compressed, header bytes of the given block number.
+    pub fn raw_header_bytes(&self, num: BlockNumber) -> ProviderResult<Option<Vec<u8>>> {
+        Ok(self.cursor()?.get_one_raw::<HeaderMask<Header>>(num).map(<[u8]>::to_vec))
+    }
+
+    /// Returns the header of the given block number, if its hash is `block_hash`.
+    ///
+    /// The row is found by its number, so unlike [`HeaderProvider::header`], the filter and PHF
+    /// are skipped, and their false positives can't happen.
+    pub fn header_by_hash_and_number(
+        &self,
+        block_hash: &BlockHash,
+        num: BlockNumber,
+    ) -> ProviderResult<Option<Header>> {
+        Ok(self
+            .cursor()?
+            .get_two::<HeaderMask<Header, BlockHash>>(num.into())?
+            .filter(|(_, hash)| hash == block_hash)
+            .map(|(header, _)| header))
+    }
+
+    /// Returns the raw, still compressed, transaction bytes of the given transaction number.
+    pub fn raw_tx_bytes(&self, num: TxNumber) -> ProviderResult<Option<Vec<u8>>> {
+        Ok(self
+            .cursor()?
+            .get_one_raw::<TransactionMask<TransactionSignedNoHash>>(num)
+            .map(<[u8]>::to_vec))
+    }
+
+    /// Returns the total difficulty of every block in the range, only decompressing the total
+    /// difficulty column.
+    pub fn header_td_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> ProviderResult<Vec<(BlockNumber, U256)>> {
+        let numbers = self.row_numbers(range);
+
+        let mut cursor = self.cursor()?;
+        let mut tds = Vec::with_capacity(numbers.len());
+
+        for num in numbers {
+            if let Some(td) = cursor.get_one::<HeaderMask<CompactU256>>(num.into())? {
+                tds.push((num, td.into()))
+            }
+        }
+        Ok(tds)
+    }
+
+    /// Returns the header of every block in the range paired with its total difficulty, reading
+    /// both columns of each row in a single cursor walk.
+    pub fn headers_with_td_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> ProviderResult<Vec<(Header, U256)>> {
+        let numbers = self.row_numbers(range);
+
+        let mut cursor = self.cursor()?;
+        let mut headers = Vec::with_capacity(numbers.len());
+
+        for num in numbers {
+            if let Some((header, td)) =
+                cursor.get_two::<HeaderMask<Header, CompactU256>>(num.into())?
+            {
+                headers.push((header, td.into()))
+            }
+        }
+        Ok(headers)
+    }
+
+    /// Returns the transactions in the range paired with their senders.
+    ///
+    /// Senders are read along with the transactions in a single cursor walk if the snapshot has a
+    /// [senders column](reth_primitives::snapshot::SegmentHeader::has_senders), otherwise they are
+    /// recovered from the signatures.
+    pub fn transactions_with_senders_by_tx_range(
+        &self,
+        range: impl RangeBounds<TxNumber>,
+    ) -> ProviderResult<Vec<(TransactionSigned, Address)>> {
+        if self.user_header().has_senders() {
+            let numbers = self.row_numbers(range);
+
+            let mut cursor = self.cursor()?;
+            let mut txes = Vec::with_capacity(numbers.len());
+
+            for num in numbers {
+                if let Some((tx, sender)) = cursor.get_two::<TransactionMask<
+                    TransactionSignedNoHash,
+                    <TxSenders as Table>::Value,
+                >>(num.into())?
+                {
+                    txes.push((tx.with_hash(), sender))
+                }
+            }
+            return Ok(txes)
+        }
+
+        let txs = self.transactions_by_tx_range(range)?;
+        let senders = TransactionSignedNoHash::recover_signers(&txs, txs.len())
+            .ok_or(ProviderError::SenderRecoveryError)?;
+        Ok(txs.into_iter().map(TransactionSignedNoHash::with_hash).zip(senders).collect())
+    }
+
+    /// Resolves the block or transaction number bounds of `range` to the `(start, end)` row
+    /// offsets within the segment, with `end` exclusive and clamped to the rows of the segment.
+    ///
+    /// Unbounded starts and ends resolve to the first and past the last row respectively. Ranges
+    /// outside of the segment, or empty ones, resolve to an empty range of rows.
+    pub(crate) fn resolve_bounds(&self, range: impl RangeBounds<u64>) -> (usize, usize) {
+        let header = self.user_header();
+        let (first, last) = (header.start(), header.end());
+
+        let start = match range.start_bound() {
+            Bound::Included(&num) => num,
+            Bound::Excluded(&num) => num.saturating_add(1),
+            Bound::Unbounded => first,
+        }
+        .max(first);
+        let end = match range.end_bound() {
+            Bound::Included(&num) => num.saturating_add(1),
+            Bound::Excluded(&num) => num,
+            Bound::Unbounded => u64::MAX,
+        }
+        .min(last.saturating_add(1))
+        .max(start);
+
+        ((start - first) as usize, (end - first) as usize)
+    }
+
+    /// Returns the block or transaction numbers in `range` that are within the segment. See
+    /// [Self::resolve_bounds].
+    fn row_numbers(&self, range: impl RangeBounds<u64>) -> impl ExactSizeIterator<Item = u64> {
+        let (start, end) = self.resolve_bounds(range);
+        let first = self.user_header().start();
+        (start..end).map(move |row| first + row as u64)
+    }
+
+    /// Loads the pages of the snapshot that hold the blocks or transactions in `range` into
+    /// memory. See [NippyJar::warm_rows](reth_nippy_jar::NippyJar::warm_rows).
+    pub fn warm(&self, range: impl RangeBounds<u64>) {
+        let (start, end) = self.resolve_bounds(range);
+        self.jar.warm_rows(&self.mmap_handle(), start..end);
+    }
+
     /// Adds a new auxiliar snapshot to help query data from the main one
     pub fn with_auxiliar(mut self, auxiliar_jar: SnapshotJarProvider<'a>) -> Self {
         self.auxiliar_jar = Some(Box::new(auxiliar_jar));
@@ -77,12 +245,12 @@ impl<'a> HeaderProvider for SnapshotJarProvider<'a> {
     }
 
     fn headers_range(&self, range: impl RangeBounds<BlockNumber>) -> ProviderResult<Vec<Header>> {
-        let range = to_range(range);
+        let numbers = self.row_numbers(range);
 
         let mut cursor = self.cursor()?;
-        let mut headers = Vec::with_capacity((range.end - range.start) as usize);
+        let mut headers = Vec::with_capacity(numbers.len());
 
-        for num in range.start..range.end {
+        for num in numbers {
             if let Some(header) = cursor.get_one::<HeaderMask<Header>>(num.into())? {
                 headers.push(header);
             }
@@ -103,12 +271,12 @@ impl<'a> HeaderProvider for SnapshotJarProvider<'a> {
         range: impl RangeBounds<BlockNumber>,
         mut predicate: impl FnMut(&SealedHeader) -> bool,
     ) -> ProviderResult<Vec<SealedHeader>> {
-        let range = to_range(range);
+        let numbers = self.row_numbers(range);
 
         let mut cursor = self.cursor()?;
-        let mut headers = Vec::with_capacity((range.end - range.start) as usize);
+        let mut headers = Vec::with_capacity(numbers.len());
 
-        for number in range.start..range.end {
+        for number in numbers {
             if let Some((header, hash)) =
                 cursor.get_two::<HeaderMask<Header, BlockHash>>(number.into())?
             {
@@ -133,10 +301,12 @@ impl<'a> BlockHashReader for SnapshotJarProvider<'a> {
         start: BlockNumber,
         end: BlockNumber,
     ) -> ProviderResult<Vec<B256>> {
+        let numbers = self.row_numbers(start..end);
+
         let mut cursor = self.cursor()?;
-        let mut hashes = Vec::with_capacity((end - start) as usize);
+        let mut hashes = Vec::with_capacity(numbers.len());
 
-        for number in start..end {
+        for number in numbers {
             if let Some(hash) = cursor.get_one::<HeaderMask<BlockHash>>(number.into())? {
                 hashes.push(hash)
             }
@@ -224,11 +394,30 @@ impl<'a> TransactionsProvider for SnapshotJarProvider<'a> {
 
     fn transactions_by_block_range(
         &self,
-        _range: impl RangeBounds<BlockNumber>,
+        range: impl RangeBounds<BlockNumber>,
     ) -> ProviderResult<Vec<Vec<TransactionSigned>>> {
-        // Related to indexing tables. Live database should get the tx_range and call snapshot
-        // provider with `transactions_by_tx_range` instead.
-        Err(ProviderError::UnsupportedProvider)
+        let header = self.user_header();
+        let Range { start, end } = to_range(range);
+        let blocks = start.max(header.block_start())..end.min(header.block_end() + 1);
+
+        let mut cursor = self.cursor()?;
+        blocks
+            .map(|block| {
+                // The transaction counts of every block are recorded when the snapshot is created,
+                // without them only the live database can group transactions by block.
+                let Range { start, end } =
+                    header.tx_range_for_block(block).ok_or(ProviderError::UnsupportedProvider)?;
+                let mut txes = Vec::with_capacity((end - start) as usize);
+                for num in start..end {
+                    if let Some(tx) =
+                        cursor.get_one::<TransactionMask<TransactionSignedNoHash>>(num.into())?
+                    {
+                        txes.push(tx.with_hash())
+                    }
+                }
+                Ok(txes)
+            })
+            .collect()
     }
 
     fn senders_by_tx_range(
@@ -244,11 +433,12 @@ impl<'a> TransactionsProvider for SnapshotJarProvider<'a> {
         &self,
         range: impl RangeBounds<TxNumber>,
     ) -> ProviderResult<Vec<reth_primitives::TransactionSignedNoHash>> {
-        let range = to_range(range);
+        let numbers = self.row_numbers(range);
+
         let mut cursor = self.cursor()?;
-        let mut txes = Vec::with_capacity((range.end - range.start) as usize);
+        let mut txes = Vec::with_capacity(numbers.len());
 
-        for num in range {
+        for num in numbers {
             if let Some(tx) =
                 cursor.get_one::<TransactionMask<TransactionSignedNoHash>>(num.into())?
             {
@@ -259,8 +449,14 @@ impl<'a> TransactionsProvider for SnapshotJarProvider<'a> {
     }
 
     fn transaction_sender(&self, num: TxNumber) -> ProviderResult<Option<Address>> {
-        Ok(self
-            .cursor()?
+        let mut cursor = self.cursor()?;
+
+        // Use the senders column if the snapshot has one, otherwise recover it from the signature.
+        if self.user_header().has_senders() {
+            return cursor.get_one::<TransactionMask<<TxSenders as Table>::Value>>(num.into())
+        }
+
+        Ok(cursor
             .get_one::<TransactionMask<TransactionSignedNoHash>>(num.into())?
             .and_then(|tx| tx.recover_signer()))
     }
@@ -287,15 +483,15 @@ impl<'a> ReceiptProvider for SnapshotJarProvider<'a> {
     }
 }
 
-fn to_range<R: RangeBounds<u64>>(bounds: R) -> Range<u64> {
+pub(crate) fn to_range<R: RangeBounds<u64>>(bounds: R) -> Range<u64> {
     let start = match bounds.start_bound() {
         std::ops::Bound::Included(&v) => v,
-        std::ops::Bound::Excluded(&v) => v + 1,
+        std::ops::Bound::Excluded(&v) => v.saturating_add(1),
         std::ops::Bound::Unbounded => 0,
     };
 
     let end = match bounds.end_bound() {
-        std::ops::Bound::Included(&v) => v + 1,
+        std::ops::Bound::Included(&v) => v.saturating_add(1),
         std::ops::Bound::Excluded(&v) => v,
         std::ops::Bound::Unbounded => u64::MAX,
     };
@@ -1,5 +1,7 @@
-use super::{LoadedJar, SnapshotJarProvider};
-use crate::{BlockHashReader, BlockNumReader, HeaderProvider, TransactionsProvider};
+use super::{jar::to_range, LoadedJar, SnapshotFallbackProvider, SnapshotJarProvider};
+use crate::{
+    BlockHashReader, BlockNumReader, DatabaseProvider, HeaderProvider, TransactionsProvider,
+};
 use dashmap::DashMap;
 use parking_lot::RwLock;
 use reth_db::{
@@ -7,30 +9,81 @@ use reth_db::{
     snapshot::{HeaderMask, TransactionMask},
 };
 use reth_interfaces::provider::{ProviderError, ProviderResult};
-use reth_nippy_jar::NippyJar;
+use reth_nippy_jar::{compression::Decompressor, filter::InclusionFilter, MmapAdvice, NippyJar};
 use reth_primitives::{
-    snapshot::HighestSnapshots, Address, BlockHash, BlockHashOrNumber, BlockNumber, ChainInfo,
-    Header, SealedHeader, SnapshotSegment, TransactionMeta, TransactionSigned,
-    TransactionSignedNoHash, TxHash, TxNumber, B256, U256,
+    snapshot::{iter_snapshots, HighestSnapshots, SegmentHeader},
+    Address, BlockHash, BlockHashOrNumber, BlockNumber, ChainInfo, Header, SealedHeader,
+    SnapshotSegment, TransactionMeta, TransactionSigned, TransactionSignedNoHash, TxHash, TxNumber,
+    B256, U256,
 };
 use revm::primitives::HashMap;
 use std::{
     collections::BTreeMap,
-    ops::{RangeBounds, RangeInclusive},
+    ops::{Range, RangeBounds, RangeInclusive},
     path::{Path, PathBuf},
 };
 use tokio::sync::watch;
 
-/// Alias type for a map that can be queried for transaction/block ranges from a block/transaction
-/// segment respectively. It uses `BlockNumber` to represent the block end of a snapshot range or
-/// `TxNumber` to represent the transaction end of a snapshot range.
+/// Block range and transaction range of a snapshot.
+type SnapshotRanges = (RangeInclusive<BlockNumber>, RangeInclusive<TxNumber>);
+
+/// Alias type for a map that can be queried for the ranges of the snapshots of a segment. It uses
+/// `BlockNumber` to represent the block end of a snapshot range or `TxNumber` to represent the
+/// transaction end of a snapshot range.
 ///
 /// Can be in one of the two formats:
-/// - `HashMap<SnapshotSegment, BTreeMap<BlockNumber, RangeInclusive<TxNumber>>>`
-/// - `HashMap<SnapshotSegment, BTreeMap<TxNumber, RangeInclusive<BlockNumber>>>`
-type SegmentRanges = HashMap<SnapshotSegment, BTreeMap<u64, RangeInclusive<u64>>>;
+/// - `HashMap<SnapshotSegment, BTreeMap<BlockNumber, SnapshotRanges>>`
+/// - `HashMap<SnapshotSegment, BTreeMap<TxNumber, SnapshotRanges>>`
+type SegmentRanges = HashMap<SnapshotSegment, BTreeMap<u64, SnapshotRanges>>;
+
+/// Position of a block or transaction number relative to the snapshots of a segment.
+#[derive(Debug)]
+enum SnapshotPosition {
+    /// The number is in the snapshot with the given ranges.
+    Covered(SnapshotRanges),
+    /// The number is below the lowest snapshot, which starts at the given number.
+    BelowLowest(u64),
+    /// The number is in a gap between two snapshots.
+    InGap,
+    /// The number is above the highest snapshot, or the segment has no snapshots.
+    AboveHighest,
+}
+
+impl SnapshotPosition {
+    /// Finds the position of `number` in the snapshots of the index, where `start` returns the
+    /// start of a snapshot in the same unit as the index keys.
+    fn find(
+        index: Option<&BTreeMap<u64, SnapshotRanges>>,
+        number: u64,
+        start: impl Fn(&SnapshotRanges) -> u64,
+    ) -> Self {
+        let Some(index) = index else { return Self::AboveHighest };
+        // The first snapshot ending at or after the number is the only one that can contain it.
+        let Some((end, ranges)) = index.range(number..).next() else { return Self::AboveHighest };
+
+        let snapshot_start = start(ranges);
+        if snapshot_start <= number {
+            Self::Covered(ranges.clone())
+        } else if index.keys().next() == Some(end) {
+            Self::BelowLowest(snapshot_start)
+        } else {
+            Self::InGap
+        }
+    }
+
+    /// Returns the ranges of the snapshot covering the number, if any.
+    fn into_ranges(self) -> Option<SnapshotRanges> {
+        match self {
+            Self::Covered(ranges) => Some(ranges),
+            _ => None,
+        }
+    }
+}
 
 /// [`SnapshotProvider`] manages all existing [`SnapshotJarProvider`].
+///
+/// Every lookup opens its own cursor, with its own decompressors, so a single provider can be
+/// shared across threads to serve concurrent queries without locking the snapshots.
 #[derive(Debug, Default)]
 pub struct SnapshotProvider {
     /// Maintains a map which allows for concurrent access to different `NippyJars`, over different
@@ -44,6 +97,12 @@ pub struct SnapshotProvider {
     highest_tracker: Option<watch::Receiver<Option<HighestSnapshots>>>,
     /// Directory where snapshots are located
     path: PathBuf,
+    /// Whether to hint the access pattern of every query to the kernel, see
+    /// [`Self::with_mmap_advice`].
+    mmap_advice: bool,
+    /// Whether to verify the checksum of every snapshot when it's loaded, see
+    /// [`Self::with_checksum_verification`].
+    verify_checksums: bool,
 }
 
 impl SnapshotProvider {
@@ -55,6 +114,8 @@ impl SnapshotProvider {
             snapshots_tx_index: Default::default(),
             highest_tracker: None,
             path: path.as_ref().to_path_buf(),
+            mmap_advice: false,
+            verify_checksums: false,
         }
     }
 
@@ -67,7 +128,92 @@ impl SnapshotProvider {
         self
     }
 
+    /// Hints the access pattern of every query to the kernel: range scans advise sequential
+    /// access of the memory mapped snapshots, while point lookups advise random access.
+    ///
+    /// The advice is only applied when it changes, so mixing both kinds of queries on the same
+    /// snapshot adds a syscall every time the kind of query switches.
+    pub fn with_mmap_advice(mut self, enabled: bool) -> Self {
+        self.mmap_advice = enabled;
+        self
+    }
+
+    /// Verifies the checksum recorded with every snapshot against its data file when the snapshot
+    /// is first used, and fails with [`ProviderError::SnapshotChecksumMismatch`] if
+    /// they differ.
+    ///
+    /// This catches corrupted files before serving wrong data, at the cost of reading every
+    /// snapshot in full when it's loaded. Snapshots without a checksum are not verified.
+    pub fn with_checksum_verification(mut self, enabled: bool) -> Self {
+        self.verify_checksums = enabled;
+        self
+    }
+
+    /// Returns a provider that looks up data outside the snapshots in the database instead of
+    /// failing, see [`SnapshotFallbackProvider`].
+    pub fn with_fallback<'a, TX>(
+        &'a self,
+        db_provider: &'a DatabaseProvider<TX>,
+    ) -> SnapshotFallbackProvider<'a, TX> {
+        SnapshotFallbackProvider::new(self, db_provider)
+    }
+
+    /// Applies the access pattern of a query to the snapshot, if enabled with
+    /// [`Self::with_mmap_advice`].
+    fn advise(
+        &self,
+        jar_provider: &SnapshotJarProvider<'_>,
+        advice: MmapAdvice,
+    ) -> ProviderResult<()> {
+        if self.mmap_advice {
+            jar_provider.mmap_handle().advise(advice)?;
+        }
+        Ok(())
+    }
+
+    /// Gets the [`SnapshotJarProvider`] of the requested segment and block for a point lookup.
+    fn lookup_provider_from_block(
+        &self,
+        segment: SnapshotSegment,
+        block: BlockNumber,
+    ) -> ProviderResult<SnapshotJarProvider<'_>> {
+        let jar_provider = self.get_segment_provider_from_block(segment, block, None)?;
+        self.advise(&jar_provider, MmapAdvice::Random)?;
+        Ok(jar_provider)
+    }
+
+    /// Gets the [`SnapshotJarProvider`] of the requested segment and transaction for a point
+    /// lookup.
+    fn lookup_provider_from_transaction(
+        &self,
+        segment: SnapshotSegment,
+        tx: TxNumber,
+    ) -> ProviderResult<SnapshotJarProvider<'_>> {
+        let jar_provider = self.get_segment_provider_from_transaction(segment, tx, None)?;
+        self.advise(&jar_provider, MmapAdvice::Random)?;
+        Ok(jar_provider)
+    }
+
+    /// Updates the inner block and transaction indexes with the snapshots found on disk.
+    pub fn update_index(&self) -> ProviderResult<()> {
+        let mut block_index = self.snapshots_block_index.write();
+        let mut tx_index = self.snapshots_tx_index.write();
+
+        for (segment, block_range, tx_range) in iter_snapshots(&self.path)? {
+            block_index
+                .entry(segment)
+                .or_default()
+                .insert(*block_range.end(), (block_range.clone(), tx_range.clone()));
+            tx_index.entry(segment).or_default().insert(*tx_range.end(), (block_range, tx_range));
+        }
+
+        Ok(())
+    }
+
     /// Gets the [`SnapshotJarProvider`] of the requested segment and block.
+    ///
+    /// If no snapshot has the block, the error tells whether it's below the lowest snapshot, in a
+    /// gap between snapshots, or above the highest snapshot.
     pub fn get_segment_provider_from_block(
         &self,
         segment: SnapshotSegment,
@@ -79,10 +225,21 @@ impl SnapshotProvider {
             || self.get_segment_ranges_from_block(segment, block),
             path,
         )?
-        .ok_or_else(|| ProviderError::MissingSnapshotBlock(segment, block))
+        .ok_or_else(|| match self.block_position(segment, block) {
+            SnapshotPosition::BelowLowest(start) => {
+                ProviderError::SnapshotBlockBelowLowest(segment, block, start)
+            }
+            SnapshotPosition::InGap => ProviderError::SnapshotBlockInGap(segment, block),
+            SnapshotPosition::Covered(_) | SnapshotPosition::AboveHighest => {
+                ProviderError::MissingSnapshotBlock(segment, block)
+            }
+        })
     }
 
     /// Gets the [`SnapshotJarProvider`] of the requested segment and transaction.
+    ///
+    /// If no snapshot has the transaction, the error tells whether it's below the lowest
+    /// snapshot, in a gap between snapshots, or above the highest snapshot.
     pub fn get_segment_provider_from_transaction(
         &self,
         segment: SnapshotSegment,
@@ -94,7 +251,32 @@ impl SnapshotProvider {
             || self.get_segment_ranges_from_transaction(segment, tx),
             path,
         )?
-        .ok_or_else(|| ProviderError::MissingSnapshotTx(segment, tx))
+        .ok_or_else(|| match self.transaction_position(segment, tx) {
+            SnapshotPosition::BelowLowest(start) => {
+                ProviderError::SnapshotTxBelowLowest(segment, tx, start)
+            }
+            SnapshotPosition::InGap => ProviderError::SnapshotTxInGap(segment, tx),
+            SnapshotPosition::Covered(_) | SnapshotPosition::AboveHighest => {
+                ProviderError::MissingSnapshotTx(segment, tx)
+            }
+        })
+    }
+
+    /// Gets the [`SnapshotJarProvider`] of the requested segment and row number.
+    ///
+    /// The number is a block number or a transaction number, depending on the segment. See
+    /// [`SnapshotSegment::is_block_based`].
+    pub fn get_segment_provider_from_number(
+        &self,
+        segment: SnapshotSegment,
+        number: u64,
+        path: Option<&Path>,
+    ) -> ProviderResult<SnapshotJarProvider<'_>> {
+        if segment.is_block_based() {
+            self.get_segment_provider_from_block(segment, number, path)
+        } else {
+            self.get_segment_provider_from_transaction(segment, number, path)
+        }
     }
 
     /// Gets the [`SnapshotJarProvider`] of the requested segment and block or transaction.
@@ -141,40 +323,53 @@ impl SnapshotProvider {
         if let Some(jar) = self.map.get(&key) {
             Ok(jar.into())
         } else {
-            self.map.insert(
-                key,
-                LoadedJar::new(NippyJar::load(
-                    &self.path.join(segment.filename(block_range, tx_range)),
-                )?)?,
-            );
+            let jar = NippyJar::load(&self.path.join(segment.filename(block_range, tx_range)))?;
+            if self.verify_checksums {
+                Self::verify_checksum(segment, &jar)?;
+            }
+            self.map.insert(key, LoadedJar::new(jar)?);
             Ok(self.map.get(&key).expect("qed").into())
         }
     }
 
+    /// Checks the checksum recorded with the snapshot, if any, against its data file. See
+    /// [`NippyJar::checksum`].
+    fn verify_checksum(
+        segment: SnapshotSegment,
+        jar: &NippyJar<SegmentHeader>,
+    ) -> ProviderResult<()> {
+        if let Some(checksum) = jar.checksum() {
+            if jar.compute_checksum()? != checksum {
+                return Err(ProviderError::SnapshotChecksumMismatch(segment, jar.data_path()))
+            }
+        }
+        Ok(())
+    }
+
+    /// Finds the position of the block in the snapshots of the segment, from the provider inner
+    /// block index.
+    fn block_position(&self, segment: SnapshotSegment, block: u64) -> SnapshotPosition {
+        SnapshotPosition::find(self.snapshots_block_index.read().get(&segment), block, |ranges| {
+            *ranges.0.start()
+        })
+    }
+
+    /// Finds the position of the transaction in the snapshots of the segment, from the provider
+    /// inner transaction index.
+    fn transaction_position(&self, segment: SnapshotSegment, tx: u64) -> SnapshotPosition {
+        SnapshotPosition::find(self.snapshots_tx_index.read().get(&segment), tx, |ranges| {
+            *ranges.1.start()
+        })
+    }
+
     /// Gets a snapshot segment's block range and transaction range from the provider inner block
     /// index.
     fn get_segment_ranges_from_block(
         &self,
         segment: SnapshotSegment,
         block: u64,
-    ) -> Option<(RangeInclusive<BlockNumber>, RangeInclusive<TxNumber>)> {
-        let snapshots = self.snapshots_block_index.read();
-        let segment_snapshots = snapshots.get(&segment)?;
-
-        // It's more probable that the request comes from a newer block height, so we iterate
-        // the snapshots in reverse.
-        let mut snapshots_rev_iter = segment_snapshots.iter().rev().peekable();
-
-        while let Some((block_end, tx_range)) = snapshots_rev_iter.next() {
-            // `unwrap_or(0) is safe here as it sets block_start to 0 if the iterator is empty,
-            // indicating the lowest height snapshot has been reached.
-            let block_start =
-                snapshots_rev_iter.peek().map(|(block_end, _)| *block_end + 1).unwrap_or(0);
-            if block_start <= block {
-                return Some((block_start..=*block_end, tx_range.clone()))
-            }
-        }
-        None
+    ) -> Option<SnapshotRanges> {
+        self.block_position(segment, block).into_ranges()
     }
 
     /// Gets a snapshot segment's block range and transaction range from the provider inner
@@ -183,21 +378,401 @@ impl SnapshotProvider {
         &self,
         segment: SnapshotSegment,
         tx: u64,
-    ) -> Option<(RangeInclusive<BlockNumber>, RangeInclusive<TxNumber>)> {
-        let snapshots = self.snapshots_tx_index.read();
-        let segment_snapshots = snapshots.get(&segment)?;
-
-        // It's more probable that the request comes from a newer tx height, so we iterate
-        // the snapshots in reverse.
-        let mut snapshots_rev_iter = segment_snapshots.iter().rev().peekable();
-
-        while let Some((tx_end, block_range)) = snapshots_rev_iter.next() {
-            let tx_start = snapshots_rev_iter.peek().map(|(tx_end, _)| *tx_end + 1).unwrap_or(0);
-            if tx_start <= tx {
-                return Some((block_range.clone(), tx_start..=*tx_end))
+    ) -> Option<SnapshotRanges> {
+        self.transaction_position(segment, tx).into_ranges()
+    }
+
+    /// Builds `n` sets of decompressors for the snapshot of the segment holding the block, and
+    /// runs `f` with them, e.g. to pre-warm the pool of an RPC server.
+    ///
+    /// The decompressors borrow the dictionaries of the loaded snapshot, so they can only be used
+    /// within `f`, with cursors of the given [`SnapshotJarProvider`]. Each set can be given to a
+    /// single cursor at a time, see [`SnapshotJarProvider::cursor_with_decompressors`].
+    pub fn build_decompressors<T>(
+        &self,
+        segment: SnapshotSegment,
+        block: BlockNumber,
+        n: usize,
+        f: impl for<'b> FnOnce(
+            &'b SnapshotJarProvider<'b>,
+            Vec<Vec<Decompressor<'b>>>,
+        ) -> ProviderResult<T>,
+    ) -> ProviderResult<T> {
+        let jar_provider = self.get_segment_provider_from_block(segment, block, None)?;
+        let decompressors = jar_provider.build_decompressors(n)?;
+        f(&jar_provider, decompressors)
+    }
+
+    /// Returns the raw, still compressed, header bytes of the given block number.
+    ///
+    /// Useful when copying data between snapshots, since it avoids decompressing values that will
+    /// be stored again.
+    pub fn raw_header_bytes(&self, num: BlockNumber) -> ProviderResult<Option<Vec<u8>>> {
+        self.lookup_provider_from_block(SnapshotSegment::Headers, num)?.raw_header_bytes(num)
+    }
+
+    /// Returns the header of the given block number, if its hash is `block_hash`.
+    ///
+    /// This is faster than [`HeaderProvider::header`] when the number is already known, e.g. from
+    /// the canonical headers, since the snapshot holding the header is found by its number instead
+    /// of checking the filters of every snapshot.
+    pub fn header_by_hash_and_number(
+        &self,
+        block_hash: &BlockHash,
+        num: BlockNumber,
+    ) -> ProviderResult<Option<Header>> {
+        self.lookup_provider_from_block(SnapshotSegment::Headers, num)?
+            .header_by_hash_and_number(block_hash, num)
+    }
+
+    /// Returns the raw, still compressed, transaction bytes of the given transaction number.
+    pub fn raw_tx_bytes(&self, num: TxNumber) -> ProviderResult<Option<Vec<u8>>> {
+        self.lookup_provider_from_transaction(SnapshotSegment::Transactions, num)?.raw_tx_bytes(num)
+    }
+
+    /// Returns the transactions of the given transaction numbers, in the same order as `ids`.
+    ///
+    /// Transaction numbers are sorted, so that a single cursor is used for every snapshot that
+    /// holds any of them. Transactions which are not in any snapshot are returned as `None`.
+    pub fn transactions_by_ids(
+        &self,
+        ids: &[TxNumber],
+    ) -> ProviderResult<Vec<Option<TransactionSigned>>> {
+        let mut sorted_ids = ids.iter().copied().enumerate().collect::<Vec<_>>();
+        sorted_ids.sort_unstable_by_key(|(_, id)| *id);
+        let mut sorted_ids = sorted_ids.into_iter().peekable();
+
+        let mut transactions = vec![None; ids.len()];
+        while let Some(&(_, first_id)) = sorted_ids.peek() {
+            let jar_provider = match self.get_segment_provider_from_transaction(
+                SnapshotSegment::Transactions,
+                first_id,
+                None,
+            ) {
+                Ok(jar_provider) => jar_provider,
+                Err(ProviderError::MissingSnapshotTx(_, _)) => {
+                    // Transactions past the highest snapshot
+                    break
+                }
+                Err(
+                    ProviderError::SnapshotTxBelowLowest(_, _, _) |
+                    ProviderError::SnapshotTxInGap(_, _),
+                ) => {
+                    sorted_ids.next();
+                    continue
+                }
+                Err(err) => return Err(err),
+            };
+
+            let tx_end = jar_provider.user_header().tx_end();
+            self.advise(&jar_provider, MmapAdvice::Random)?;
+            let mut cursor = jar_provider.cursor()?;
+            while let Some((idx, id)) = sorted_ids.next_if(|(_, id)| *id <= tx_end) {
+                // The cursor and its decompressors are reused across lookups
+                cursor.reset();
+                transactions[idx] = cursor
+                    .get_one::<TransactionMask<TransactionSignedNoHash>>(id.into())?
+                    .map(|tx| tx.with_hash());
+            }
+        }
+
+        Ok(transactions)
+    }
+
+    /// Returns the total difficulty of every block in the range, as stored in the headers
+    /// snapshots.
+    ///
+    /// Only the total difficulty column is decompressed. Blocks that no snapshot holds are
+    /// ignored.
+    pub fn header_td_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> ProviderResult<Vec<(BlockNumber, U256)>> {
+        let mut tds = Vec::new();
+        self.walk_blocks(SnapshotSegment::Headers, range, |jar_provider, blocks| {
+            tds.extend(jar_provider.header_td_range(blocks)?);
+            Ok(())
+        })?;
+        Ok(tds)
+    }
+
+    /// Returns the header of every block in the range paired with its total difficulty, as stored
+    /// in the headers snapshots.
+    ///
+    /// Both columns are read in a single pass over each snapshot, see
+    /// [SnapshotJarProvider::headers_with_td_range]. Blocks that no snapshot holds are ignored.
+    pub fn headers_with_td_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> ProviderResult<Vec<(Header, U256)>> {
+        let mut headers = Vec::new();
+        self.walk_blocks(SnapshotSegment::Headers, range, |jar_provider, blocks| {
+            headers.extend(jar_provider.headers_with_td_range(blocks)?);
+            Ok(())
+        })?;
+        Ok(headers)
+    }
+
+    /// Returns the transactions in the range paired with their senders, as stored in the
+    /// transactions snapshots.
+    ///
+    /// See [SnapshotJarProvider::transactions_with_senders_by_tx_range]. Transactions that no
+    /// snapshot holds are ignored.
+    pub fn transactions_with_senders_by_tx_range(
+        &self,
+        range: impl RangeBounds<TxNumber>,
+    ) -> ProviderResult<Vec<(TransactionSigned, Address)>> {
+        let mut txes = Vec::new();
+        self.walk_txs(SnapshotSegment::Transactions, range, |jar_provider, tx_numbers| {
+            txes.extend(jar_provider.transactions_with_senders_by_tx_range(tx_numbers)?);
+            Ok(())
+        })?;
+        Ok(txes)
+    }
+
+    /// Returns an iterator over the canonical hashes of the blocks in `start..end`.
+    ///
+    /// Unlike [BlockHashReader::canonical_hashes_range], headers are decompressed one at a time
+    /// and hashed with [Header::hash_slow], so memory usage does not grow with the range. The
+    /// iterator stops at the first missing header or after the first error.
+    pub fn canonical_hashes_iter(
+        &self,
+        start: BlockNumber,
+        end: BlockNumber,
+    ) -> impl Iterator<Item = ProviderResult<B256>> + '_ {
+        let mut numbers = start..end;
+        let mut current: Option<(SnapshotJarProvider<'_>, BlockNumber)> = None;
+
+        std::iter::from_fn(move || {
+            let number = numbers.next()?;
+
+            if current.as_ref().map_or(true, |(_, block_end)| number > *block_end) {
+                // Release the current snapshot before loading the next one.
+                current.take();
+                match self.get_segment_provider_from_block(SnapshotSegment::Headers, number, None) {
+                    Ok(jar_provider) => {
+                        if let Err(err) = self.advise(&jar_provider, MmapAdvice::Sequential) {
+                            numbers = end..end;
+                            return Some(Err(err))
+                        }
+                        let block_end = jar_provider.user_header().block_end();
+                        current = Some((jar_provider, block_end));
+                    }
+                    Err(ProviderError::MissingSnapshotBlock(_, _)) => {
+                        // Past the highest snapshot
+                        numbers = end..end;
+                        return None
+                    }
+                    Err(err) => {
+                        numbers = end..end;
+                        return Some(Err(err))
+                    }
+                }
             }
+
+            let (jar_provider, _) = current.as_ref().expect("qed");
+            match jar_provider.header_by_number(number) {
+                Ok(Some(header)) => Some(Ok(header.hash_slow())),
+                Ok(None) => {
+                    numbers = end..end;
+                    None
+                }
+                Err(err) => {
+                    numbers = end..end;
+                    Some(Err(err))
+                }
+            }
+        })
+    }
+
+    /// Returns an iterator over every transaction of the transactions snapshots, in order.
+    ///
+    /// Transactions are decompressed one at a time, so memory usage does not grow with the number
+    /// of transactions. The iterator stops at the first missing transaction or after the first
+    /// error.
+    pub fn iter_transactions(
+        &self,
+    ) -> impl Iterator<Item = ProviderResult<TransactionSigned>> + '_ {
+        let mut snapshots = self
+            .snapshots_tx_index
+            .read()
+            .get(&SnapshotSegment::Transactions)
+            .map(|segment_snapshots| segment_snapshots.values().cloned().collect::<Vec<_>>())
+            .unwrap_or_default()
+            .into_iter();
+        let mut current: Option<(SnapshotJarProvider<'_>, RangeInclusive<TxNumber>)> = None;
+
+        std::iter::from_fn(move || loop {
+            let next = current.as_mut().and_then(|(jar_provider, tx_numbers)| {
+                tx_numbers.next().map(|num| jar_provider.transaction_by_id(num))
+            });
+            match next {
+                Some(Ok(Some(tx))) => return Some(Ok(tx)),
+                Some(result) => {
+                    snapshots = Vec::new().into_iter();
+                    current = None;
+                    return result.err().map(Err)
+                }
+                None => {}
+            }
+
+            // Release the current snapshot before loading the next one.
+            current.take();
+            let (block_range, tx_range) = snapshots.next()?;
+            let jar_provider = match self.get_or_create_jar_provider(
+                SnapshotSegment::Transactions,
+                &block_range,
+                &tx_range,
+            ) {
+                Ok(jar_provider) => jar_provider,
+                Err(err) => {
+                    snapshots = Vec::new().into_iter();
+                    return Some(Err(err))
+                }
+            };
+            if let Err(err) = self.advise(&jar_provider, MmapAdvice::Sequential) {
+                snapshots = Vec::new().into_iter();
+                return Some(Err(err))
+            }
+            current = Some((jar_provider, tx_range));
+        })
+    }
+
+    /// Loads the pages of the snapshots of the segment that hold the rows in `range` into memory,
+    /// so a subsequent scan of the range doesn't stall on page faults, e.g. on cold archival
+    /// files.
+    ///
+    /// The range is of block numbers or transaction numbers, depending on the segment. See
+    /// [`SnapshotSegment::is_block_based`]. Rows that no snapshot holds are ignored.
+    pub fn warm(
+        &self,
+        segment: SnapshotSegment,
+        range: impl RangeBounds<u64>,
+    ) -> ProviderResult<()> {
+        if segment.is_block_based() {
+            self.walk_blocks(segment, range, |jar_provider, blocks| {
+                jar_provider.warm(blocks);
+                Ok(())
+            })
+        } else {
+            self.walk_txs(segment, range, |jar_provider, tx_numbers| {
+                jar_provider.warm(tx_numbers);
+                Ok(())
+            })
+        }
+    }
+
+    /// Calls `f` with the provider of every snapshot of the segment that holds blocks in `range`,
+    /// in order, and the blocks of the range it holds. See [`Self::walk_range`].
+    fn walk_blocks(
+        &self,
+        segment: SnapshotSegment,
+        range: impl RangeBounds<BlockNumber>,
+        f: impl FnMut(&SnapshotJarProvider<'_>, Range<BlockNumber>) -> ProviderResult<()>,
+    ) -> ProviderResult<()> {
+        self.walk_range(segment, &self.snapshots_block_index, |(blocks, _)| blocks, range, f)
+    }
+
+    /// Calls `f` with the provider of every snapshot of the segment that holds transactions in
+    /// `range`, in order, and the transactions of the range it holds. See [`Self::walk_range`].
+    fn walk_txs(
+        &self,
+        segment: SnapshotSegment,
+        range: impl RangeBounds<TxNumber>,
+        f: impl FnMut(&SnapshotJarProvider<'_>, Range<TxNumber>) -> ProviderResult<()>,
+    ) -> ProviderResult<()> {
+        self.walk_range(segment, &self.snapshots_tx_index, |(_, txs)| txs, range, f)
+    }
+
+    /// Calls `f` with the provider of every snapshot of the segment that holds rows in `range`,
+    /// in order, and the rows of the range it holds, where `rows` selects the range of rows of a
+    /// snapshot in the unit of the keys of `index`.
+    ///
+    /// Rows that no snapshot holds, i.e. below the lowest snapshot, in a gap between snapshots or
+    /// past the highest snapshot, are skipped.
+    fn walk_range(
+        &self,
+        segment: SnapshotSegment,
+        index: &RwLock<SegmentRanges>,
+        rows: impl Fn(&SnapshotRanges) -> &RangeInclusive<u64>,
+        range: impl RangeBounds<u64>,
+        mut f: impl FnMut(&SnapshotJarProvider<'_>, Range<u64>) -> ProviderResult<()>,
+    ) -> ProviderResult<()> {
+        let Range { start, end } = to_range(range);
+        if start >= end {
+            return Ok(())
+        }
+
+        // The index is released before the snapshots are loaded.
+        let snapshots = index.read().get(&segment).map_or_else(Vec::new, |segment_snapshots| {
+            segment_snapshots
+                .range(start..)
+                .map(|(_, ranges)| ranges.clone())
+                .take_while(|ranges| *rows(ranges).start() < end)
+                .collect()
+        });
+
+        for ranges in snapshots {
+            let snapshot_rows = rows(&ranges);
+            let jar_range = start.max(*snapshot_rows.start())..end.min(snapshot_rows.end() + 1);
+            let jar_provider = self.get_or_create_jar_provider(segment, &ranges.0, &ranges.1)?;
+            self.advise(&jar_provider, MmapAdvice::Sequential)?;
+            f(&jar_provider, jar_range)?;
         }
-        None
+
+        Ok(())
+    }
+
+    /// Returns `true` if a snapshot of the segment covers the block, according to the ranges of
+    /// its [`SegmentHeader`](reth_primitives::snapshot::SegmentHeader).
+    pub fn contains_block(&self, segment: SnapshotSegment, num: BlockNumber) -> bool {
+        matches!(self.block_position(segment, num), SnapshotPosition::Covered(_))
+    }
+
+    /// Returns `true` if a snapshot of the segment covers the transaction, according to the ranges
+    /// of its [`SegmentHeader`](reth_primitives::snapshot::SegmentHeader).
+    pub fn contains_tx(&self, segment: SnapshotSegment, num: TxNumber) -> bool {
+        matches!(self.transaction_position(segment, num), SnapshotPosition::Covered(_))
+    }
+
+    /// Returns `true` if the inclusion filter of a snapshot of the segment contains the hash.
+    ///
+    /// Filters can return false positives, but no false negatives: `false` means that no snapshot
+    /// of the segment has the hash. Fails if a snapshot of the segment has no filter.
+    pub fn may_contain_hash(&self, segment: SnapshotSegment, hash: &B256) -> ProviderResult<bool> {
+        Ok(self
+            .find_snapshot(segment, |jar_provider| {
+                Ok(jar_provider.contains(hash.as_slice())?.then_some(()))
+            })?
+            .is_some())
+    }
+
+    /// Returns the number of headers across all headers snapshots.
+    pub fn header_count(&self) -> u64 {
+        self.row_count(SnapshotSegment::Headers, |(block_range, _)| block_range)
+    }
+
+    /// Returns the number of transactions across all transactions snapshots.
+    pub fn tx_count(&self) -> u64 {
+        self.row_count(SnapshotSegment::Transactions, |(_, tx_range)| tx_range)
+    }
+
+    /// Returns the number of rows across all snapshots of the segment, where `rows` selects the
+    /// range of rows of a snapshot.
+    fn row_count(
+        &self,
+        segment: SnapshotSegment,
+        rows: impl Fn(&SnapshotRanges) -> &RangeInclusive<u64>,
+    ) -> u64 {
+        self.snapshots_block_index.read().get(&segment).map_or(0, |segment_snapshots| {
+            segment_snapshots
+                .values()
+                .map(|ranges| {
+                    let rows = rows(ranges);
+                    rows.end() - rows.start() + 1
+                })
+                .sum()
+        })
     }
 
     /// Gets the highest snapshot if it exists for a snapshot segment.
@@ -209,6 +784,9 @@ impl SnapshotProvider {
 
     /// Iterates through segment snapshots in reverse order, executing a function until it returns
     /// some object. Useful for finding objects by [`TxHash`] or [`BlockHash`].
+    ///
+    /// `func` returns `None` if the object is not in the given snapshot, in which case the next
+    /// one is checked. `None` is only returned if none of the snapshots has the object.
     pub fn find_snapshot<T>(
         &self,
         segment: SnapshotSegment,
@@ -218,19 +796,11 @@ impl SnapshotProvider {
         if let Some(segment_snapshots) = snapshots.get(&segment) {
             // It's more probable that the request comes from a newer block height, so we iterate
             // the snapshots in reverse.
-            let mut snapshots_rev_iter = segment_snapshots.iter().rev().peekable();
-
-            while let Some((block_end, tx_range)) = snapshots_rev_iter.next() {
-                // `unwrap_or(0) is safe here as it sets block_start to 0 if the iterator
-                // is empty, indicating the lowest height snapshot has been reached.
-                let block_start =
-                    snapshots_rev_iter.peek().map(|(block_end, _)| *block_end + 1).unwrap_or(0);
-
-                if let Some(res) = func(self.get_or_create_jar_provider(
-                    segment,
-                    &(block_start..=*block_end),
-                    tx_range,
-                )?)? {
+            for (block_range, tx_range) in segment_snapshots.values().rev() {
+                let jar_provider =
+                    self.get_or_create_jar_provider(segment, block_range, tx_range)?;
+                self.advise(&jar_provider, MmapAdvice::Random)?;
+                if let Some(res) = func(jar_provider)? {
                     return Ok(Some(res))
                 }
             }
@@ -246,18 +816,14 @@ impl HeaderProvider for SnapshotProvider {
             Ok(jar_provider
                 .cursor()?
                 .get_two::<HeaderMask<Header, BlockHash>>(block_hash.into())?
-                .and_then(|(header, hash)| {
-                    if &hash == block_hash {
-                        return Some(header)
-                    }
-                    None
-                }))
+                // A hash mismatch means the filter returned a false positive: the header is not
+                // in this snapshot, but it might still be in another one.
+                .and_then(|(header, hash)| (&hash == block_hash).then_some(header)))
         })
     }
 
     fn header_by_number(&self, num: BlockNumber) -> ProviderResult<Option<Header>> {
-        self.get_segment_provider_from_block(SnapshotSegment::Headers, num, None)?
-            .header_by_number(num)
+        self.lookup_provider_from_block(SnapshotSegment::Headers, num)?.header_by_number(num)
     }
 
     fn header_td(&self, block_hash: &BlockHash) -> ProviderResult<Option<U256>> {
@@ -270,17 +836,30 @@ impl HeaderProvider for SnapshotProvider {
     }
 
     fn header_td_by_number(&self, num: BlockNumber) -> ProviderResult<Option<U256>> {
-        self.get_segment_provider_from_block(SnapshotSegment::Headers, num, None)?
-            .header_td_by_number(num)
+        self.lookup_provider_from_block(SnapshotSegment::Headers, num)?.header_td_by_number(num)
     }
 
-    fn headers_range(&self, _range: impl RangeBounds<BlockNumber>) -> ProviderResult<Vec<Header>> {
-        todo!();
+    fn headers_range(&self, range: impl RangeBounds<BlockNumber>) -> ProviderResult<Vec<Header>> {
+        let mut headers = Vec::new();
+        self.walk_blocks(SnapshotSegment::Headers, range, |jar_provider, blocks| {
+            headers.extend(jar_provider.headers_range(blocks)?);
+            Ok(())
+        })?;
+        Ok(headers)
     }
 
     fn sealed_header(&self, num: BlockNumber) -> ProviderResult<Option<SealedHeader>> {
-        self.get_segment_provider_from_block(SnapshotSegment::Headers, num, None)?
-            .sealed_header(num)
+        match self.lookup_provider_from_block(SnapshotSegment::Headers, num) {
+            // The hash is stored next to the header, so it's not computed again
+            Ok(jar_provider) => jar_provider.sealed_header(num),
+            // Not held by any snapshot
+            Err(
+                ProviderError::MissingSnapshotBlock(_, _) |
+                ProviderError::SnapshotBlockBelowLowest(_, _, _) |
+                ProviderError::SnapshotBlockInGap(_, _),
+            ) => Ok(None),
+            Err(err) => Err(err),
+        }
     }
 
     fn sealed_headers_while(
@@ -294,15 +873,20 @@ impl HeaderProvider for SnapshotProvider {
 
 impl BlockHashReader for SnapshotProvider {
     fn block_hash(&self, num: u64) -> ProviderResult<Option<B256>> {
-        self.get_segment_provider_from_block(SnapshotSegment::Headers, num, None)?.block_hash(num)
+        self.lookup_provider_from_block(SnapshotSegment::Headers, num)?.block_hash(num)
     }
 
     fn canonical_hashes_range(
         &self,
-        _start: BlockNumber,
-        _end: BlockNumber,
+        start: BlockNumber,
+        end: BlockNumber,
     ) -> ProviderResult<Vec<B256>> {
-        todo!()
+        let mut hashes = Vec::with_capacity(end.saturating_sub(start) as usize);
+        self.walk_blocks(SnapshotSegment::Headers, start..end, |jar_provider, blocks| {
+            hashes.extend(jar_provider.canonical_hashes_range(blocks.start, blocks.end)?);
+            Ok(())
+        })?;
+        Ok(hashes)
     }
 }
 
@@ -312,15 +896,21 @@ impl BlockNumReader for SnapshotProvider {
     }
 
     fn best_block_number(&self) -> ProviderResult<BlockNumber> {
-        todo!()
+        // Highest block end across the snapshots of every segment
+        self.snapshots_block_index
+            .read()
+            .values()
+            .filter_map(|segment_snapshots| segment_snapshots.keys().next_back().copied())
+            .max()
+            .ok_or(ProviderError::BestBlockNotFound)
     }
 
     fn last_block_number(&self) -> ProviderResult<BlockNumber> {
         todo!()
     }
 
-    fn block_number(&self, _hash: B256) -> ProviderResult<Option<BlockNumber>> {
-        todo!()
+    fn block_number(&self, hash: B256) -> ProviderResult<Option<BlockNumber>> {
+        self.find_snapshot(SnapshotSegment::Headers, |jar_provider| jar_provider.block_number(hash))
     }
 }
 
@@ -341,7 +931,7 @@ impl TransactionsProvider for SnapshotProvider {
     }
 
     fn transaction_by_id(&self, num: TxNumber) -> ProviderResult<Option<TransactionSigned>> {
-        self.get_segment_provider_from_transaction(SnapshotSegment::Transactions, num, None)?
+        self.lookup_provider_from_transaction(SnapshotSegment::Transactions, num)?
             .transaction_by_id(num)
     }
 
@@ -349,7 +939,7 @@ impl TransactionsProvider for SnapshotProvider {
         &self,
         num: TxNumber,
     ) -> ProviderResult<Option<TransactionSignedNoHash>> {
-        self.get_segment_provider_from_transaction(SnapshotSegment::Transactions, num, None)?
+        self.lookup_provider_from_transaction(SnapshotSegment::Transactions, num)?
             .transaction_by_id_no_hash(num)
     }
 
@@ -383,9 +973,14 @@ impl TransactionsProvider for SnapshotProvider {
 
     fn transactions_by_block_range(
         &self,
-        _range: impl RangeBounds<BlockNumber>,
+        range: impl RangeBounds<BlockNumber>,
     ) -> ProviderResult<Vec<Vec<TransactionSigned>>> {
-        todo!()
+        let mut txes = Vec::new();
+        self.walk_blocks(SnapshotSegment::Transactions, range, |jar_provider, blocks| {
+            txes.extend(jar_provider.transactions_by_block_range(blocks)?);
+            Ok(())
+        })?;
+        Ok(txes)
     }
 
     fn senders_by_tx_range(
@@ -397,12 +992,26 @@ impl TransactionsProvider for SnapshotProvider {
 
     fn transactions_by_tx_range(
         &self,
-        _range: impl RangeBounds<TxNumber>,
+        range: impl RangeBounds<TxNumber>,
     ) -> ProviderResult<Vec<reth_primitives::TransactionSignedNoHash>> {
-        todo!()
+        let mut txes = Vec::new();
+        self.walk_txs(SnapshotSegment::Transactions, range, |jar_provider, tx_numbers| {
+            txes.extend(jar_provider.transactions_by_tx_range(tx_numbers)?);
+            Ok(())
+        })?;
+        Ok(txes)
     }
 
     fn transaction_sender(&self, id: TxNumber) -> ProviderResult<Option<Address>> {
-        Ok(self.transaction_by_id_no_hash(id)?.and_then(|tx| tx.recover_signer()))
+        match self.lookup_provider_from_transaction(SnapshotSegment::Transactions, id) {
+            Ok(jar_provider) => jar_provider.transaction_sender(id),
+            // Not held by any snapshot
+            Err(
+                ProviderError::MissingSnapshotTx(_, _) |
+                ProviderError::SnapshotTxBelowLowest(_, _, _) |
+                ProviderError::SnapshotTxInGap(_, _),
+            ) => Ok(None),
+            Err(err) => Err(err),
+        }
     }
 }
@@ -4,6 +4,9 @@ pub use manager::SnapshotProvider;
 mod jar;
 pub use jar::SnapshotJarProvider;
 
+mod fallback;
+pub use fallback::SnapshotFallbackProvider;
+
 use reth_interfaces::provider::ProviderResult;
 use reth_nippy_jar::NippyJar;
 use reth_primitives::{snapshot::SegmentHeader, SnapshotSegment};
@@ -41,17 +44,38 @@ impl Deref for LoadedJar {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::{test_utils::create_test_provider_factory, HeaderProvider};
+    use crate::{
+        test_utils::create_test_provider_factory, BlockHashReader, BlockNumReader, HeaderProvider,
+        ProviderFactory, TransactionsProvider,
+    };
     use rand::{self, seq::SliceRandom};
     use reth_db::{
         cursor::DbCursorRO,
-        snapshot::create_snapshot_T1_T2_T3,
+        database::Database,
+        models::StoredBlockBodyIndices,
+        snapshot::{
+            append_snapshot_T1, append_snapshot_T1_T2_T3, create_snapshot_T1,
+            create_snapshot_T1_T2, create_snapshot_T1_T2_T3, HeaderMask, SnapshotCursor,
+            TransactionMask,
+        },
+        table::Decompress,
         transaction::{DbTx, DbTxMut},
-        CanonicalHeaders, HeaderNumbers, HeaderTD, Headers, RawTable,
+        BlockBodyIndices, CanonicalHeaders, HeaderNumbers, HeaderTD, Headers, RawTable,
+        Transactions, TxSenders,
+    };
+    use reth_interfaces::{
+        provider::ProviderError,
+        test_utils::generators::{self, random_header, random_header_range, random_signed_tx},
+    };
+    use reth_nippy_jar::{compression::Compression, ColumnResult, MmapAdvice, NippyJar};
+    use reth_primitives::{
+        BlockHash, BlockNumber, Header, SealedHeader, TransactionSigned, TransactionSignedNoHash,
+        TxNumber, B256, U256,
+    };
+    use std::{
+        ops::{Bound, RangeInclusive},
+        path::Path,
     };
-    use reth_interfaces::test_utils::generators::{self, random_header_range};
-    use reth_nippy_jar::NippyJar;
-    use reth_primitives::{BlockNumber, B256, U256};
 
     #[test]
     fn test_snap() {
@@ -132,11 +156,13 @@ mod test {
         {
             let db_provider = factory.provider().unwrap();
             let manager = SnapshotProvider::new(snap_path.path());
+            manager.update_index().unwrap();
             let jar_provider = manager
                 .get_segment_provider_from_block(SnapshotSegment::Headers, 0, Some(&snap_file))
                 .unwrap();
 
             assert!(!headers.is_empty());
+            assert_eq!(manager.block_number(B256::random()).unwrap(), None);
 
             // Shuffled for chaos.
             headers.shuffle(&mut generators::rng());
@@ -145,6 +171,8 @@ mod test {
                 let header_hash = header.hash();
                 let header = header.unseal();
 
+                assert!(manager.may_contain_hash(SnapshotSegment::Headers, &header_hash).unwrap());
+
                 // Compare Header
                 assert_eq!(header, db_provider.header(&header_hash).unwrap().unwrap());
                 assert_eq!(header, jar_provider.header(&header_hash).unwrap().unwrap());
@@ -154,7 +182,1382 @@ mod test {
                     db_provider.header_td(&header_hash).unwrap().unwrap(),
                     jar_provider.header_td(&header_hash).unwrap().unwrap()
                 );
+
+                // Compare BlockNumber
+                assert_eq!(
+                    db_provider.block_number(header_hash).unwrap(),
+                    manager.block_number(header_hash).unwrap()
+                );
+
+                // Compare raw Header bytes, once decompressed
+                let raw = manager.raw_header_bytes(header.number).unwrap().unwrap();
+                let compressor = jar_provider.compressor().unwrap();
+                assert_eq!(
+                    header,
+                    Header::decompress(compressor.decompress(&raw).unwrap()).unwrap()
+                );
+            }
+
+            // Row counts of the snapshotted ranges
+            assert_eq!(manager.header_count(), row_count);
+            assert_eq!(manager.tx_count(), 0);
+
+            // Coverage of the snapshotted ranges
+            for num in [0, row_count / 2, row_count - 1] {
+                assert!(manager.contains_block(SnapshotSegment::Headers, num));
+                assert!(!manager.contains_block(SnapshotSegment::Transactions, num));
+            }
+            assert!(!manager.contains_block(SnapshotSegment::Headers, row_count));
+            assert!(!manager.contains_tx(SnapshotSegment::Transactions, 0));
+
+            // Compare canonical hashes, read from the hashes column and computed from headers
+            let hashes = manager.canonical_hashes_range(0, row_count).unwrap();
+            assert_eq!(hashes.len(), row_count as usize);
+            assert_eq!(hashes, db_provider.canonical_hashes_range(0, row_count).unwrap());
+            assert_eq!(
+                manager
+                    .canonical_hashes_iter(0, row_count)
+                    .collect::<ProviderResult<Vec<_>>>()
+                    .unwrap(),
+                hashes
+            );
+            assert_eq!(
+                manager.canonical_hashes_iter(10, 20).collect::<ProviderResult<Vec<_>>>().unwrap(),
+                hashes[10..20]
+            );
+
+            // Compare total difficulties, only decompressing their column
+            let tds = manager.header_td_range(..).unwrap();
+            assert_eq!(tds.len(), row_count as usize);
+            for (number, td) in &tds {
+                assert_eq!(Some(*td), db_provider.header_td_by_number(*number).unwrap());
+            }
+            assert_eq!(manager.header_td_range(10..=19).unwrap(), tds[10..20]);
+
+            // Compare headers with their total difficulties against separate range scans
+            let headers_with_td = manager.headers_with_td_range(..).unwrap();
+            assert_eq!(
+                headers_with_td,
+                manager
+                    .headers_range(..)
+                    .unwrap()
+                    .into_iter()
+                    .zip(tds.into_iter().map(|(_, td)| td))
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(manager.headers_with_td_range(10..=19).unwrap(), headers_with_td[10..20]);
+            assert!(manager.headers_with_td_range(row_count..).unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_snap_segment_dispatch() {
+        // Ranges
+        let block_range = 0..=9;
+        let tx_range = 0..=19;
+
+        // Data sources
+        let factory = create_test_provider_factory();
+        let snap_path = tempfile::tempdir().unwrap();
+
+        let mut provider_rw = factory.provider_rw().unwrap();
+        let tx = provider_rw.tx_mut();
+        let mut rng = generators::rng();
+        for header in random_header_range(&mut rng, 0..10, B256::random()) {
+            tx.put::<CanonicalHeaders>(header.number, header.hash()).unwrap();
+            tx.put::<Headers>(header.number, header.clone().unseal()).unwrap();
+            tx.put::<HeaderTD>(header.number, U256::from(header.number).into()).unwrap();
+        }
+        for tx_num in tx_range.clone() {
+            tx.put::<Transactions>(tx_num, random_signed_tx(&mut rng).into()).unwrap();
+        }
+        provider_rw.commit().unwrap();
+
+        // Create one snapshot per segment, in the same directory
+        {
+            let provider = factory.provider().unwrap();
+            let none_vec = || None::<Vec<std::vec::IntoIter<Vec<u8>>>>;
+            let no_keys = || None::<std::vec::IntoIter<ColumnResult<B256>>>;
+
+            let segment = SnapshotSegment::Headers;
+            let mut nippy_jar = NippyJar::new(
+                3,
+                &snap_path.path().join(segment.filename(&block_range, &block_range)),
+                SegmentHeader::new(block_range.clone(), block_range.clone(), segment),
+            );
+            create_snapshot_T1_T2_T3::<
+                Headers,
+                HeaderTD,
+                CanonicalHeaders,
+                BlockNumber,
+                SegmentHeader,
+            >(
+                provider.tx_ref(),
+                block_range.clone(),
+                None,
+                none_vec(),
+                no_keys(),
+                10,
+                &mut nippy_jar,
+            )
+            .unwrap();
+
+            let segment = SnapshotSegment::Transactions;
+            let mut nippy_jar = NippyJar::new(
+                1,
+                &snap_path.path().join(segment.filename(&block_range, &tx_range)),
+                SegmentHeader::new(block_range.clone(), tx_range.clone(), segment),
+            );
+            create_snapshot_T1::<Transactions, TxNumber, SegmentHeader>(
+                provider.tx_ref(),
+                tx_range.clone(),
+                None,
+                none_vec(),
+                no_keys(),
+                20,
+                &mut nippy_jar,
+            )
+            .unwrap();
+        }
+
+        let db_provider = factory.provider().unwrap();
+        let manager = SnapshotProvider::new(snap_path.path());
+        manager.update_index().unwrap();
+
+        // Headers are routed by block number
+        for num in block_range {
+            let jar_provider = manager
+                .get_segment_provider_from_number(SnapshotSegment::Headers, num, None)
+                .unwrap();
+            assert_eq!(jar_provider.user_header().start(), 0);
+            assert_eq!(
+                jar_provider.header_by_number(num).unwrap(),
+                db_provider.header_by_number(num).unwrap()
+            );
+        }
+
+        // Transactions are routed by transaction number, past the end of the block range
+        for num in tx_range.clone() {
+            let jar_provider = manager
+                .get_segment_provider_from_number(SnapshotSegment::Transactions, num, None)
+                .unwrap();
+            assert_eq!(jar_provider.user_header().tx_end(), 19);
+            assert_eq!(
+                jar_provider.transaction_by_id(num).unwrap(),
+                db_provider.transaction_by_id(num).unwrap()
+            );
+        }
+
+        // Coverage is checked per segment, by block or transaction number
+        assert!(manager.contains_block(SnapshotSegment::Transactions, 9));
+        assert!(!manager.contains_block(SnapshotSegment::Transactions, 10));
+        assert!(manager.contains_tx(SnapshotSegment::Transactions, 0));
+        assert!(manager.contains_tx(SnapshotSegment::Transactions, 19));
+        assert!(!manager.contains_tx(SnapshotSegment::Transactions, 20));
+
+        // Hashes can't be checked without a filter
+        assert!(manager.may_contain_hash(SnapshotSegment::Transactions, &B256::random()).is_err());
+
+        // Transactions are streamed in order
+        let txs = manager.iter_transactions().collect::<ProviderResult<Vec<_>>>().unwrap();
+        assert_eq!(txs.len() as u64, manager.tx_count());
+        assert_eq!(
+            txs,
+            db_provider
+                .transactions_by_tx_range(tx_range.clone())
+                .unwrap()
+                .into_iter()
+                .map(|tx| tx.with_hash())
+                .collect::<Vec<_>>()
+        );
+
+        // Sealed headers match the database, and are missing past the highest snapshot
+        for num in [0, 3, 9] {
+            let sealed_header = manager.sealed_header(num).unwrap();
+            assert_eq!(sealed_header, db_provider.sealed_header(num).unwrap());
+            assert_eq!(
+                sealed_header.map(|header| header.hash),
+                db_provider.block_hash(num).unwrap()
+            );
+        }
+        assert_eq!(manager.sealed_header(10).unwrap(), None);
+
+        // No receipts snapshot
+        assert!(matches!(
+            manager.get_segment_provider_from_number(SnapshotSegment::Receipts, 0, None),
+            Err(ProviderError::MissingSnapshotTx(SnapshotSegment::Receipts, 0))
+        ));
+
+        // The access pattern is only hinted when enabled, and doesn't change query results
+        let advice = |manager: &SnapshotProvider, segment| {
+            manager
+                .get_segment_provider_from_number(segment, 0, None)
+                .unwrap()
+                .mmap_handle()
+                .advice()
+        };
+        let hashes = manager.canonical_hashes_range(0, 10).unwrap();
+        assert_eq!(advice(&manager, SnapshotSegment::Headers), MmapAdvice::Normal);
+
+        let manager = SnapshotProvider::new(snap_path.path()).with_mmap_advice(true);
+        manager.update_index().unwrap();
+        assert_eq!(advice(&manager, SnapshotSegment::Headers), MmapAdvice::Normal);
+        assert_eq!(manager.canonical_hashes_range(0, 10).unwrap(), hashes);
+        assert_eq!(advice(&manager, SnapshotSegment::Headers), MmapAdvice::Sequential);
+        assert_eq!(manager.header_by_number(5).unwrap(), db_provider.header_by_number(5).unwrap());
+        assert_eq!(advice(&manager, SnapshotSegment::Headers), MmapAdvice::Random);
+
+        assert_eq!(
+            manager.transactions_by_tx_range(0..20).unwrap(),
+            db_provider.transactions_by_tx_range(0..20).unwrap()
+        );
+        assert_eq!(advice(&manager, SnapshotSegment::Transactions), MmapAdvice::Sequential);
+        assert_eq!(
+            manager.transaction_by_id(3).unwrap(),
+            db_provider.transaction_by_id(3).unwrap()
+        );
+        assert_eq!(advice(&manager, SnapshotSegment::Transactions), MmapAdvice::Random);
+    }
+
+    #[test]
+    fn test_snap_append() {
+        // Ranges
+        let row_count = 100u64;
+        let first_range = 0..=49;
+        let second_range = 50..=(row_count - 1);
+        let full_range = 0..=(row_count - 1);
+        let segment = SnapshotSegment::Headers;
+
+        // Data sources
+        let factory = create_test_provider_factory();
+        let snap_path = tempfile::tempdir().unwrap();
+        let first_file = snap_path.path().join(segment.filename(&first_range, &first_range));
+        let full_file = snap_path.path().join(segment.filename(&full_range, &full_range));
+
+        let headers = random_header_range(&mut generators::rng(), 0..row_count, B256::random());
+
+        let mut provider_rw = factory.provider_rw().unwrap();
+        let tx = provider_rw.tx_mut();
+        let mut td = U256::ZERO;
+        for header in headers.clone() {
+            td += header.header.difficulty;
+            let hash = header.hash();
+
+            tx.put::<CanonicalHeaders>(header.number, hash).unwrap();
+            tx.put::<Headers>(header.number, header.clone().unseal()).unwrap();
+            tx.put::<HeaderTD>(header.number, td.into()).unwrap();
+            tx.put::<HeaderNumbers>(hash, header.number).unwrap();
+        }
+        provider_rw.commit().unwrap();
+
+        let keys = |count: usize| {
+            headers.iter().take(count).map(|header| -> ColumnResult<B256> { Ok(header.hash()) })
+        };
+
+        {
+            let provider = factory.provider().unwrap();
+            let none_vec = None::<Vec<std::vec::IntoIter<Vec<u8>>>>;
+
+            // Create a snapshot for the first half of the blocks
+            let mut nippy_jar = NippyJar::new(
+                3,
+                first_file.as_path(),
+                SegmentHeader::new(first_range.clone(), first_range.clone(), segment),
+            )
+            .with_zstd(false, 0)
+            .with_cuckoo_filter(row_count as usize)
+            .with_fmph();
+            create_snapshot_T1_T2_T3::<
+                Headers,
+                HeaderTD,
+                CanonicalHeaders,
+                BlockNumber,
+                SegmentHeader,
+            >(
+                provider.tx_ref(),
+                first_range.clone(),
+                None,
+                none_vec,
+                Some(keys(50)),
+                50,
+                &mut nippy_jar,
+            )
+            .unwrap();
+
+            // Append the second half to it, which renames it after the extended range
+            let mut nippy_jar = NippyJar::<SegmentHeader>::load(&first_file).unwrap();
+            append_snapshot_T1_T2_T3::<Headers, HeaderTD, CanonicalHeaders>(
+                provider.tx_ref(),
+                second_range.clone(),
+                second_range,
+                None,
+                Some(keys(row_count as usize)),
+                50,
+                &mut nippy_jar,
+            )
+            .unwrap();
+            assert_eq!(nippy_jar.data_path(), full_file);
+        }
+        assert!(!first_file.exists());
+
+        let db_provider = factory.provider().unwrap();
+        let manager = SnapshotProvider::new(snap_path.path());
+        manager.update_index().unwrap();
+
+        for header in headers {
+            let header_hash = header.hash();
+            let jar_provider =
+                manager.get_segment_provider_from_block(segment, header.number, None).unwrap();
+            assert_eq!(jar_provider.user_header().block_end(), row_count - 1);
+
+            // Compare by number
+            assert_eq!(
+                manager.header_by_number(header.number).unwrap(),
+                db_provider.header_by_number(header.number).unwrap()
+            );
+            assert_eq!(
+                manager.header_td_by_number(header.number).unwrap(),
+                db_provider.header_td_by_number(header.number).unwrap()
+            );
+
+            // Compare by hash, through the rebuilt filter and PHF
+            assert_eq!(manager.block_number(header_hash).unwrap(), Some(header.number));
+            assert_eq!(manager.header(&header_hash).unwrap(), Some(header.unseal()));
+        }
+    }
+
+    #[test]
+    fn test_snap_header_filter_false_positive() {
+        let segment = SnapshotSegment::Headers;
+        let factory = create_test_provider_factory();
+        let snap_path = tempfile::tempdir().unwrap();
+        let mut rng = generators::rng();
+
+        let insert_headers = |headers: &[SealedHeader]| {
+            let mut provider_rw = factory.provider_rw().unwrap();
+            let tx = provider_rw.tx_mut();
+            for header in headers {
+                tx.put::<CanonicalHeaders>(header.number, header.hash()).unwrap();
+                tx.put::<Headers>(header.number, header.header.clone()).unwrap();
+                tx.put::<HeaderTD>(header.number, U256::from(header.number).into()).unwrap();
+                tx.put::<HeaderNumbers>(header.hash(), header.number).unwrap();
             }
+            provider_rw.commit().unwrap();
+        };
+        let create_snapshot = |range: RangeInclusive<BlockNumber>, headers: &[SealedHeader]| {
+            let file = snap_path.path().join(segment.filename(&range, &range));
+            let mut nippy_jar = NippyJar::new(
+                3,
+                file.as_path(),
+                SegmentHeader::new(range.clone(), range.clone(), segment),
+            )
+            .with_zstd(false, 0)
+            .with_cuckoo_filter(headers.len())
+            .with_fmph();
+
+            let provider = factory.provider().unwrap();
+            create_snapshot_T1_T2_T3::<
+                Headers,
+                HeaderTD,
+                CanonicalHeaders,
+                BlockNumber,
+                SegmentHeader,
+            >(
+                provider.tx_ref(),
+                range,
+                None,
+                None::<Vec<std::vec::IntoIter<Vec<u8>>>>,
+                Some(headers.iter().map(|header| -> ColumnResult<B256> { Ok(header.hash()) })),
+                headers.len(),
+                &mut nippy_jar,
+            )
+            .unwrap();
+            file
+        };
+
+        // Snapshot the newer blocks first
+        let newer_headers = random_header_range(&mut rng, 100..200, B256::random());
+        insert_headers(&newer_headers);
+        let newer_file = create_snapshot(100..=199, &newer_headers);
+
+        // Find a header that is not part of the newer snapshot, but whose hash still passes its
+        // filter and PHF
+        let newer_jar = NippyJar::<SegmentHeader>::load(&newer_file).unwrap();
+        let mut cursor = SnapshotCursor::new(&newer_jar, newer_jar.open_data().unwrap()).unwrap();
+        let false_positive = std::iter::repeat_with(|| random_header(&mut rng, 50, None))
+            .take(1_000_000)
+            .find(|header| {
+                let hash = header.hash();
+                cursor
+                    .get_two::<HeaderMask<Header, BlockHash>>((&hash).into())
+                    .unwrap()
+                    .is_some_and(|(_, found)| found != hash)
+            })
+            .expect("no false positive found");
+        let false_positive_hash = false_positive.hash();
+
+        let mut older_headers = random_header_range(&mut rng, 0..100, B256::random());
+        older_headers[50] = false_positive.clone();
+        insert_headers(&older_headers);
+        create_snapshot(0..=99, &older_headers);
+
+        let manager = SnapshotProvider::new(snap_path.path());
+        manager.update_index().unwrap();
+
+        // The newer snapshot doesn't have the header...
+        {
+            let jar_provider = manager.get_segment_provider_from_block(segment, 150, None).unwrap();
+            assert_eq!(jar_provider.header(&false_positive_hash).unwrap(), None);
+            assert_eq!(jar_provider.header_td(&false_positive_hash).unwrap(), None);
+        }
+
+        // ...so the lookup goes on to the older one
+        assert_eq!(
+            manager.header(&false_positive_hash).unwrap(),
+            Some(false_positive.header.clone())
+        );
+        assert_eq!(manager.header_td(&false_positive_hash).unwrap(), Some(U256::from(50)));
+
+        // Headers absent from every snapshot are still reported as such
+        assert_eq!(manager.header(&B256::random()).unwrap(), None);
+
+        // Lookups with a known number go straight to the row, and check its hash
+        for header in [&older_headers[10], &false_positive, &newer_headers[20]] {
+            assert_eq!(
+                manager.header_by_hash_and_number(&header.hash(), header.number).unwrap(),
+                manager.header_by_number(header.number).unwrap()
+            );
+        }
+        assert_eq!(manager.header_by_hash_and_number(&false_positive_hash, 150).unwrap(), None);
+        assert_eq!(
+            manager.header_by_hash_and_number(&newer_headers[20].hash(), 121).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_snap_range_bounds() {
+        let segment = SnapshotSegment::Headers;
+        let range = 100..=199;
+        let factory = create_test_provider_factory();
+        let snap_path = tempfile::tempdir().unwrap();
+        let snap_file = snap_path.path().join(segment.filename(&range, &range));
+
+        let headers = random_header_range(&mut generators::rng(), 100..200, B256::random());
+        let mut provider_rw = factory.provider_rw().unwrap();
+        let tx = provider_rw.tx_mut();
+        for header in headers.iter() {
+            tx.put::<CanonicalHeaders>(header.number, header.hash()).unwrap();
+            tx.put::<Headers>(header.number, header.header.clone()).unwrap();
+            tx.put::<HeaderTD>(header.number, U256::from(header.number).into()).unwrap();
         }
+        provider_rw.commit().unwrap();
+
+        let mut nippy_jar = NippyJar::new(
+            3,
+            snap_file.as_path(),
+            SegmentHeader::new(range.clone(), range.clone(), segment),
+        );
+        let provider = factory.provider().unwrap();
+        create_snapshot_T1_T2_T3::<Headers, HeaderTD, CanonicalHeaders, BlockNumber, SegmentHeader>(
+            provider.tx_ref(),
+            range,
+            None,
+            None::<Vec<std::vec::IntoIter<Vec<u8>>>>,
+            None::<std::vec::IntoIter<ColumnResult<B256>>>,
+            headers.len(),
+            &mut nippy_jar,
+        )
+        .unwrap();
+
+        let manager = SnapshotProvider::new(snap_path.path());
+        manager.update_index().unwrap();
+        let jar_provider = manager.get_segment_provider_from_block(segment, 100, None).unwrap();
+
+        let blocks = |range: std::ops::Range<BlockNumber>| {
+            range.map(|num| (num, U256::from(num))).collect::<Vec<_>>()
+        };
+
+        // a..b
+        assert_eq!(jar_provider.resolve_bounds(110..120), (10, 20));
+        assert_eq!(jar_provider.header_td_range(110..120).unwrap(), blocks(110..120));
+        assert_eq!(jar_provider.resolve_bounds(50..150), (0, 50));
+        assert_eq!(jar_provider.resolve_bounds(120..110), (20, 20));
+        assert_eq!(jar_provider.resolve_bounds(50..60), (0, 0));
+        assert_eq!(jar_provider.resolve_bounds(250..260), (100, 100));
+        assert!(jar_provider.header_td_range(250..260).unwrap().is_empty());
+
+        // a..=b
+        assert_eq!(jar_provider.resolve_bounds(110..=120), (10, 21));
+        assert_eq!(jar_provider.header_td_range(110..=120).unwrap(), blocks(110..121));
+        assert_eq!(jar_provider.resolve_bounds(150..=u64::MAX), (50, 100));
+
+        // ..b
+        assert_eq!(jar_provider.resolve_bounds(..120), (0, 20));
+        assert_eq!(jar_provider.header_td_range(..120).unwrap(), blocks(100..120));
+
+        // a..
+        assert_eq!(jar_provider.resolve_bounds(150..), (50, 100));
+        assert_eq!(jar_provider.header_td_range(150..).unwrap(), blocks(150..200));
+
+        // ..
+        assert_eq!(jar_provider.resolve_bounds(..), (0, 100));
+        assert_eq!(jar_provider.header_td_range(..).unwrap(), blocks(100..200));
+        assert_eq!(jar_provider.headers_range(..).unwrap().len(), headers.len());
+
+        // Excluded start bounds
+        assert_eq!(
+            jar_provider.resolve_bounds((Bound::Excluded(110), Bound::Included(120))),
+            (11, 21)
+        );
+    }
+
+    #[test]
+    fn test_snap_best_block_number() {
+        let snap_path = tempfile::tempdir().unwrap();
+        let manager = SnapshotProvider::new(snap_path.path());
+        manager.update_index().unwrap();
+        assert!(matches!(manager.best_block_number(), Err(ProviderError::BestBlockNotFound)));
+
+        // The index only depends on the snapshot file names
+        for (segment, block_range, tx_range) in [
+            (SnapshotSegment::Headers, 0..=9, 0..=9),
+            (SnapshotSegment::Headers, 10..=19, 10..=19),
+            (SnapshotSegment::Transactions, 0..=9, 0..=29),
+        ] {
+            std::fs::File::create(snap_path.path().join(segment.filename(&block_range, &tx_range)))
+                .unwrap();
+        }
+
+        manager.update_index().unwrap();
+        assert_eq!(manager.best_block_number().unwrap(), 19);
+    }
+
+    #[test]
+    fn test_snap_db_fallback() {
+        let segment = SnapshotSegment::Headers;
+        let factory = create_test_provider_factory();
+        let snap_path = tempfile::tempdir().unwrap();
+
+        // Blocks below 10 are only in the database
+        let headers = random_header_range(&mut generators::rng(), 0..20, B256::random());
+        let mut provider_rw = factory.provider_rw().unwrap();
+        let tx = provider_rw.tx_mut();
+        for header in &headers {
+            tx.put::<CanonicalHeaders>(header.number, header.hash()).unwrap();
+            tx.put::<Headers>(header.number, header.header.clone()).unwrap();
+            tx.put::<HeaderTD>(header.number, U256::from(header.number).into()).unwrap();
+            tx.put::<HeaderNumbers>(header.hash(), header.number).unwrap();
+        }
+        provider_rw.commit().unwrap();
+
+        let range = 10..=19;
+        {
+            let provider = factory.provider().unwrap();
+            let mut nippy_jar = NippyJar::new(
+                3,
+                &snap_path.path().join(segment.filename(&range, &range)),
+                SegmentHeader::new(range.clone(), range.clone(), segment),
+            )
+            .with_cuckoo_filter(10)
+            .with_fmph();
+            create_snapshot_T1_T2_T3::<
+                Headers,
+                HeaderTD,
+                CanonicalHeaders,
+                BlockNumber,
+                SegmentHeader,
+            >(
+                provider.tx_ref(),
+                range,
+                None,
+                None::<Vec<std::vec::IntoIter<Vec<u8>>>>,
+                Some(
+                    headers[10..].iter().map(|header| -> ColumnResult<B256> { Ok(header.hash()) }),
+                ),
+                10,
+                &mut nippy_jar,
+            )
+            .unwrap();
+        }
+
+        let db_provider = factory.provider().unwrap();
+        let manager = SnapshotProvider::new(snap_path.path());
+        manager.update_index().unwrap();
+        assert!(matches!(
+            manager.header_by_number(5),
+            Err(ProviderError::SnapshotBlockBelowLowest(SnapshotSegment::Headers, 5, 10))
+        ));
+
+        // Blocks below the snapshot range are served by the database
+        let provider = manager.with_fallback(&db_provider);
+        for header in headers.iter().step_by(3) {
+            let num = header.number;
+            assert_eq!(provider.header_by_number(num).unwrap(), Some(header.header.clone()));
+            assert_eq!(provider.sealed_header(num).unwrap(), Some(header.clone()));
+            assert_eq!(provider.block_hash(num).unwrap(), Some(header.hash()));
+            assert_eq!(provider.header_td_by_number(num).unwrap(), Some(U256::from(num)));
+            assert_eq!(provider.header(&header.hash()).unwrap(), Some(header.header.clone()));
+        }
+
+        // Blocks in neither are missing
+        assert_eq!(provider.header_by_number(20).unwrap(), None);
+        assert_eq!(provider.header(&B256::random()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_snap_warm() {
+        let row_count = 100u64;
+        let segment = SnapshotSegment::Transactions;
+
+        let factory = create_test_provider_factory();
+        let snap_path = tempfile::tempdir().unwrap();
+
+        let mut provider_rw = factory.provider_rw().unwrap();
+        let tx = provider_rw.tx_mut();
+        let mut rng = generators::rng();
+        for tx_num in 0..2 * row_count {
+            tx.put::<Transactions>(tx_num, random_signed_tx(&mut rng).into()).unwrap();
+        }
+        provider_rw.commit().unwrap();
+
+        // two snapshots of `row_count` transactions each
+        let provider = factory.provider().unwrap();
+        for (block_range, tx_range) in
+            [(0..=1, 0..=(row_count - 1)), (2..=3, row_count..=(2 * row_count - 1))]
+        {
+            let mut nippy_jar = NippyJar::new(
+                1,
+                &snap_path.path().join(segment.filename(&block_range, &tx_range)),
+                SegmentHeader::new(block_range, tx_range.clone(), segment),
+            );
+            create_snapshot_T1::<Transactions, TxNumber, SegmentHeader>(
+                provider.tx_ref(),
+                tx_range,
+                None,
+                None::<Vec<std::vec::IntoIter<Vec<u8>>>>,
+                None::<std::vec::IntoIter<ColumnResult<B256>>>,
+                row_count as usize,
+                &mut nippy_jar,
+            )
+            .unwrap();
+        }
+
+        let manager = SnapshotProvider::new(snap_path.path()).with_mmap_advice(true);
+        manager.update_index().unwrap();
+
+        // spans both snapshots
+        let range = row_count / 2..row_count * 3 / 2;
+        manager.warm(segment, range.clone()).unwrap();
+        assert_eq!(
+            manager.transactions_by_tx_range(range.clone()).unwrap(),
+            provider.transactions_by_tx_range(range).unwrap()
+        );
+
+        // past the highest snapshot
+        manager.warm(segment, row_count..).unwrap();
+        assert_eq!(
+            manager.transactions_by_tx_range(row_count..).unwrap(),
+            provider.transactions_by_tx_range(row_count..2 * row_count).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_snap_checksum() {
+        let row_count = 20u64;
+        let block_range = 0..=1;
+        let tx_range = 0..=(row_count - 1);
+        let segment = SnapshotSegment::Transactions;
+
+        let factory = create_test_provider_factory();
+        let snap_path = tempfile::tempdir().unwrap();
+        let snap_file = snap_path.path().join(segment.filename(&block_range, &tx_range));
+
+        let mut provider_rw = factory.provider_rw().unwrap();
+        let tx = provider_rw.tx_mut();
+        let mut rng = generators::rng();
+        for tx_num in tx_range.clone() {
+            tx.put::<Transactions>(tx_num, random_signed_tx(&mut rng).into()).unwrap();
+        }
+        provider_rw.commit().unwrap();
+
+        {
+            let provider = factory.provider().unwrap();
+            let mut nippy_jar = NippyJar::new(
+                1,
+                snap_file.as_path(),
+                SegmentHeader::new(block_range, tx_range, segment),
+            )
+            .with_zstd(false, 0);
+            create_snapshot_T1::<Transactions, TxNumber, SegmentHeader>(
+                provider.tx_ref(),
+                0..=(row_count - 1),
+                None,
+                None::<Vec<std::vec::IntoIter<Vec<u8>>>>,
+                None::<std::vec::IntoIter<ColumnResult<B256>>>,
+                row_count as usize,
+                &mut nippy_jar,
+            )
+            .unwrap();
+        }
+
+        // The checksum is recorded when the snapshot is created
+        let jar = NippyJar::<SegmentHeader>::load(&snap_file).unwrap();
+        assert_eq!(jar.checksum(), Some(jar.compute_checksum().unwrap()));
+        drop(jar);
+
+        let db_provider = factory.provider().unwrap();
+        let manager = SnapshotProvider::new(snap_path.path()).with_checksum_verification(true);
+        manager.update_index().unwrap();
+        assert_eq!(
+            manager.transaction_by_id(0).unwrap(),
+            db_provider.transaction_by_id(0).unwrap()
+        );
+        drop(manager);
+
+        // Flip a byte of the last row
+        let mut bytes = std::fs::read(&snap_file).unwrap();
+        *bytes.last_mut().unwrap() ^= 0xff;
+        std::fs::write(&snap_file, bytes).unwrap();
+
+        let manager = SnapshotProvider::new(snap_path.path()).with_checksum_verification(true);
+        manager.update_index().unwrap();
+        assert!(matches!(
+            manager.transaction_by_id(0),
+            Err(ProviderError::SnapshotChecksumMismatch(SnapshotSegment::Transactions, path))
+                if path == snap_file
+        ));
+
+        // Without verification, the intact rows are still served
+        let manager = SnapshotProvider::new(snap_path.path());
+        manager.update_index().unwrap();
+        assert_eq!(
+            manager.transaction_by_id(0).unwrap(),
+            db_provider.transaction_by_id(0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_snap_out_of_coverage() {
+        let snap_path = tempfile::tempdir().unwrap();
+
+        // The index only depends on the snapshot file names. Blocks below 10 were pruned, and
+        // there is a gap between the two snapshots.
+        for (block_range, tx_range) in [(10..=19, 100..=199), (30..=39, 300..=399)] {
+            for segment in [SnapshotSegment::Headers, SnapshotSegment::Transactions] {
+                std::fs::File::create(
+                    snap_path.path().join(segment.filename(&block_range, &tx_range)),
+                )
+                .unwrap();
+            }
+        }
+        let manager = SnapshotProvider::new(snap_path.path());
+        manager.update_index().unwrap();
+
+        let segment = SnapshotSegment::Transactions;
+        let block = |num| manager.get_segment_provider_from_block(segment, num, None).err();
+        let tx = |num| manager.get_segment_provider_from_transaction(segment, num, None).err();
+
+        // Below the lowest snapshot
+        assert!(matches!(
+            block(5),
+            Some(ProviderError::SnapshotBlockBelowLowest(SnapshotSegment::Transactions, 5, 10))
+        ));
+        assert!(matches!(
+            tx(0),
+            Some(ProviderError::SnapshotTxBelowLowest(SnapshotSegment::Transactions, 0, 100))
+        ));
+
+        // In the gap between the snapshots
+        assert!(matches!(
+            block(25),
+            Some(ProviderError::SnapshotBlockInGap(SnapshotSegment::Transactions, 25))
+        ));
+        assert!(matches!(
+            tx(299),
+            Some(ProviderError::SnapshotTxInGap(SnapshotSegment::Transactions, 299))
+        ));
+
+        // Above the highest snapshot
+        assert!(matches!(
+            block(40),
+            Some(ProviderError::MissingSnapshotBlock(SnapshotSegment::Transactions, 40))
+        ));
+        assert!(matches!(
+            tx(400),
+            Some(ProviderError::MissingSnapshotTx(SnapshotSegment::Transactions, 400))
+        ));
+
+        // A segment without snapshots
+        assert!(matches!(
+            manager.get_segment_provider_from_block(SnapshotSegment::Receipts, 15, None).err(),
+            Some(ProviderError::MissingSnapshotBlock(SnapshotSegment::Receipts, 15))
+        ));
+
+        // Senders of transactions that no snapshot holds are unknown
+        for tx_num in [0, 250, 400] {
+            assert_eq!(manager.transaction_sender(tx_num).unwrap(), None);
+        }
+
+        // Same for the headers of blocks that no snapshot holds
+        for block in [5, 25, 40] {
+            assert_eq!(manager.sealed_header(block).unwrap(), None);
+        }
+
+        // Covered numbers are routed to their snapshot, which fails to load since it's empty
+        for (block, tx) in [(10, 100), (19, 199), (30, 300), (39, 399)] {
+            assert!(matches!(
+                manager.get_segment_provider_from_block(segment, block, None).err(),
+                Some(ProviderError::NippyJar(_))
+            ));
+            assert!(matches!(
+                manager.get_segment_provider_from_transaction(segment, tx, None).err(),
+                Some(ProviderError::NippyJar(_))
+            ));
+        }
+
+        // Range queries skip the numbers that no snapshot holds, and only load the snapshots
+        // that overlap the range
+        for tx_range in [0..100, 200..300, 400..500] {
+            assert_eq!(manager.transactions_by_tx_range(tx_range).unwrap(), vec![]);
+        }
+        for block_range in [0..10, 20..30, 40..50] {
+            assert_eq!(manager.transactions_by_block_range(block_range).unwrap(), vec![]);
+        }
+        assert!(matches!(
+            manager.transactions_by_tx_range(150..250).err(),
+            Some(ProviderError::NippyJar(_))
+        ));
+        assert!(matches!(
+            manager.transactions_by_block_range(20..35).err(),
+            Some(ProviderError::NippyJar(_))
+        ));
+    }
+
+    #[test]
+    fn test_snap_transaction_sender() {
+        let row_count = 20u64;
+        let factory = create_test_provider_factory();
+
+        // Snapshots with and without a senders column
+        for with_senders in [false, true] {
+            let snap_path = tempfile::tempdir().unwrap();
+            create_tx_snapshot(&factory, snap_path.path(), row_count, with_senders, false);
+
+            // Compare senders from the snapshot against the database
+            let db_provider = factory.provider().unwrap();
+            let manager = SnapshotProvider::new(snap_path.path());
+            manager.update_index().unwrap();
+
+            for tx_num in [0, 1, row_count / 2, row_count - 1] {
+                let sender = db_provider.transaction_sender(tx_num).unwrap();
+                assert!(sender.is_some());
+                assert_eq!(sender, manager.transaction_sender(tx_num).unwrap());
+            }
+
+            // Out of range
+            assert_eq!(manager.transaction_sender(row_count).unwrap(), None);
+        }
+    }
+
+    #[test]
+    fn test_snap_transactions_with_senders_by_tx_range() {
+        let row_count = 20u64;
+        let factory = create_test_provider_factory();
+
+        // Snapshots with and without a senders column
+        for with_senders in [false, true] {
+            let snap_path = tempfile::tempdir().unwrap();
+            create_tx_snapshot(&factory, snap_path.path(), row_count, with_senders, false);
+
+            let manager = SnapshotProvider::new(snap_path.path());
+            manager.update_index().unwrap();
+
+            // Compare against reading transactions and senders separately
+            let range = 3..row_count - 2;
+            let pairs = manager.transactions_with_senders_by_tx_range(range.clone()).unwrap();
+            {
+                let jar_provider = manager
+                    .get_segment_provider_from_transaction(SnapshotSegment::Transactions, 0, None)
+                    .unwrap();
+                let transactions = jar_provider.transactions_by_tx_range(range.clone()).unwrap();
+                let senders = jar_provider.senders_by_tx_range(range).unwrap();
+                assert_eq!(
+                    pairs,
+                    transactions
+                        .into_iter()
+                        .map(|tx| tx.with_hash())
+                        .zip(senders)
+                        .collect::<Vec<_>>()
+                );
+            }
+
+            // Transactions past the highest snapshot are ignored
+            let all = manager.transactions_with_senders_by_tx_range(0..row_count + 5).unwrap();
+            assert_eq!(all.len(), row_count as usize);
+            let db_provider = factory.provider().unwrap();
+            for (tx_num, (transaction, sender)) in all.into_iter().enumerate() {
+                let tx_num = tx_num as TxNumber;
+                assert_eq!(Some(transaction), db_provider.transaction_by_id(tx_num).unwrap());
+                assert_eq!(Some(sender), db_provider.transaction_sender(tx_num).unwrap());
+            }
+            assert_eq!(
+                manager.transactions_by_tx_range(0..row_count + 5).unwrap(),
+                db_provider.transactions_by_tx_range(0..row_count).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_snap_transactions_by_block_range() {
+        // Two snapshots, with the number of transactions of each of their blocks
+        let snapshots = [(0..=2, 0..=5, vec![2, 0, 4]), (3..=4, 6..=9, vec![3, 1])];
+        let row_count = 10u64;
+
+        // Data sources
+        let factory = create_test_provider_factory();
+        let snap_path = tempfile::tempdir().unwrap();
+
+        let mut rng = generators::rng();
+        let transactions = (0..row_count).map(|_| random_signed_tx(&mut rng)).collect::<Vec<_>>();
+        let mut provider_rw = factory.provider_rw().unwrap();
+        let tx = provider_rw.tx_mut();
+        for (tx_num, transaction) in transactions.iter().enumerate() {
+            tx.put::<Transactions>(tx_num as TxNumber, transaction.clone().into()).unwrap();
+        }
+        provider_rw.commit().unwrap();
+
+        // Create Snapshots
+        for (block_range, tx_range, tx_counts) in snapshots {
+            let segment = SnapshotSegment::Transactions;
+            let segment_header = SegmentHeader::new(block_range.clone(), tx_range.clone(), segment)
+                .with_block_tx_counts(tx_counts);
+            let mut nippy_jar = NippyJar::new(
+                1,
+                &snap_path.path().join(segment.filename(&block_range, &tx_range)),
+                segment_header,
+            );
+
+            let provider = factory.provider().unwrap();
+            create_snapshot_T1::<Transactions, TxNumber, SegmentHeader>(
+                provider.tx_ref(),
+                tx_range.clone(),
+                None,
+                None::<Vec<std::vec::IntoIter<Vec<u8>>>>,
+                None::<std::vec::IntoIter<ColumnResult<B256>>>,
+                (tx_range.end() - tx_range.start() + 1) as usize,
+                &mut nippy_jar,
+            )
+            .unwrap();
+        }
+
+        let manager = SnapshotProvider::new(snap_path.path());
+        manager.update_index().unwrap();
+
+        // Blocks are grouped across the snapshot boundary
+        assert_eq!(
+            manager.transactions_by_block_range(1..4).unwrap(),
+            vec![vec![], transactions[2..6].to_vec(), transactions[6..9].to_vec()]
+        );
+
+        // Blocks past the highest snapshot are ignored
+        assert_eq!(
+            manager.transactions_by_block_range(0..10).unwrap(),
+            vec![
+                transactions[0..2].to_vec(),
+                vec![],
+                transactions[2..6].to_vec(),
+                transactions[6..9].to_vec(),
+                transactions[9..10].to_vec(),
+            ]
+        );
+
+        // The transaction counts of appended blocks are read from the database
+        let appended = (0..4).map(|_| random_signed_tx(&mut rng)).collect::<Vec<_>>();
+        let mut provider_rw = factory.provider_rw().unwrap();
+        let tx = provider_rw.tx_mut();
+        for (tx_num, transaction) in (row_count..).zip(appended.iter()) {
+            tx.put::<Transactions>(tx_num, transaction.clone().into()).unwrap();
+        }
+        tx.put::<BlockBodyIndices>(5, StoredBlockBodyIndices { first_tx_num: 10, tx_count: 0 })
+            .unwrap();
+        tx.put::<BlockBodyIndices>(6, StoredBlockBodyIndices { first_tx_num: 10, tx_count: 4 })
+            .unwrap();
+        provider_rw.commit().unwrap();
+
+        let segment = SnapshotSegment::Transactions;
+        let mut nippy_jar = NippyJar::<SegmentHeader>::load(
+            &snap_path.path().join(segment.filename(&(3..=4), &(6..=9))),
+        )
+        .unwrap();
+        let provider = factory.provider().unwrap();
+        append_snapshot_T1::<Transactions>(
+            provider.tx_ref(),
+            5..=6,
+            10..=13,
+            None,
+            None::<std::vec::IntoIter<ColumnResult<B256>>>,
+            appended.len(),
+            &mut nippy_jar,
+        )
+        .unwrap();
+        assert_eq!(
+            nippy_jar.data_path(),
+            snap_path.path().join(segment.filename(&(3..=6), &(6..=13)))
+        );
+        assert_eq!(nippy_jar.user_header().tx_range_for_block(6), Some(10..14));
+
+        let manager = SnapshotProvider::new(snap_path.path());
+        manager.update_index().unwrap();
+        assert_eq!(
+            manager.transactions_by_block_range(4..7).unwrap(),
+            vec![transactions[9..10].to_vec(), vec![], appended]
+        );
+    }
+
+    #[test]
+    fn test_snap_concurrent_transaction_by_hash() {
+        let factory = create_test_provider_factory();
+        let snap_path = tempfile::tempdir().unwrap();
+        let transactions = create_tx_snapshot(&factory, snap_path.path(), 40, false, true);
+
+        let manager = SnapshotProvider::new(snap_path.path());
+        manager.update_index().unwrap();
+
+        // Every thread looks up all transactions, starting from a different one, through the same
+        // provider.
+        std::thread::scope(|scope| {
+            for offset in 0..8 {
+                let manager = &manager;
+                let transactions = &transactions;
+                scope.spawn(move || {
+                    for round in 0..transactions.len() {
+                        let expected = &transactions[(offset * 5 + round) % transactions.len()];
+                        assert_eq!(
+                            manager.transaction_by_hash(expected.hash()).unwrap().as_ref(),
+                            Some(expected)
+                        );
+                    }
+                    assert_eq!(manager.transaction_by_hash(B256::random()).unwrap(), None);
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn test_snap_transactions_by_ids() {
+        let row_count = 20u64;
+        let factory = create_test_provider_factory();
+        let snap_path = tempfile::tempdir().unwrap();
+        create_tx_snapshot(&factory, snap_path.path(), row_count, false, false);
+
+        let db_provider = factory.provider().unwrap();
+        let manager = SnapshotProvider::new(snap_path.path());
+        manager.update_index().unwrap();
+
+        // Out of order, repeated and out of range ids
+        let ids = [row_count - 1, 3, row_count + 5, 0, 3, row_count, 10];
+        let transactions = manager.transactions_by_ids(&ids).unwrap();
+        assert_eq!(transactions.len(), ids.len());
+
+        for (id, transaction) in ids.into_iter().zip(transactions) {
+            assert_eq!(transaction, db_provider.transaction_by_id(id).unwrap());
+            assert_eq!(transaction.is_some(), id < row_count);
+        }
+    }
+
+    #[test]
+    fn test_snap_cursor_number() {
+        // Ranges, not starting at zero so that the snapshot start offsets are exercised
+        let row_count = 10u64;
+        let block_range = 10..=(10 + row_count - 1);
+        let tx_range = 30..=(30 + row_count - 1);
+
+        // Data sources
+        let factory = create_test_provider_factory();
+        let snap_path = tempfile::tempdir().unwrap();
+        let mut rng = generators::rng();
+        let headers = random_header_range(
+            &mut rng,
+            *block_range.start()..(*block_range.end() + 1),
+            B256::random(),
+        );
+        let transactions = tx_range.clone().map(|_| random_signed_tx(&mut rng)).collect::<Vec<_>>();
+
+        let mut provider_rw = factory.provider_rw().unwrap();
+        let tx = provider_rw.tx_mut();
+        for header in &headers {
+            tx.put::<CanonicalHeaders>(header.number, header.hash()).unwrap();
+            tx.put::<Headers>(header.number, header.clone().unseal()).unwrap();
+            tx.put::<HeaderTD>(header.number, header.difficulty.into()).unwrap();
+        }
+        for (tx_num, transaction) in tx_range.clone().zip(&transactions) {
+            tx.put::<Transactions>(tx_num, transaction.clone().into()).unwrap();
+        }
+        provider_rw.commit().unwrap();
+
+        // Create Snapshots
+        {
+            let provider = factory.provider().unwrap();
+            let none_vec = None::<Vec<std::vec::IntoIter<Vec<u8>>>>;
+
+            let snap_file =
+                snap_path.path().join(SnapshotSegment::Headers.filename(&block_range, &tx_range));
+            let segment_header =
+                SegmentHeader::new(block_range.clone(), tx_range.clone(), SnapshotSegment::Headers);
+            let mut nippy_jar = NippyJar::new(3, snap_file.as_path(), segment_header)
+                .with_cuckoo_filter(row_count as usize + 10)
+                .with_fmph();
+            let hashes = headers.iter().map(|header| Ok(header.hash()));
+            create_snapshot_T1_T2_T3::<
+                Headers,
+                HeaderTD,
+                CanonicalHeaders,
+                BlockNumber,
+                SegmentHeader,
+            >(
+                provider.tx_ref(),
+                block_range.clone(),
+                None,
+                none_vec.clone(),
+                Some(hashes),
+                row_count as usize,
+                &mut nippy_jar,
+            )
+            .unwrap();
+
+            let snap_file = snap_path
+                .path()
+                .join(SnapshotSegment::Transactions.filename(&block_range, &tx_range));
+            let segment_header = SegmentHeader::new(
+                block_range.clone(),
+                tx_range.clone(),
+                SnapshotSegment::Transactions,
+            );
+            let mut nippy_jar = NippyJar::new(1, snap_file.as_path(), segment_header)
+                .with_cuckoo_filter(row_count as usize + 10)
+                .with_fmph();
+            let hashes = transactions.iter().map(|transaction| Ok(transaction.hash()));
+            create_snapshot_T1::<Transactions, TxNumber, SegmentHeader>(
+                provider.tx_ref(),
+                tx_range.clone(),
+                None,
+                none_vec,
+                Some(hashes),
+                row_count as usize,
+                &mut nippy_jar,
+            )
+            .unwrap();
+        }
+
+        let manager = SnapshotProvider::new(snap_path.path());
+        manager.update_index().unwrap();
+
+        // The number of the row a lookup by hash found, at the first and last rows of the jars
+        for index in [0, row_count as usize - 1] {
+            let header = &headers[index];
+            assert_eq!(manager.block_number(header.hash()).unwrap(), Some(header.number));
+
+            let tx_num = *tx_range.start() + index as u64;
+            assert_eq!(manager.transaction_id(transactions[index].hash()).unwrap(), Some(tx_num));
+        }
+        assert_eq!(manager.block_number(B256::random()).unwrap(), None);
+        assert_eq!(manager.transaction_id(B256::random()).unwrap(), None);
+
+        // A headers snapshot whose name claims blocks below its first row. The iterator stops at
+        // the first missing header, and doesn't resume at the rows past it.
+        let gap_path = tempfile::tempdir().unwrap();
+        let headers_file = SnapshotSegment::Headers.filename(&block_range, &tx_range);
+        let gap_file = SnapshotSegment::Headers.filename(&(0..=*block_range.end()), &tx_range);
+        for extension in ["", ".idx"] {
+            std::fs::copy(
+                snap_path.path().join(format!("{headers_file}{extension}")),
+                gap_path.path().join(format!("{gap_file}{extension}")),
+            )
+            .unwrap();
+        }
+        let manager = SnapshotProvider::new(gap_path.path());
+        manager.update_index().unwrap();
+
+        let mut hashes = manager.canonical_hashes_iter(0, *block_range.end() + 1);
+        assert!(hashes.next().is_none());
+        assert!(hashes.next().is_none());
+        assert_eq!(
+            manager
+                .canonical_hashes_iter(*block_range.start(), *block_range.end() + 1)
+                .collect::<ProviderResult<Vec<_>>>()
+                .unwrap(),
+            headers.iter().map(|header| header.hash()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_snap_decompressor_pool() {
+        // Ranges
+        let row_count = 100u64;
+        let block_range = 0..=1;
+        let tx_range = 0..=(row_count - 1);
+        let segment_header = SegmentHeader::new(
+            block_range.clone(),
+            tx_range.clone(),
+            SnapshotSegment::Transactions,
+        );
+
+        // Data sources
+        let factory = create_test_provider_factory();
+        let snap_path = tempfile::tempdir().unwrap();
+        let snap_file =
+            snap_path.path().join(SnapshotSegment::Transactions.filename(&block_range, &tx_range));
+
+        let mut provider_rw = factory.provider_rw().unwrap();
+        let tx = provider_rw.tx_mut();
+        let mut rng = generators::rng();
+        for tx_num in tx_range.clone() {
+            tx.put::<Transactions>(tx_num, random_signed_tx(&mut rng).into()).unwrap();
+        }
+        provider_rw.commit().unwrap();
+
+        // Create Snapshot, compressed with a dictionary
+        {
+            let mut nippy_jar =
+                NippyJar::new(1, snap_file.as_path(), segment_header).with_zstd(true, 5000);
+
+            let provider = factory.provider().unwrap();
+            let tx = provider.tx_ref();
+            let mut cursor = tx.cursor_read::<RawTable<Transactions>>().unwrap();
+            let dict_set = cursor
+                .walk(None)
+                .unwrap()
+                .map(|row| row.unwrap().1.into_value())
+                .collect::<Vec<_>>();
+
+            create_snapshot_T1::<Transactions, TxNumber, SegmentHeader>(
+                tx,
+                tx_range,
+                None,
+                Some(vec![dict_set.into_iter()]),
+                None::<std::vec::IntoIter<ColumnResult<B256>>>,
+                row_count as usize,
+                &mut nippy_jar,
+            )
+            .unwrap();
+        }
+
+        let db_provider = factory.provider().unwrap();
+        let expected = (0..row_count)
+            .map(|tx_num| db_provider.transaction_by_id(tx_num).unwrap().unwrap())
+            .collect::<Vec<_>>();
+
+        let manager = SnapshotProvider::new(snap_path.path());
+        manager.update_index().unwrap();
+
+        let readers = 4;
+        manager
+            .build_decompressors(SnapshotSegment::Transactions, 0, readers, |jar_provider, pool| {
+                assert_eq!(pool.len(), readers);
+                assert!(pool.iter().all(|decompressors| decompressors.len() == 1));
+
+                // Every reader takes a set of decompressors from the pool
+                std::thread::scope(|scope| {
+                    let handles = pool
+                        .into_iter()
+                        .enumerate()
+                        .map(|(reader, decompressors)| {
+                            let expected = &expected;
+                            scope.spawn(move || {
+                                let mut cursor =
+                                    jar_provider.cursor_with_decompressors(decompressors).unwrap();
+                                for tx_num in (reader..expected.len()).step_by(readers) {
+                                    let transaction = cursor
+                                        .get_one::<TransactionMask<TransactionSignedNoHash>>(
+                                            (tx_num as u64).into(),
+                                        )
+                                        .unwrap()
+                                        .map(|tx| tx.with_hash());
+                                    assert_eq!(transaction.as_ref(), Some(&expected[tx_num]));
+                                }
+                            })
+                        })
+                        .collect::<Vec<_>>();
+
+                    for handle in handles {
+                        handle.join().unwrap();
+                    }
+                });
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    /// Inserts `row_count` random transactions and their senders into the database, and creates a
+    /// transactions snapshot of them in `dir`. The snapshot has a senders column if `with_senders`
+    /// is set, and a filter and PHF over the transaction hashes if `with_keys` is set.
+    ///
+    /// Returns the inserted transactions.
+    fn create_tx_snapshot<DB: Database>(
+        factory: &ProviderFactory<DB>,
+        dir: &Path,
+        row_count: u64,
+        with_senders: bool,
+        with_keys: bool,
+    ) -> Vec<TransactionSigned> {
+        let block_range = 0..=1;
+        let tx_range = 0..=(row_count - 1);
+        let segment = SnapshotSegment::Transactions;
+
+        let mut rng = generators::rng();
+        let transactions = tx_range.clone().map(|_| random_signed_tx(&mut rng)).collect::<Vec<_>>();
+        let mut provider_rw = factory.provider_rw().unwrap();
+        let tx = provider_rw.tx_mut();
+        for (tx_num, transaction) in tx_range.clone().zip(&transactions) {
+            tx.put::<Transactions>(tx_num, transaction.clone().into()).unwrap();
+            tx.put::<TxSenders>(tx_num, transaction.recover_signer().unwrap()).unwrap();
+        }
+        provider_rw.commit().unwrap();
+
+        let mut segment_header = SegmentHeader::new(block_range.clone(), tx_range.clone(), segment);
+        if with_senders {
+            segment_header = segment_header.with_senders();
+        }
+        let columns = if with_senders { 2 } else { 1 };
+        let mut nippy_jar = NippyJar::new(
+            columns,
+            &dir.join(segment.filename(&block_range, &tx_range)),
+            segment_header,
+        )
+        .with_zstd(false, 0);
+        if with_keys {
+            nippy_jar = nippy_jar.with_cuckoo_filter(row_count as usize).with_fmph();
+        }
+
+        let provider = factory.provider().unwrap();
+        let none_vec = None::<Vec<std::vec::IntoIter<Vec<u8>>>>;
+        let keys = with_keys
+            .then(|| transactions.iter().map(|tx| -> ColumnResult<B256> { Ok(tx.hash()) }));
+        if with_senders {
+            create_snapshot_T1_T2::<Transactions, TxSenders, TxNumber, SegmentHeader>(
+                provider.tx_ref(),
+                tx_range,
+                None,
+                none_vec,
+                keys,
+                row_count as usize,
+                &mut nippy_jar,
+            )
+            .unwrap();
+        } else {
+            create_snapshot_T1::<Transactions, TxNumber, SegmentHeader>(
+                provider.tx_ref(),
+                tx_range,
+                None,
+                none_vec,
+                keys,
+                row_count as usize,
+                &mut nippy_jar,
+            )
+            .unwrap();
+        }
+
+        transactions
     }
 }
@@ -456,10 +456,13 @@ impl BlockReader for MockEthProvider {
 
     fn block_with_senders(
         &self,
-        _id: BlockHashOrNumber,
+        id: BlockHashOrNumber,
         _transaction_kind: TransactionVariant,
     ) -> ProviderResult<Option<BlockWithSenders>> {
-        Ok(None)
+        Ok(self.block(id)?.and_then(|block| {
+            let senders = block.senders()?;
+            Some(block.with_senders(senders))
+        }))
     }
 
     fn block_range(&self, range: RangeInclusive<BlockNumber>) -> ProviderResult<Vec<Block>> {
@@ -0,0 +1,135 @@
+use super::HashedAccountCursor;
+use reth_primitives::{Account, B256};
+use schnellru::{ByLength, LruMap};
+
+/// A [HashedAccountCursor] wrapper that keeps a small LRU cache of recent `seek` results.
+///
+/// Repeated seeks of the same key are answered from the cache without descending the underlying
+/// b-tree. Since a cached seek does not reposition the underlying cursor, the next call to
+/// [HashedAccountCursor::next] first re-seeks to the last requested key. The cache is cleared
+/// every time the underlying cursor is moved by `next`.
+pub struct CachingHashedAccountCursor<C> {
+    /// The underlying cursor.
+    cursor: C,
+    /// Recent seek results by requested key.
+    cache: LruMap<B256, Option<(B256, Account)>>,
+    /// The key of the last seek that was answered from the cache. If set, the underlying cursor
+    /// is not positioned at the entry returned to the caller.
+    pending_seek: Option<B256>,
+}
+
+impl<C> CachingHashedAccountCursor<C> {
+    /// Wraps the cursor, caching up to `capacity` seek results.
+    pub fn new(cursor: C, capacity: u32) -> Self {
+        Self { cursor, cache: LruMap::new(ByLength::new(capacity)), pending_seek: None }
+    }
+
+    /// Returns the underlying cursor.
+    pub fn into_inner(self) -> C {
+        self.cursor
+    }
+}
+
+impl<C: std::fmt::Debug> std::fmt::Debug for CachingHashedAccountCursor<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CachingHashedAccountCursor")
+            .field("cursor", &self.cursor)
+            .field("cached", &self.cache.len())
+            .field("pending_seek", &self.pending_seek)
+            .finish()
+    }
+}
+
+impl<C: HashedAccountCursor> HashedAccountCursor for CachingHashedAccountCursor<C> {
+    fn seek(&mut self, key: B256) -> Result<Option<(B256, Account)>, reth_db::DatabaseError> {
+        if let Some(entry) = self.cache.get(&key) {
+            let entry = *entry;
+            self.pending_seek = Some(key);
+            return Ok(entry)
+        }
+
+        let entry = self.cursor.seek(key)?;
+        self.pending_seek = None;
+        self.cache.insert(key, entry);
+        Ok(entry)
+    }
+
+    fn next(&mut self) -> Result<Option<(B256, Account)>, reth_db::DatabaseError> {
+        if let Some(key) = self.pending_seek.take() {
+            self.cursor.seek(key)?;
+        }
+        self.cache.clear();
+        self.cursor.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{
+        cursor::DbCursorRO,
+        database::Database,
+        tables,
+        test_utils::create_test_rw_db,
+        transaction::{DbTx, DbTxMut},
+    };
+
+    /// Counts the seeks reaching the wrapped cursor.
+    struct CountingCursor<C> {
+        cursor: C,
+        seeks: usize,
+    }
+
+    impl<C: DbCursorRO<tables::HashedAccount>> HashedAccountCursor for CountingCursor<C> {
+        fn seek(&mut self, key: B256) -> Result<Option<(B256, Account)>, reth_db::DatabaseError> {
+            self.seeks += 1;
+            DbCursorRO::seek(&mut self.cursor, key)
+        }
+
+        fn next(&mut self) -> Result<Option<(B256, Account)>, reth_db::DatabaseError> {
+            DbCursorRO::next(&mut self.cursor)
+        }
+    }
+
+    #[test]
+    fn caching_account_cursor() {
+        let accounts = Vec::from_iter((1..11).map(|key| {
+            (B256::with_last_byte(key * 2), Account { nonce: key as u64, ..Default::default() })
+        }));
+
+        let db = create_test_rw_db();
+        db.update(|tx| {
+            for (key, account) in accounts.iter() {
+                tx.put::<tables::HashedAccount>(*key, *account).unwrap();
+            }
+        })
+        .unwrap();
+
+        let tx = db.tx().unwrap();
+        let cursor = tx.cursor_read::<tables::HashedAccount>().unwrap();
+        let mut cursor = CachingHashedAccountCursor::new(CountingCursor { cursor, seeks: 0 }, 4);
+
+        // Cache hits return identical data without reaching the database.
+        let first = cursor.seek(B256::with_last_byte(5)).unwrap();
+        assert_eq!(first, Some(accounts[2]));
+        assert_eq!(cursor.seek(B256::with_last_byte(5)).unwrap(), first);
+        assert_eq!(cursor.cursor.seeks, 1);
+
+        // Seeking past the last entry is cached as well.
+        assert_eq!(cursor.seek(B256::with_last_byte(21)).unwrap(), None);
+        assert_eq!(cursor.seek(B256::with_last_byte(21)).unwrap(), None);
+        assert_eq!(cursor.cursor.seeks, 2);
+
+        // The underlying cursor is positioned at the last uncached seek, the next entry must still
+        // follow the cached one.
+        assert_eq!(cursor.seek(B256::with_last_byte(14)).unwrap(), Some(accounts[6]));
+        assert_eq!(cursor.seek(B256::with_last_byte(5)).unwrap(), first);
+        assert_eq!(cursor.cursor.seeks, 3);
+        assert_eq!(cursor.next().unwrap(), Some(accounts[3]));
+        assert_eq!(cursor.next().unwrap(), Some(accounts[4]));
+
+        // Moving the cursor invalidates the cache.
+        assert_eq!(cursor.seek(B256::with_last_byte(5)).unwrap(), first);
+        assert_eq!(cursor.cursor.seeks, 5);
+    }
+}
@@ -30,6 +30,15 @@ where
     fn next(&mut self) -> Result<Option<(B256, Account)>, reth_db::DatabaseError> {
         self.next()
     }
+
+    fn count_range(&mut self, start: B256, end: B256) -> Result<u64, reth_db::DatabaseError> {
+        let mut count = 0;
+        for entry in self.walk_range(start..end)? {
+            entry?;
+            count += 1;
+        }
+        Ok(count)
+    }
 }
 
 impl<C> HashedStorageCursor for C
@@ -40,6 +49,13 @@ where
         Ok(self.seek_exact(key)?.is_none())
     }
 
+    fn seek_first_nonempty(
+        &mut self,
+        key: B256,
+    ) -> Result<Option<StorageEntry>, reth_db::DatabaseError> {
+        self.seek_by_key_subkey(key, B256::ZERO)
+    }
+
     fn seek(
         &mut self,
         key: B256,
@@ -7,6 +7,10 @@ mod default;
 mod post_state;
 pub use post_state::*;
 
+/// Caching wrapper for hashed account cursors.
+mod caching;
+pub use caching::CachingHashedAccountCursor;
+
 /// The factory trait for creating cursors over the hashed state.
 pub trait HashedCursorFactory {
     /// The hashed account cursor type.
@@ -28,6 +32,19 @@ pub trait HashedAccountCursor {
 
     /// Move the cursor to the next entry and return it.
     fn next(&mut self) -> Result<Option<(B256, Account)>, reth_db::DatabaseError>;
+
+    /// Returns the number of accounts with a hashed key in `start..end`.
+    ///
+    /// The cursor is left positioned at the first entry past the range.
+    fn count_range(&mut self, start: B256, end: B256) -> Result<u64, reth_db::DatabaseError> {
+        let mut count = 0;
+        let mut entry = self.seek(start)?;
+        while entry.as_ref().is_some_and(|(key, _)| *key < end) {
+            count += 1;
+            entry = self.next()?;
+        }
+        Ok(count)
+    }
 }
 
 /// The cursor for iterating over hashed storage entries.
@@ -35,6 +52,16 @@ pub trait HashedStorageCursor {
     /// Returns `true` if there are no entries for a given key.
     fn is_storage_empty(&mut self, key: B256) -> Result<bool, reth_db::DatabaseError>;
 
+    /// Position the cursor at the first storage entry for a given key and return it.
+    ///
+    /// Returns `None` if there are no entries for the key. Unlike calling
+    /// [HashedStorageCursor::is_storage_empty] followed by [HashedStorageCursor::seek], emptiness
+    /// is determined by the same lookup that positions the cursor.
+    fn seek_first_nonempty(
+        &mut self,
+        key: B256,
+    ) -> Result<Option<StorageEntry>, reth_db::DatabaseError>;
+
     /// Seek an entry greater or equal to the given key/subkey and position the cursor there.
     fn seek(
         &mut self,
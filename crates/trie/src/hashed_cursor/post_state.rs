@@ -459,6 +459,17 @@ where
         Ok(is_empty)
     }
 
+    /// Seek the first account storage entry for a given hashed key, skipping the entries that are
+    /// zero-valued or wiped in post state.
+    fn seek_first_nonempty(
+        &mut self,
+        account: B256,
+    ) -> Result<Option<StorageEntry>, reth_db::DatabaseError> {
+        // Reset the position in post state, since it only moves forward on seek.
+        self.account = None;
+        self.seek(account, B256::ZERO)
+    }
+
     /// Seek the next account storage entry for a given hashed key pair.
     fn seek(
         &mut self,
@@ -710,6 +721,54 @@ mod tests {
         assert_account_cursor_order(&factory, expected);
     }
 
+    #[test]
+    fn count_accounts_in_range() {
+        // even keys are in db, odd keys are in post state
+        let accounts =
+            Vec::from_iter((1..41).map(|key| (B256::with_last_byte(key), Account::default())));
+        // account 5 is destroyed in post state, and account 10 in db
+        let removed_keys = Vec::from_iter([5, 10].into_iter().map(B256::with_last_byte));
+
+        let db = create_test_rw_db();
+        db.update(|tx| {
+            for (key, account) in accounts.iter().filter(|x| x.0[31] % 2 == 0) {
+                tx.put::<tables::HashedAccount>(*key, *account).unwrap();
+            }
+        })
+        .unwrap();
+
+        let mut hashed_post_state = HashedPostState::default();
+        for (hashed_address, account) in accounts.iter().filter(|x| x.0[31] % 2 != 0) {
+            if !removed_keys.contains(hashed_address) {
+                hashed_post_state.insert_account(*hashed_address, *account);
+            }
+        }
+        for hashed_address in &removed_keys {
+            hashed_post_state.insert_destroyed_account(*hashed_address);
+        }
+        hashed_post_state.sort();
+
+        let key = B256::with_last_byte;
+        let tx = db.tx().unwrap();
+
+        let mut db_cursor = tx.cursor_read::<tables::HashedAccount>().unwrap();
+        assert_eq!(db_cursor.count_range(B256::ZERO, B256::repeat_byte(0xff)).unwrap(), 20);
+        assert_eq!(db_cursor.count_range(key(10), key(20)).unwrap(), 5);
+        assert_eq!(db_cursor.count_range(key(11), key(12)).unwrap(), 0);
+        assert_eq!(db_cursor.count_range(key(41), B256::repeat_byte(0xff)).unwrap(), 0);
+
+        // the post state cursor only seeks forward, so every range uses a new cursor
+        let factory = HashedPostStateCursorFactory::new(&tx, &hashed_post_state);
+        let count =
+            |start, end| factory.hashed_account_cursor().unwrap().count_range(start, end).unwrap();
+        assert_eq!(count(B256::ZERO, B256::repeat_byte(0xff)), 38);
+        assert_eq!(count(key(1), key(10)), 8);
+        assert_eq!(count(key(10), key(20)), 9);
+        assert_eq!(count(key(20), key(20)), 0);
+        assert_eq!(count(key(40), key(41)), 1);
+        assert_eq!(count(key(41), B256::repeat_byte(0xff)), 0);
+    }
+
     #[test]
     fn post_state_accounts_take_precedence() {
         let accounts = Vec::from_iter((1..10).map(|key| {
@@ -856,6 +915,83 @@ mod tests {
         }
     }
 
+    #[test]
+    fn storage_seek_first_nonempty() {
+        let address = B256::random();
+        let db_storage =
+            BTreeMap::from_iter((1..11).map(|key| (B256::with_last_byte(key), U256::from(key))));
+
+        let db = create_test_rw_db();
+        db.update(|tx| {
+            for (slot, value) in db_storage.iter() {
+                tx.put::<tables::HashedStorage>(
+                    address,
+                    StorageEntry { key: *slot, value: *value },
+                )
+                .unwrap();
+            }
+        })
+        .unwrap();
+
+        // database storage
+        {
+            let tx = db.tx().unwrap();
+            let mut cursor = (&tx).hashed_storage_cursor().unwrap();
+            assert_eq!(
+                cursor.seek_first_nonempty(address).unwrap(),
+                Some(StorageEntry { key: B256::with_last_byte(1), value: U256::from(1) })
+            );
+            assert_eq!(cursor.seek_first_nonempty(B256::random()).unwrap(), None);
+        }
+
+        // storage emptied in post state without being wiped
+        {
+            let mut hashed_storage = HashedStorage::new(false);
+            for slot in db_storage.keys() {
+                hashed_storage.insert_zero_valued_slot(*slot);
+            }
+
+            let mut hashed_post_state = HashedPostState::default();
+            hashed_post_state.insert_hashed_storage(address, hashed_storage);
+            let hashed_post_state = hashed_post_state.sorted();
+
+            let tx = db.tx().unwrap();
+            let factory = HashedPostStateCursorFactory::new(&tx, &hashed_post_state);
+            let mut cursor = factory.hashed_storage_cursor().unwrap();
+            // the database entries are still there, but all of them are zeroed
+            assert!(!cursor.is_storage_empty(address).unwrap());
+            assert_eq!(cursor.seek_first_nonempty(address).unwrap(), None);
+        }
+
+        // all but the last database slot emptied in post state, after the cursor moved past the
+        // first post state slot
+        {
+            let last_slot = B256::with_last_byte(10);
+            let mut hashed_storage = HashedStorage::new(false);
+            for slot in db_storage.keys().filter(|slot| **slot != last_slot) {
+                hashed_storage.insert_zero_valued_slot(*slot);
+            }
+            hashed_storage.insert_non_zero_valued_storage(B256::ZERO, U256::MAX);
+
+            let mut hashed_post_state = HashedPostState::default();
+            hashed_post_state.insert_hashed_storage(address, hashed_storage);
+            let hashed_post_state = hashed_post_state.sorted();
+
+            let tx = db.tx().unwrap();
+            let factory = HashedPostStateCursorFactory::new(&tx, &hashed_post_state);
+            let mut cursor = factory.hashed_storage_cursor().unwrap();
+            assert_eq!(
+                cursor.seek(address, last_slot).unwrap().map(|entry| entry.key),
+                Some(last_slot)
+            );
+
+            let first = StorageEntry { key: B256::ZERO, value: U256::MAX };
+            assert_eq!(cursor.seek_first_nonempty(address).unwrap(), Some(first));
+            assert_eq!(cursor.next().unwrap().map(|entry| entry.key), Some(last_slot));
+            assert_eq!(cursor.next().unwrap(), None);
+        }
+    }
+
     #[test]
     fn storage_cursor_correct_order() {
         let address = B256::random();
@@ -113,7 +113,7 @@ where
         let mut proofs = slots.iter().copied().map(StorageProof::new).collect::<Vec<_>>();
 
         // short circuit on empty storage
-        if hashed_storage_cursor.is_storage_empty(hashed_address)? {
+        if hashed_storage_cursor.seek_first_nonempty(hashed_address)?.is_none() {
             return Ok((EMPTY_ROOT_HASH, proofs))
         }
 
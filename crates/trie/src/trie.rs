@@ -436,7 +436,7 @@ where
         let mut hashed_storage_cursor = self.hashed_cursor_factory.hashed_storage_cursor()?;
 
         // short circuit on empty storage
-        if hashed_storage_cursor.is_storage_empty(self.hashed_address)? {
+        if hashed_storage_cursor.seek_first_nonempty(self.hashed_address)?.is_none() {
             return Ok((
                 EMPTY_ROOT_HASH,
                 0,